@@ -88,7 +88,10 @@ pub fn build_feature_list(manifest_dir: &Path, output_dir: &Path) -> Result<(),
 
     let mut features_rs = BufWriter::new(File::create(output_dir.join("features.rs"))?);
     writeln!(features_rs, "/// An identifier of an engine feature required for parsing and/or matching a particular signature or signature element.")?;
-    writeln!(features_rs, "#[derive(Clone, Debug, Copy, PartialEq)]")?;
+    writeln!(
+        features_rs,
+        "#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]"
+    )?;
     writeln!(features_rs, "pub enum Feature {{")?;
     feature_flevel
         .iter()
@@ -112,6 +115,20 @@ pub fn build_feature_list(manifest_dir: &Path, output_dir: &Path) -> Result<(),
     writeln!(features_rs, "    }}")?;
     writeln!(features_rs, "}}")?;
 
+    // Write out the FLEVEL-to-release-version table for version reporting
+    writeln!(
+        features_rs,
+        "pub(crate) static FLEVEL_VERSIONS: &[(u32, &[&str])] = &["
+    )?;
+    for (flevel, versions) in &flevel_versions {
+        write!(features_rs, "    ({flevel}, &[")?;
+        for version in versions {
+            write!(features_rs, "\"{version}\", ")?;
+        }
+        writeln!(features_rs, "]),")?;
+    }
+    writeln!(features_rs, "];")?;
+
     Ok(())
 }
 
@@ -168,7 +185,7 @@ pub fn load_filetypes(
         writeln!(filetypes_c_input, "#[allow(non_camel_case_types)]")?;
         writeln!(
             filetypes_c_input,
-            "#[derive(Clone, Debug, PartialEq, Display, EnumString, FromPrimitive, ToPrimitive)]"
+            "#[derive(Clone, Debug, PartialEq, Eq, Hash, Display, EnumString, FromPrimitive, ToPrimitive)]"
         )?;
         writeln!(filetypes_c_input, "pub enum FileType {{")?;
         for filetype in filetype_feature_tag.keys() {
@@ -0,0 +1,214 @@
+/*
+ *  Copyright (C) 2024 Cisco Systems, Inc. and/or its affiliates. All rights reserved.
+ *
+ *  This program is free software; you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License version 2 as
+ *  published by the Free Software Foundation.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program; if not, write to the Free Software
+ *  Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston,
+ *  MA 02110-1301, USA.
+ */
+
+//! A stable, versioned JSON schema for audit reports and diagnostics, for
+//! machine consumers (e.g., CI jobs) that must survive crate upgrades.
+//!
+//! # Stability guarantee
+//!
+//! Within a schema major version (the `V1` suffix and the `schema_version`
+//! field), fields and diagnostic codes are only ever *added*, never renamed
+//! or removed.  Consumers should ignore unrecognized fields.  A change that
+//! would break this contract gets a new set of structs (`V2`) instead; the
+//! committed fixture under `test-data/` enforces this in the test suite.
+
+use crate::signature::{SigMeta, SigValidationError, SigWarning, Signature};
+use serde::{Deserialize, Serialize};
+
+/// The schema version written into [`AuditReportV1::schema_version`]
+pub const SCHEMA_VERSION_V1: u32 = 1;
+
+/// The severity of a [`DiagnosticV1`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeverityV1 {
+    /// A validation failure: the signature is malformed or unsatisfiable
+    Error,
+    /// A warning: the signature is well-formed but likely to behave poorly
+    Warning,
+}
+
+/// A single diagnostic raised while auditing a signature
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticV1 {
+    /// A stable, machine-readable code identifying the diagnostic's kind, as
+    /// reported by [`SigWarning::code`] or [`SigValidationError::code`]
+    pub code: String,
+
+    /// Whether this diagnostic is fatal to the signature
+    pub severity: SeverityV1,
+
+    /// The human-readable rendering of the diagnostic.  Unlike `code`, this
+    /// text may be reworded between releases.
+    pub message: String,
+
+    /// The name of the signature the diagnostic applies to
+    pub sig_name: String,
+}
+
+impl DiagnosticV1 {
+    /// Convert a validation error raised for the named signature
+    #[must_use]
+    pub fn from_validation_error(sig_name: &str, err: &SigValidationError) -> Self {
+        Self {
+            code: err.code().to_owned(),
+            severity: SeverityV1::Error,
+            message: err.to_string(),
+            sig_name: sig_name.to_owned(),
+        }
+    }
+
+    /// Convert a warning raised for the named signature
+    #[must_use]
+    pub fn from_warning(sig_name: &str, warning: &SigWarning) -> Self {
+        Self {
+            code: warning.code().to_owned(),
+            severity: SeverityV1::Warning,
+            message: warning.to_string(),
+            sig_name: sig_name.to_owned(),
+        }
+    }
+}
+
+/// An audit report over a set of signatures: every validation error and
+/// warning, in input order, together with the number of signatures examined
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditReportV1 {
+    /// The major version of this schema; always [`SCHEMA_VERSION_V1`] for
+    /// this struct
+    pub schema_version: u32,
+
+    /// How many signatures were audited (including clean ones, which
+    /// contribute no diagnostics)
+    pub signatures_audited: usize,
+
+    /// The diagnostics raised, in the order the signatures were audited
+    pub diagnostics: Vec<DiagnosticV1>,
+}
+
+impl Default for AuditReportV1 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AuditReportV1 {
+    /// Create an empty report
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION_V1,
+            signatures_audited: 0,
+            diagnostics: vec![],
+        }
+    }
+
+    /// Audit a single signature, recording its validation error (if any) and
+    /// warnings
+    pub fn audit_signature(&mut self, sig: &dyn Signature, sigmeta: &SigMeta) {
+        self.signatures_audited += 1;
+        if let Err(err) = sig.validate(sigmeta) {
+            self.diagnostics
+                .push(DiagnosticV1::from_validation_error(sig.name(), &err));
+        }
+        for warning in sig.warnings() {
+            self.diagnostics
+                .push(DiagnosticV1::from_warning(sig.name(), &warning));
+        }
+    }
+
+    /// Serialize this report as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Deserialize a report from JSON
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{signature::parse_from_cvd_with_meta, SigType};
+
+    /// The fixture this report must match is committed; regenerate it (and
+    /// review the diff against the stability guarantee) with
+    /// `UPDATE_AUDIT_FIXTURE=1 cargo test`
+    fn small_audit_run() -> AuditReportV1 {
+        let mut report = AuditReportV1::new();
+        for (sig_type, sig) in [
+            // Clean
+            (
+                SigType::FileHash,
+                &b"44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature"[..],
+            ),
+            // Body matches few distinct byte values
+            (
+                SigType::Logical,
+                &b"Test.Low.Diversity;Engine:51-255,Target:0;(0&1);0000000000;414243"[..],
+            ),
+            // Specified feature level below the computed minimum
+            (
+                SigType::Logical,
+                &b"Test.FLevel.Low;Engine:80-255,Target:0;0;/foobar/"[..],
+            ),
+        ] {
+            let (sig, sigmeta) = parse_from_cvd_with_meta(sig_type, &sig.into()).unwrap();
+            report.audit_signature(sig.as_ref(), &sigmeta);
+        }
+        report
+    }
+
+    #[test]
+    fn golden_audit_report() {
+        let fixture_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test-data/audit-report-v1.json"
+        );
+        let report = small_audit_run();
+        let json = report.to_json().unwrap();
+        if std::env::var_os("UPDATE_AUDIT_FIXTURE").is_some() {
+            std::fs::write(fixture_path, json + "\n").unwrap();
+            return;
+        }
+        let fixture = std::fs::read_to_string(fixture_path).unwrap();
+        assert_eq!(json, fixture.trim_end());
+    }
+
+    /// The committed fixture must always deserialize: fields may be added to
+    /// the schema, but never renamed or removed
+    #[test]
+    fn fixture_deserializes() {
+        let fixture = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test-data/audit-report-v1.json"
+        ))
+        .unwrap();
+        let report = AuditReportV1::from_json(&fixture).unwrap();
+        assert_eq!(report.schema_version, SCHEMA_VERSION_V1);
+        assert_eq!(report.signatures_audited, 3);
+        assert_eq!(report.diagnostics.len(), 2);
+        assert_eq!(report.diagnostics[0].code, "low-byte-diversity");
+        assert_eq!(report.diagnostics[0].severity, SeverityV1::Warning);
+        assert_eq!(report.diagnostics[1].code, "min-flevel-too-low");
+        assert_eq!(report.diagnostics[1].severity, SeverityV1::Error);
+        assert_eq!(report, small_audit_run());
+    }
+}
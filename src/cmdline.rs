@@ -17,7 +17,8 @@
  */
 
 use anyhow::{anyhow, Result};
-use clam_sigutil::SigType;
+use clam_sigutil::{signature::bodysig::MatcherClass, SigType};
+use clap::Parser;
 use std::{
     fs::File,
     io::{BufRead, BufReader, Read},
@@ -25,7 +26,6 @@ use std::{
     str,
     time::{Duration, Instant},
 };
-use clap::Parser;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -175,6 +175,8 @@ fn process_sigs<F: Read>(opt: &Opt, sig_type: SigType, fh: &mut F) -> Result<()>
     let mut line_no = 0;
     let mut sigbuf = vec![];
     let mut err_count = 0;
+    let mut bm_count = 0;
+    let mut ac_count = 0;
 
     let mut fh = BufReader::new(fh);
 
@@ -218,6 +220,13 @@ fn process_sigs<F: Read>(opt: &Opt, sig_type: SigType, fh: &mut F) -> Result<()>
                     println!(" > {:?}", sig.features());
                 }
 
+                for body_sig in sig.body_sigs() {
+                    match body_sig.matcher_class() {
+                        MatcherClass::BoyerMoore => bm_count += 1,
+                        MatcherClass::AhoCorasick => ac_count += 1,
+                    }
+                }
+
                 if opt.validate {
                     if let Err(e) = sig.validate(&sigmeta) {
                         eprintln!(
@@ -262,6 +271,7 @@ fn process_sigs<F: Read>(opt: &Opt, sig_type: SigType, fh: &mut F) -> Result<()>
                 elapsed,
                 Duration::from_nanos((elapsed.as_nanos() / n_records).try_into()?)
             );
+            println!(" - body sig matchers: {bm_count} Boyer-Moore, {ac_count} Aho-Corasick");
         }
     } else {
         eprintln!(" - no records");
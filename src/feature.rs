@@ -42,11 +42,59 @@ pub trait EngineReq {
             .max()
             .map(|start| (start..).into())
     }
+
+    /// The minimum feature level required by this element, or `None` if no
+    /// required feature implies one.  This is a shorthand for the start of
+    /// [`computed_feature_level()`](EngineReq::computed_feature_level);
+    /// implementations that can determine the value more directly may
+    /// override it.
+    fn min_flevel(&self) -> Option<u32> {
+        self.computed_feature_level()?.start()
+    }
+}
+
+/// The ClamAV release version associated with the given feature level, per
+/// the published FLEVEL table.  Development and pre-release versions are
+/// skipped in favor of the first stable release at that level.  Returns
+/// `None` for levels with no published release (including future levels).
+#[must_use]
+pub fn flevel_to_version(flevel: u32) -> Option<&'static str> {
+    let versions = features::FLEVEL_VERSIONS
+        .iter()
+        .find(|(fl, _)| *fl == flevel)
+        .map(|(_, versions)| *versions)?;
+    versions
+        .iter()
+        .copied()
+        .find(|v| !v.contains("dev") && !v.contains('-'))
+        .or_else(|| versions.first().copied())
+}
+
+/// The feature level introduced by the given ClamAV release version (with or
+/// without a leading `v`), or `None` if the version isn't in the published
+/// FLEVEL table
+#[must_use]
+pub fn version_to_flevel(version: &str) -> Option<u32> {
+    let version = version.strip_prefix('v').unwrap_or(version);
+    features::FLEVEL_VERSIONS
+        .iter()
+        .find(|(_, versions)| versions.contains(&version))
+        .map(|(flevel, _)| *flevel)
+}
+
+/// Format a feature level for reporting, appending the associated ClamAV
+/// release version (e.g., "81 (ClamAV 0.99.0)") when one is known
+#[must_use]
+pub fn flevel_with_version(flevel: u32) -> String {
+    match flevel_to_version(flevel) {
+        Some(version) => format!("{flevel} (ClamAV {version})"),
+        None => flevel.to_string(),
+    }
 }
 
 /// A wrapper around a set of features identifiers, which may be known at compile
 /// time or computed after examining signature content.
-#[derive(PartialEq)]
+#[derive(PartialEq, Eq, Hash)]
 pub enum Set {
     Empty,
     Static(&'static [Feature]),
@@ -128,7 +176,7 @@ impl std::fmt::Debug for WithMinFlevel {
 
 /// A wrapper type for a FeatureSet that includes the minimum feature FLEVEL in
 /// debug formatting
-#[derive(PartialEq)]
+#[derive(PartialEq, Eq, Hash)]
 pub struct SetWithMinFlevel(Set);
 
 impl From<Set> for SetWithMinFlevel {
@@ -152,3 +200,36 @@ impl std::fmt::Debug for SetWithMinFlevel {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flevel_version_mapping() {
+        assert_eq!(flevel_to_version(51), Some("0.96.0"));
+        assert_eq!(flevel_to_version(81), Some("0.99.0"));
+        assert_eq!(flevel_to_version(121), Some("0.103.0"));
+        assert_eq!(flevel_to_version(160), Some("1.0.0"));
+        // Levels with only a pre-release fall back to it
+        assert_eq!(flevel_to_version(100), Some("0.101.0-BETA"));
+        // Unknown (or future) levels are reported gracefully
+        assert_eq!(flevel_to_version(86), None);
+        assert_eq!(flevel_to_version(9999), None);
+    }
+
+    #[test]
+    fn version_flevel_mapping() {
+        assert_eq!(version_to_flevel("0.96.0"), Some(51));
+        assert_eq!(version_to_flevel("0.103.0"), Some(121));
+        // A leading `v` (as found in release tags) is tolerated
+        assert_eq!(version_to_flevel("v1.4.0"), Some(210));
+        assert_eq!(version_to_flevel("2.0.0"), None);
+    }
+
+    #[test]
+    fn flevel_reporting_includes_version() {
+        assert_eq!(flevel_with_version(81), "81 (ClamAV 0.99.0)");
+        assert_eq!(flevel_with_version(9999), "9999");
+    }
+}
@@ -33,7 +33,99 @@ use crate::{
 // These are autogenerated at build time
 include!(concat!(env!("OUT_DIR"), "/filetypes-c_const"));
 
-#[derive(Debug, Error, PartialEq)]
+impl FileType {
+    /// Whether this file type denotes a native executable image, within which
+    /// executable-relative offsets (entry-point- or section-relative) can be
+    /// resolved
+    #[must_use]
+    pub fn is_native_executable(&self) -> bool {
+        matches!(
+            self,
+            FileType::CL_TYPE_MSEXE
+                | FileType::CL_TYPE_ELF
+                | FileType::CL_TYPE_MACHO
+                | FileType::CL_TYPE_MACHO_UNIBIN
+        )
+    }
+
+    /// Whether this file type denotes a container: a format from which the
+    /// engine extracts embedded files, and which can therefore appear as the
+    /// enclosing object in container-relative matching (e.g., the
+    /// `ContainerType` field of a container-metadata signature).  Plain
+    /// content types (text, graphics, executable images) are not containers.
+    #[must_use]
+    pub fn is_container(&self) -> bool {
+        matches!(
+            self,
+            // Archive and compressed-stream formats
+            FileType::CL_TYPE_POSIX_TAR
+                | FileType::CL_TYPE_OLD_TAR
+                | FileType::CL_TYPE_CPIO_OLD
+                | FileType::CL_TYPE_CPIO_ODC
+                | FileType::CL_TYPE_CPIO_NEWC
+                | FileType::CL_TYPE_CPIO_CRC
+                | FileType::CL_TYPE_GZ
+                | FileType::CL_TYPE_ZIP
+                | FileType::CL_TYPE_BZ
+                | FileType::CL_TYPE_XZ
+                | FileType::CL_TYPE_RAR
+                | FileType::CL_TYPE_ARJ
+                | FileType::CL_TYPE_7Z
+                | FileType::CL_TYPE_XAR
+                | FileType::CL_TYPE_EGG
+                | FileType::CL_TYPE_ALZ
+                | FileType::CL_TYPE_LHA_LZH
+                | FileType::CL_TYPE_MSSZDD
+                | FileType::CL_TYPE_MSCAB
+                | FileType::CL_TYPE_MSCHM
+                // Installers and self-extracting archives
+                | FileType::CL_TYPE_SIS
+                | FileType::CL_TYPE_NULSFT
+                | FileType::CL_TYPE_AUTOIT
+                | FileType::CL_TYPE_ISHIELD_MSI
+                | FileType::CL_TYPE_SFX
+                | FileType::CL_TYPE_ZIPSFX
+                | FileType::CL_TYPE_RARSFX
+                | FileType::CL_TYPE_7ZSFX
+                | FileType::CL_TYPE_CABSFX
+                | FileType::CL_TYPE_ARJSFX
+                | FileType::CL_TYPE_EGGSFX
+                // Document formats with embedded content
+                | FileType::CL_TYPE_MSOLE2
+                | FileType::CL_TYPE_OOXML_WORD
+                | FileType::CL_TYPE_OOXML_PPT
+                | FileType::CL_TYPE_OOXML_XL
+                | FileType::CL_TYPE_OOXML_HWP
+                | FileType::CL_TYPE_XML_WORD
+                | FileType::CL_TYPE_XML_XL
+                | FileType::CL_TYPE_XML_HWP
+                | FileType::CL_TYPE_HWP3
+                | FileType::CL_TYPE_HWPOLE2
+                | FileType::CL_TYPE_RTF
+                | FileType::CL_TYPE_PDF
+                | FileType::CL_TYPE_XDP
+                | FileType::CL_TYPE_ONENOTE
+                | FileType::CL_TYPE_SWF
+                // Mail and encoded-attachment formats
+                | FileType::CL_TYPE_MAIL
+                | FileType::CL_TYPE_MHTML
+                | FileType::CL_TYPE_TNEF
+                | FileType::CL_TYPE_BINHEX
+                | FileType::CL_TYPE_UUENCODED
+                | FileType::CL_TYPE_PST
+                // Disk images and partition tables
+                | FileType::CL_TYPE_ISO9660
+                | FileType::CL_TYPE_UDF
+                | FileType::CL_TYPE_DMG
+                | FileType::CL_TYPE_MBR
+                | FileType::CL_TYPE_GPT
+                | FileType::CL_TYPE_APM
+                | FileType::CL_TYPE_PART_HFSPLUS
+        )
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
 pub enum FileTypeParseError {
     #[error("not UTF-8: {0}")]
     UTF8(#[from] Utf8Error),
@@ -0,0 +1,368 @@
+/*
+ *  Copyright (C) 2024 Cisco Systems, Inc. and/or its affiliates. All rights reserved.
+ *
+ *  This program is free software; you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License version 2 as
+ *  published by the Free Software Foundation.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program; if not, write to the Free Software
+ *  Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston,
+ *  MA 02110-1301, USA.
+ */
+
+//! The ClamAV database manifest (`.info`) format.  CVD payloads carry a
+//! manifest listing each contained file along with its size and SHA2-256
+//! digest, which is used to verify partial (cdiff-based) updates.  The first
+//! line is a `ClamAV-VDB:` header describing the database build; each
+//! subsequent line describes one file.
+
+use crate::util::{parse_number_dec, ParseNumberError};
+use std::fmt;
+use thiserror::Error;
+
+/// Length (in bytes) of the SHA2-256 digests listed in a manifest
+const SHA2_256_LEN: usize = 32;
+
+/// The `ClamAV-VDB:` header line of a database manifest, matching the layout
+/// of a CVD header: build time, version, signature count, functionality
+/// level, MD5 and digital signature (both typically unpopulated, as `X`, in
+/// manifests), builder, and epoch build time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VdbHeader {
+    pub build_time: String,
+    pub version: u32,
+    pub n_sigs: usize,
+    pub f_level: u32,
+    pub md5: String,
+    pub dsig: String,
+    pub builder: String,
+    pub build_unixtime: u64,
+}
+
+/// A single file entry within a database manifest
+#[derive(Debug, Clone, PartialEq)]
+pub struct InfoEntry {
+    pub file_name: String,
+    pub file_size: u64,
+    pub sha2_256: [u8; SHA2_256_LEN],
+}
+
+/// A parsed database manifest: the `ClamAV-VDB:` header, plus one entry per
+/// contained file
+#[derive(Debug, Clone, PartialEq)]
+pub struct InfoManifest {
+    pub header: VdbHeader,
+    entries: Vec<InfoEntry>,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum InfoParseError {
+    #[error("manifest is empty")]
+    Empty,
+
+    #[error("manifest is not unicode: {0}")]
+    NotUnicode(#[from] std::str::Utf8Error),
+
+    #[error("header doesn't begin with ClamAV-VDB")]
+    MissingHeaderMagic,
+
+    #[error("header is missing the {0} field")]
+    MissingHeaderField(&'static str),
+
+    #[error("parsing header version: {0}")]
+    ParseVersion(#[source] ParseNumberError<u32>),
+
+    #[error("parsing header signature count: {0}")]
+    ParseNSigs(#[source] ParseNumberError<usize>),
+
+    #[error("parsing header functionality level: {0}")]
+    ParseFLevel(#[source] ParseNumberError<u32>),
+
+    #[error("parsing header build time (epoch): {0}")]
+    ParseBuildUnixtime(#[source] ParseNumberError<u64>),
+
+    #[error("entry at line {0} is missing the {1} field")]
+    MissingEntryField(usize, &'static str),
+
+    #[error("parsing file size at line {0}: {1}")]
+    ParseFileSize(usize, #[source] ParseNumberError<u64>),
+
+    #[error("decoding SHA2-256 digest at line {0}: {1}")]
+    DecodeDigest(usize, #[source] hex::FromHexError),
+}
+
+/// Errors encountered while verifying file contents against a manifest entry
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
+pub enum InfoVerifyError {
+    #[error("file {0:?} is not listed in the manifest")]
+    UnknownFile(String),
+
+    #[error("file size mismatch (manifest lists {expected}, found {actual})")]
+    SizeMismatch { expected: u64, actual: u64 },
+
+    #[error("SHA2-256 digest mismatch")]
+    DigestMismatch,
+}
+
+impl InfoManifest {
+    /// Parse a complete manifest from its raw bytes
+    pub fn parse(bytes: &[u8]) -> Result<Self, InfoParseError> {
+        let content = std::str::from_utf8(bytes)?;
+        let mut lines = content.lines().enumerate();
+
+        let (_, header_line) = lines.next().ok_or(InfoParseError::Empty)?;
+        let header = header_line.parse()?;
+
+        let mut entries = vec![];
+        for (line_idx, line) in lines {
+            if line.is_empty() {
+                continue;
+            }
+            let line_no = line_idx + 1;
+            let mut fields = line.split(':');
+            let file_name = fields
+                .next()
+                .ok_or(InfoParseError::MissingEntryField(line_no, "file name"))?
+                .to_owned();
+            let file_size = parse_number_dec(
+                fields
+                    .next()
+                    .ok_or(InfoParseError::MissingEntryField(line_no, "file size"))?
+                    .as_bytes(),
+            )
+            .map_err(|e| InfoParseError::ParseFileSize(line_no, e))?;
+            let sha2_256 = crate::util::decode_hex(
+                fields
+                    .next()
+                    .ok_or(InfoParseError::MissingEntryField(line_no, "digest"))?
+                    .as_bytes(),
+            )
+            .map_err(|e| InfoParseError::DecodeDigest(line_no, e))?;
+            entries.push(InfoEntry {
+                file_name,
+                file_size,
+                sha2_256,
+            });
+        }
+
+        Ok(Self { header, entries })
+    }
+
+    /// Begin a new manifest with the given header and no file entries
+    #[must_use]
+    pub fn new(header: VdbHeader) -> Self {
+        Self {
+            header,
+            entries: vec![],
+        }
+    }
+
+    /// Add an entry for the named file, computing its size and SHA2-256
+    /// digest from the supplied contents
+    pub fn add_file<S: Into<String>>(&mut self, file_name: S, contents: &[u8]) {
+        self.entries.push(InfoEntry {
+            file_name: file_name.into(),
+            file_size: contents.len() as u64,
+            sha2_256: openssl::sha::sha256(contents),
+        });
+    }
+
+    /// The per-file entries listed in this manifest
+    #[must_use]
+    pub fn entries(&self) -> &[InfoEntry] {
+        &self.entries
+    }
+
+    /// Verify the supplied file contents against the manifest's entry for the
+    /// named file
+    pub fn verify_file(&self, file_name: &str, contents: &[u8]) -> Result<(), InfoVerifyError> {
+        let entry = self
+            .entries
+            .iter()
+            .find(|e| e.file_name == file_name)
+            .ok_or_else(|| InfoVerifyError::UnknownFile(file_name.to_owned()))?;
+        if entry.file_size != contents.len() as u64 {
+            return Err(InfoVerifyError::SizeMismatch {
+                expected: entry.file_size,
+                actual: contents.len() as u64,
+            });
+        }
+        if entry.sha2_256 != openssl::sha::sha256(contents) {
+            return Err(InfoVerifyError::DigestMismatch);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for InfoManifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.header)?;
+        for entry in &self.entries {
+            writeln!(
+                f,
+                "{}:{}:{}",
+                entry.file_name,
+                entry.file_size,
+                hex::encode(entry.sha2_256)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for VdbHeader {
+    type Err = InfoParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split(':');
+        if fields.next() != Some("ClamAV-VDB") {
+            return Err(InfoParseError::MissingHeaderMagic);
+        }
+        let build_time = fields
+            .next()
+            .ok_or(InfoParseError::MissingHeaderField("build time"))?
+            .to_owned();
+        let version = parse_number_dec(
+            fields
+                .next()
+                .ok_or(InfoParseError::MissingHeaderField("version"))?
+                .as_bytes(),
+        )
+        .map_err(InfoParseError::ParseVersion)?;
+        let n_sigs = parse_number_dec(
+            fields
+                .next()
+                .ok_or(InfoParseError::MissingHeaderField("signature count"))?
+                .as_bytes(),
+        )
+        .map_err(InfoParseError::ParseNSigs)?;
+        let f_level = parse_number_dec(
+            fields
+                .next()
+                .ok_or(InfoParseError::MissingHeaderField("functionality level"))?
+                .as_bytes(),
+        )
+        .map_err(InfoParseError::ParseFLevel)?;
+        let md5 = fields
+            .next()
+            .ok_or(InfoParseError::MissingHeaderField("MD5"))?
+            .to_owned();
+        let dsig = fields
+            .next()
+            .ok_or(InfoParseError::MissingHeaderField("digital signature"))?
+            .to_owned();
+        let builder = fields
+            .next()
+            .ok_or(InfoParseError::MissingHeaderField("builder"))?
+            .to_owned();
+        let build_unixtime = parse_number_dec(
+            fields
+                .next()
+                .ok_or(InfoParseError::MissingHeaderField("build time (epoch)"))?
+                .as_bytes(),
+        )
+        .map_err(InfoParseError::ParseBuildUnixtime)?;
+
+        Ok(Self {
+            build_time,
+            version,
+            n_sigs,
+            f_level,
+            md5,
+            dsig,
+            builder,
+            build_unixtime,
+        })
+    }
+}
+
+impl fmt::Display for VdbHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ClamAV-VDB:{}:{}:{}:{}:{}:{}:{}:{}",
+            self.build_time,
+            self.version,
+            self.n_sigs,
+            self.f_level,
+            self.md5,
+            self.dsig,
+            self.builder,
+            self.build_unixtime
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = concat!(
+        "ClamAV-VDB:21 Sep 2021 08-31 -0400:26292:4566249:63:X:X:raynman:1632227471\n",
+        "daily.hdb:11:ffb81f37b6d48c2933b92634592459b16557fe18a833b0b525a137698d30e36b\n",
+        "daily.ndb:15:13cebf8a8ab5d9616202acdf9ce7782d6463ac6a0fe75947bd1b5e5ce8f5b798\n",
+    );
+
+    #[test]
+    fn parse_fixture() {
+        let manifest = InfoManifest::parse(FIXTURE.as_bytes()).unwrap();
+        assert_eq!(manifest.header.version, 26292);
+        assert_eq!(manifest.header.builder, "raynman");
+        assert_eq!(manifest.header.build_time, "21 Sep 2021 08-31 -0400");
+        assert_eq!(manifest.entries().len(), 2);
+        assert_eq!(manifest.entries()[0].file_name, "daily.hdb");
+        assert_eq!(manifest.entries()[0].file_size, 11);
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let manifest = InfoManifest::parse(FIXTURE.as_bytes()).unwrap();
+        assert_eq!(manifest.to_string(), FIXTURE);
+    }
+
+    #[test]
+    fn verify_good_and_tampered() {
+        // The fixture digests were computed over these contents
+        let manifest = InfoManifest::parse(FIXTURE.as_bytes()).unwrap();
+        manifest.verify_file("daily.hdb", b"hdb-content").unwrap();
+        assert_eq!(
+            manifest.verify_file("daily.hdb", b"tampered---"),
+            Err(InfoVerifyError::DigestMismatch)
+        );
+        assert_eq!(
+            manifest.verify_file("daily.hdb", b"short"),
+            Err(InfoVerifyError::SizeMismatch {
+                expected: 11,
+                actual: 5
+            })
+        );
+        assert!(matches!(
+            manifest.verify_file("daily.cdb", b""),
+            Err(InfoVerifyError::UnknownFile(_))
+        ));
+    }
+
+    #[test]
+    fn regenerate_matches_contents() {
+        let original = InfoManifest::parse(FIXTURE.as_bytes()).unwrap();
+        let mut rebuilt = InfoManifest::new(original.header.clone());
+        rebuilt.add_file("daily.hdb", b"hdb-content");
+        rebuilt.add_file("daily.ndb", b"ndb-content-xyz");
+        assert_eq!(rebuilt, original);
+    }
+
+    #[test]
+    fn reject_bad_header() {
+        assert_eq!(
+            InfoManifest::parse(b"NotAManifest:1:2:3"),
+            Err(InfoParseError::MissingHeaderMagic)
+        );
+        assert_eq!(InfoManifest::parse(b""), Err(InfoParseError::Empty));
+    }
+}
@@ -22,21 +22,40 @@
 
 #![deny(clippy::mod_module_files)]
 
+/// Versioned JSON schema for audit reports and diagnostics
+pub mod audit;
+
 /// Functionality associated with engine features
 pub mod feature;
 
 /// File type classification
 pub mod filetype;
 
+/// Database manifest (`.info`) parsing and verification
+pub mod info;
+
+/// Commonly-used types, re-exported under stable names
+pub mod prelude;
+
 /// Regular expressions
 pub mod regexp;
 
 /// SigBytes (Vec<u8>) wrapper
 pub mod sigbytes;
 
-/// Engine signature parsing and examination
+/// Engine signature parsing and examination.
+///
+/// Paths beneath this module reflect internal organization and are not
+/// stable between releases; downstream code should prefer the [`prelude`]
+/// re-exports.
 pub mod signature;
 
+/// `proptest` strategies for generating valid signature elements, for use in
+/// property-based tests (here and downstream).  Enabled with the `testutil`
+/// feature.
+#[cfg(any(test, feature = "testutil"))]
+pub mod testutil;
+
 pub mod util;
 
 pub use feature::Feature;
@@ -0,0 +1,52 @@
+/*
+ *  Copyright (C) 2024 Cisco Systems, Inc. and/or its affiliates. All rights reserved.
+ *
+ *  This program is free software; you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License version 2 as
+ *  published by the Free Software Foundation.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program; if not, write to the Free Software
+ *  Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston,
+ *  MA 02110-1301, USA.
+ */
+
+//! Re-exports of the types needed for the common parse → validate → export
+//! workflow, under stable names.  The nested module paths these come from
+//! reflect internal organization and may move between releases; downstream
+//! code should prefer these re-exports.
+//!
+//! # Example
+//!
+//! ```
+//! use clam_sigutil::prelude::*;
+//!
+//! let raw: SigBytes = "44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature".into();
+//! let (sig, meta) = parse_from_cvd_with_meta(SigType::FileHash, &raw)?;
+//! sig.validate(&meta)?;
+//! assert_eq!(sig.to_sigbytes()?, raw);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub use crate::{
+    feature::Feature,
+    sigbytes::{AppendSigBytes, FromSigBytes, SigBytes},
+    signature::{
+        bodysig::BodySig,
+        container_metadata_sig::ContainerMetadataSig,
+        ext_sig::{ExtendedSig, Offset},
+        filehash::FileHashSig,
+        ftmagic::FTMagicSig,
+        logical_sig::{targetdesc::TargetDesc, LogicalSig},
+        parse_from_cvd, parse_from_cvd_with_meta,
+        pehash::PESectionHashSig,
+        phishing_sig::PhishingSig,
+        sigtype::SigType,
+        FromSigBytesParseError, SigMeta, SigValidationError, Signature, ToSigBytesError,
+    },
+};
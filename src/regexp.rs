@@ -39,7 +39,7 @@ pub enum ParseError {
     NotUnicode(#[from] str::Utf8Error),
 
     #[error("invalid hexdecimal escape at pos {0}: {1}")]
-    FromHex(usize, hex::FromHexError),
+    FromHex(usize, #[source] hex::FromHexError),
 
     #[error("no character following escape")]
     MidEscape,
@@ -23,12 +23,9 @@ use crate::{
     Signature,
 };
 
-pub const BYTE_DISP_PREFIX: &str = "<|";
-pub const BYTE_DISP_SUFFIX: &str = "|>";
-
 /// A type wrapper around a series of bytes found in a signature.  Allows
 /// implementing `Display` to work around potential unicode problems.
-#[derive(Default, PartialEq)]
+#[derive(Default, PartialEq, Eq, Hash)]
 pub struct SigBytes(Vec<u8>);
 
 impl SigBytes {
@@ -50,6 +47,63 @@ impl SigBytes {
     pub fn as_bytes(&self) -> &[u8] {
         &self.0
     }
+
+    /// Render to a `String`, passing valid UTF-8 through unchanged and
+    /// escaping any other bytes as `\xNN`
+    #[must_use]
+    pub fn to_string_lossy(&self) -> String {
+        self.to_string()
+    }
+
+    /// Read a single signature from a reader (e.g., a file or stdin),
+    /// stripping one trailing `\n` or `\r\n` if present
+    pub fn from_reader(mut reader: impl std::io::Read) -> std::io::Result<Self> {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+        strip_line_terminator(&mut buf);
+        Ok(SigBytes(buf))
+    }
+
+    /// Read the next line from a buffered reader, stripping the trailing
+    /// `\n` or `\r\n`.  Returns `Ok(None)` at end of input.
+    pub fn read_line(mut reader: impl std::io::BufRead) -> std::io::Result<Option<Self>> {
+        let mut buf = Vec::new();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            return Ok(None);
+        }
+        strip_line_terminator(&mut buf);
+        Ok(Some(SigBytes(buf)))
+    }
+
+    /// Iterate over the lines of a multi-line buffer, with `\n` or `\r\n`
+    /// terminators removed.  As with [`str::lines`], a final line terminator
+    /// ends the last line rather than opening an empty one.
+    pub fn lines(&self) -> impl Iterator<Item = SigBytes> + '_ {
+        let bytes: &[u8] = match self.0.as_slice() {
+            [rest @ .., b'\n'] => rest,
+            all => all,
+        };
+        let mut chunks = bytes.split(|&b| b == b'\n');
+        if self.0.is_empty() {
+            // `split` reports a single empty chunk for empty input, but an
+            // empty buffer contains no lines
+            chunks.next();
+        }
+        chunks.map(|line| match line {
+            [rest @ .., b'\r'] => rest.into(),
+            all => all.into(),
+        })
+    }
+}
+
+/// Remove one trailing `\n` or `\r\n` line terminator, if present
+fn strip_line_terminator(buf: &mut Vec<u8>) {
+    if buf.last() == Some(&b'\n') {
+        buf.pop();
+        if buf.last() == Some(&b'\r') {
+            buf.pop();
+        }
+    }
 }
 
 impl std::fmt::Debug for SigBytes {
@@ -74,6 +128,20 @@ pub trait AppendSigBytes {
     fn append_sigbytes(&self, sb: &mut SigBytes) -> Result<(), crate::signature::ToSigBytesError>;
 }
 
+// Optional values serialize as `*` when absent, the convention shared by the
+// signature formats with optional fields.
+impl<T: AppendSigBytes> AppendSigBytes for Option<T> {
+    fn append_sigbytes(&self, sb: &mut SigBytes) -> Result<(), crate::signature::ToSigBytesError> {
+        use std::fmt::Write;
+        if let Some(value) = self {
+            value.append_sigbytes(sb)
+        } else {
+            sb.write_char('*')?;
+            Ok(())
+        }
+    }
+}
+
 // Simple hex encoding of binary sequences, the most-typical representation within
 // signature databases for literal strings.
 impl AppendSigBytes for &[u8] {
@@ -109,11 +177,9 @@ impl std::fmt::Display for SigBytes {
                     }?;
                     match e.error_len() {
                         Some(len) => {
-                            f.write_str(BYTE_DISP_PREFIX)?;
                             after_valid[0..len]
                                 .iter()
-                                .try_for_each(|&b| write!(f, "{b:02x}"))?;
-                            f.write_str(BYTE_DISP_SUFFIX)?;
+                                .try_for_each(|&b| write!(f, r"\x{b:02x}"))?;
                             bytes = &after_valid[len..];
                         }
                         None => break Ok(()),
@@ -162,18 +228,36 @@ impl<const N: usize> From<&[u8; N]> for SigBytes {
 }
 
 /// A type wrapper around a single byte found in a signature. Allows implementing
-/// `Display` to work around potential unicode problems
-#[derive(Debug, PartialEq)]
+/// `Display` to work around potential unicode problems.  Ordering delegates to
+/// the inner byte value, allowing collections of these (e.g., within errors)
+/// to be sorted or deduplicated.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SigChar(pub u8);
 
-/// Convert a byte to its character representation, or a symbol indicating
-/// invalid unicode
+/// Convert a byte to a human-readable representation: printable ASCII as
+/// `'x'`, common control characters as their escape sequence (`'\n'`,
+/// `'\t'`), and everything else (including high-bit bytes) as `'\xNN'`.  This
+/// is what renders the `found` byte in body signature parse errors such as
+/// `UnexpectedChar`, and guarantees that raw control bytes never leak into
+/// error text.
 impl std::fmt::Display for SigChar {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match str::from_utf8(&[self.0]) {
-            Ok(s) => write!(f, "'{s}'"),
-            Err(_) => write!(f, "{}{:x}{}", BYTE_DISP_PREFIX, self.0, BYTE_DISP_SUFFIX),
-        }
+        f.write_str(&crate::util::byte_to_printable(self.0))
+    }
+}
+
+impl SigChar {
+    /// The raw byte value
+    #[must_use]
+    pub fn byte(&self) -> u8 {
+        self.0
+    }
+
+    /// Whether the byte renders as itself in [`Display`](std::fmt::Display)
+    /// output (printable ASCII), rather than as an escape sequence
+    #[must_use]
+    pub fn is_printable(&self) -> bool {
+        self.0.is_ascii_graphic() || self.0 == b' '
     }
 }
 
@@ -183,6 +267,30 @@ impl From<u8> for SigChar {
     }
 }
 
+/// The character couldn't be represented as a [`SigChar`] because it lies
+/// outside the ASCII range
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Hash)]
+#[error("character {0:?} is not ASCII")]
+pub struct NonAsciiChar(pub char);
+
+/// Convert from a character.  Only ASCII characters are representable as a
+/// single signature byte; anything else is reported as [`NonAsciiChar`].  (An
+/// infallible `From<char>` can't coexist with this implementation, so all
+/// `char` conversion goes through here.)
+impl TryFrom<char> for SigChar {
+    type Error = NonAsciiChar;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        if c.is_ascii() {
+            // is_ascii() guarantees the value fits in (and round-trips
+            // through) a single byte
+            Ok(Self(u8::try_from(c).unwrap()))
+        } else {
+            Err(NonAsciiChar(c))
+        }
+    }
+}
+
 impl std::io::Write for SigBytes {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
         self.0.write(buf)
@@ -210,7 +318,35 @@ mod tests {
     #[test]
     fn sigchar_display() {
         assert_eq!(format!("{}", SigChar(b'x')), "'x'");
-        assert_eq!(format!("{}", SigChar(b'\x80')), "<|80|>");
+        assert_eq!(format!("{}", SigChar(b'\n')), r"'\n'");
+        assert_eq!(format!("{}", SigChar(b'\x80')), r"'\x80'");
+        assert_eq!(format!("{}", SigChar(b'\'')), r"'\''");
+    }
+
+    #[test]
+    fn sigchar_accessors() {
+        assert_eq!(SigChar(b'x').byte(), b'x');
+        assert!(SigChar(b'x').is_printable());
+        assert!(SigChar(b' ').is_printable());
+        assert!(!SigChar(b'\n').is_printable());
+        assert!(!SigChar(b'\x80').is_printable());
+    }
+
+    #[test]
+    fn sigchar_ordering() {
+        let mut chars = [SigChar(b'c'), SigChar(b'a'), SigChar(b'b')];
+        chars.sort();
+        assert_eq!(chars, [SigChar(b'a'), SigChar(b'b'), SigChar(b'c')]);
+        // Usable for deduplication via ordered collections
+        let set: std::collections::BTreeSet<SigChar> =
+            [SigChar(b'a'), SigChar(b'a'), SigChar(b'b')].into();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn sigchar_from_char() {
+        assert_eq!(SigChar::try_from('x'), Ok(SigChar(b'x')));
+        assert_eq!(SigChar::try_from('¢'), Err(NonAsciiChar('¢')));
     }
 
     #[test]
@@ -226,18 +362,73 @@ mod tests {
     #[test]
     fn sigbytes_invalid_short_end() {
         let bytes: SigBytes = b"how now brown cow\x80".into();
-        assert_eq!(bytes.to_string(), "how now brown cow<|80|>");
+        assert_eq!(bytes.to_string(), r"how now brown cow\x80");
     }
 
     #[test]
     fn sigbytes_invalid_long_end() {
         let bytes: SigBytes = b"how now brown cow\xa0\xa1".into();
-        assert_eq!(bytes.to_string(), "how now brown cow<|a0|><|a1|>");
+        assert_eq!(bytes.to_string(), r"how now brown cow\xa0\xa1");
+    }
+
+    #[test]
+    fn sigbytes_from_reader() {
+        let sb = SigBytes::from_reader(&b"sig-one\n"[..]).unwrap();
+        assert_eq!(sb.as_bytes(), b"sig-one");
+        let sb = SigBytes::from_reader(&b"sig-one\r\n"[..]).unwrap();
+        assert_eq!(sb.as_bytes(), b"sig-one");
+        let sb = SigBytes::from_reader(&b"sig-one"[..]).unwrap();
+        assert_eq!(sb.as_bytes(), b"sig-one");
+    }
+
+    #[test]
+    fn sigbytes_read_line() {
+        let mut input = &b"one\ntwo\r\nthree"[..];
+        assert_eq!(
+            SigBytes::read_line(&mut input).unwrap(),
+            Some(b"one".into())
+        );
+        assert_eq!(
+            SigBytes::read_line(&mut input).unwrap(),
+            Some(b"two".into())
+        );
+        assert_eq!(
+            SigBytes::read_line(&mut input).unwrap(),
+            Some(b"three".into())
+        );
+        assert_eq!(SigBytes::read_line(&mut input).unwrap(), None);
+    }
+
+    #[test]
+    fn sigbytes_lines() {
+        let sb: SigBytes = b"one\ntwo\r\nthree\n".into();
+        assert_eq!(
+            sb.lines().collect::<Vec<_>>(),
+            vec![
+                SigBytes::from(b"one"),
+                SigBytes::from(b"two"),
+                SigBytes::from(b"three")
+            ]
+        );
+        // A missing final newline still yields the last line; interior empty
+        // lines are preserved
+        let sb: SigBytes = b"one\n\ntwo"[..].into();
+        assert_eq!(
+            sb.lines().collect::<Vec<_>>(),
+            vec![
+                SigBytes::from(b"one"),
+                SigBytes::from(b""),
+                SigBytes::from(b"two")
+            ]
+        );
+        let sb = SigBytes::new();
+        assert_eq!(sb.lines().count(), 0);
     }
 
     #[test]
     fn sigbytes_invalid_long_intermediate() {
         let bytes: SigBytes = b"how now\xa0\xa1brown cow".into();
-        assert_eq!(bytes.to_string(), "how now<|a0|><|a1|>brown cow");
+        assert_eq!(bytes.to_string(), r"how now\xa0\xa1brown cow");
+        assert_eq!(bytes.to_string_lossy(), r"how now\xa0\xa1brown cow");
     }
 }
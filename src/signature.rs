@@ -16,10 +16,16 @@
  *  MA 02110-1301, USA.
  */
 
+/// An owned enum view over the supported signature types
+pub mod any_sig;
 /// Body signatures, typically found in extended signatures
 pub mod bodysig;
 /// Container Metadata signature support
 pub mod container_metadata_sig;
+/// Whole-database loading
+pub mod database;
+/// Digital signature support
+pub mod digital_sig;
 /// Extended signature support
 pub mod ext_sig;
 /// File hash signature support
@@ -28,6 +34,8 @@ pub mod filehash;
 pub mod ftmagic;
 /// Common functionality for hash-based signatures
 pub mod hash;
+/// Icon (`.idb`) signature support
+pub mod icon_sig;
 pub mod intmask;
 /// Logical signature support
 pub mod logical_sig;
@@ -35,12 +43,14 @@ pub mod logical_sig;
 pub mod pehash;
 /// Phishing Signatures
 pub mod phishing_sig;
+/// Collections of signatures
+pub mod sigset;
 /// Enumeration of signature types
 pub mod sigtype;
 /// Enumeration of target types (typically found in logical and extended signatures)
 pub mod targettype;
-/// Digital signature support
-pub mod digital_sig;
+/// Visitors for examining and rewriting a signature's elements in bulk
+pub mod visitor;
 
 use crate::{
     feature::{self, EngineReq},
@@ -50,13 +60,59 @@ use crate::{
 };
 use downcast_rs::{impl_downcast, Downcast};
 use std::collections::TryReserveError;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use thiserror::Error;
 
+/// Version byte prefixed to the canonical serialization when computing
+/// [`Signature::fingerprint`].  Bump this whenever canonicalization changes in
+/// a way that should invalidate persisted fingerprints.
+pub const FINGERPRINT_VERSION: u8 = 1;
+
 /// Required functionality for a Signature.
 pub trait Signature: std::fmt::Debug + EngineReq + AppendSigBytes + Downcast {
     /// Signature name
     fn name(&self) -> &str;
 
+    /// The type of this signature
+    fn sig_type(&self) -> SigType;
+
+    /// Compute a key suitable for canonically ordering this signature within a
+    /// database.  Signatures order by name, then signature type, then a hash of
+    /// their exported form.  Signatures without a distinctive name (e.g.,
+    /// hash-based signatures exported without one) fall back to their exported
+    /// form, which leads with the digest.
+    fn sort_key(&self) -> SortKey {
+        let exported = self.to_sigbytes().unwrap_or_default();
+        let name = if self.name().is_empty() {
+            exported.to_string()
+        } else {
+            self.name().to_owned()
+        };
+        let mut hasher = DefaultHasher::new();
+        exported.as_bytes().hash(&mut hasher);
+        SortKey {
+            name,
+            sig_type: self.sig_type() as u8,
+            body_hash: hasher.finish(),
+        }
+    }
+
+    /// Literal byte strings contained within this signature, decoded to raw
+    /// bytes and suitable for indexing.  Body-based signatures report every
+    /// sufficiently-long fully-static run from their body patterns (including
+    /// alternative-string branches).  The default implementation reports
+    /// nothing.
+    fn literal_strings(&self) -> Vec<Vec<u8>> {
+        vec![]
+    }
+
+    /// The body signatures contained within this signature.  Extended
+    /// signatures have at most one; logical signatures report one per
+    /// extended sub-signature.  The default implementation reports nothing.
+    fn body_sigs(&self) -> Vec<&bodysig::BodySig> {
+        vec![]
+    }
+
     /// Return ClamAV signature, as would be expected in a CVD
     fn to_sigbytes(&self) -> Result<SigBytes, ToSigBytesError> {
         // Since this doesn't immediately allocate, implementations will still
@@ -66,6 +122,150 @@ pub trait Signature: std::fmt::Debug + EngineReq + AppendSigBytes + Downcast {
         Ok(sb)
     }
 
+    /// Return ClamAV signature, including the min/max feature level fields
+    /// carried in the associated [`SigMeta`].  The default implementation
+    /// appends the flevel fields as a colon-separated suffix, as used by the
+    /// hash-based and extended formats.  Types that encode the feature level
+    /// elsewhere (e.g., logical signatures, via the `Engine` attribute)
+    /// override this.
+    fn to_sigbytes_with_meta(&self, sigmeta: &SigMeta) -> Result<SigBytes, ToSigBytesError> {
+        use std::fmt::Write;
+        let mut sb = self.to_sigbytes()?;
+        if let Some(f_level) = &sigmeta.f_level {
+            match f_level {
+                Range::Exact(n) => write!(sb, ":{n}")?,
+                Range::From(r) => write!(sb, ":{}", r.start)?,
+                Range::Inclusive(r) => write!(sb, ":{}:{}", r.start(), r.end())?,
+                Range::ToInclusive(_) => {
+                    return Err(ToSigBytesError::UnsupportedValue(
+                        "maximum flevel without minimum".to_owned(),
+                    ))
+                }
+            }
+        }
+        Ok(sb)
+    }
+
+    /// A breakdown of this signature's matching cost.  The default
+    /// implementation (appropriate for hash-based signatures, which cost a
+    /// single digest comparison) reports minimal values.
+    fn complexity(&self) -> Complexity {
+        Complexity::default()
+    }
+
+    /// Report non-fatal conditions detected within this signature.  Unlike
+    /// [`Signature::validate`] failures, warnings describe signatures that are
+    /// well-formed but likely to behave poorly (e.g., scan slowly).  The
+    /// default implementation reports nothing.
+    fn warnings(&self) -> Vec<SigWarning> {
+        vec![]
+    }
+
+    /// A rough, unitless estimate of how expensive this signature is to
+    /// evaluate during scanning, for use in performance budgeting.  The exact
+    /// formula is arbitrary, but costs are consistent and monotonically
+    /// ordered by expected scan impact: less-specific signatures (fewer
+    /// static bytes, more wildcards) report higher costs.  The default
+    /// implementation reports a nominal constant of 10 for signature types
+    /// without a specific cost model.
+    fn estimated_scan_cost(&self) -> u64 {
+        10
+    }
+
+    /// A deterministic fingerprint of this signature, suitable for caching
+    /// and deduplication.  The digest (SHA2-256) is computed over the
+    /// canonical serialization, so formatting differences in the original
+    /// input (hex case, redundant range spellings) don't affect it, while any
+    /// semantic change does.  The serialization is prefixed with
+    /// [`FINGERPRINT_VERSION`] so that fingerprints persisted across releases
+    /// can be invalidated if canonicalization changes.
+    fn fingerprint(&self) -> [u8; 32] {
+        let exported = self.to_sigbytes().unwrap_or_default();
+        let mut buf = Vec::with_capacity(exported.len() + 1);
+        buf.push(FINGERPRINT_VERSION);
+        buf.extend_from_slice(exported.as_bytes());
+        openssl::sha::sha256(&buf)
+    }
+
+    /// A comprehensive, human-readable report of this signature for bug
+    /// reports and debugging.  This is distinct from `Debug` (which exposes
+    /// Rust internals) and from the CVD serialization (which is compact
+    /// rather than descriptive): every parsed aspect appears on its own
+    /// labeled line, and the layout is stable enough to diff across
+    /// versions.  The default implementation reports the name, type,
+    /// computed feature level, validation outcome, warnings, any
+    /// type-specific fields contributed via
+    /// [`Signature::append_diagnostic_fields`], and the serialized CVD form.
+    fn to_diagnostic_string(&self) -> String {
+        use std::fmt::Write;
+        let mut out = String::new();
+        let _ = writeln!(out, "name: {}", self.name());
+        let _ = writeln!(out, "type: {:?}", self.sig_type());
+        if let Some(f_level) = self.computed_feature_level() {
+            let mut sb = SigBytes::new();
+            if f_level.append_sigbytes(&mut sb).is_ok() {
+                let _ = writeln!(out, "computed feature level: {sb}");
+            }
+        } else {
+            let _ = writeln!(out, "computed feature level: none");
+        }
+        match self.validate(&SigMeta::default()) {
+            Ok(()) => {
+                let _ = writeln!(out, "validation: ok");
+            }
+            Err(e) => {
+                let _ = writeln!(out, "validation: {e}");
+            }
+        }
+        for warning in self.warnings() {
+            let _ = writeln!(out, "warning: {warning}");
+        }
+        self.append_diagnostic_fields(&mut out);
+        match self.to_sigbytes() {
+            Ok(sb) => {
+                let _ = writeln!(out, "cvd form: {sb}");
+            }
+            Err(e) => {
+                let _ = writeln!(out, "cvd form unavailable: {e}");
+            }
+        }
+        out
+    }
+
+    /// The decoded (escaped-ASCII) rendering of each body signature
+    /// contained in this signature, in the style of `sigtool --decode-sigs`
+    /// (see [`bodysig::BodySig::decode_ascii`]).  Signature types without
+    /// bodies report nothing.
+    fn decoded(&self) -> Vec<SigBytes> {
+        self.body_sigs()
+            .into_iter()
+            .map(bodysig::BodySig::decode_ascii)
+            .collect()
+    }
+
+    /// Walk this signature's elements, invoking the matching [`SigVisitor`]
+    /// hook for each.  Logical signatures recurse into their sub-signatures.
+    /// The default implementation (for signature types without walkable
+    /// elements, e.g. hash-based signatures) visits nothing.
+    ///
+    /// [`SigVisitor`]: visitor::SigVisitor
+    fn walk(&self, visitor: &mut dyn visitor::SigVisitor) {
+        let _ = visitor;
+    }
+
+    /// As [`Signature::walk`], but passing each element mutably for bulk
+    /// edits via [`SigVisitorMut`]
+    ///
+    /// [`SigVisitorMut`]: visitor::SigVisitorMut
+    fn walk_mut(&mut self, visitor: &mut dyn visitor::SigVisitorMut) {
+        let _ = visitor;
+    }
+
+    /// Contribute type-specific fields to
+    /// [`Signature::to_diagnostic_string`], one `label: value` line per
+    /// field.  The default implementation contributes nothing.
+    fn append_diagnostic_fields(&self, _out: &mut String) {}
+
     /// Perform all specified validation steps for a signature.
     fn validate(&self, sigmeta: &SigMeta) -> Result<(), SigValidationError> {
         self.validate_subelements(sigmeta)?;
@@ -84,36 +284,29 @@ pub trait Signature: std::fmt::Debug + EngineReq + AppendSigBytes + Downcast {
     /// elements' constraints
     fn validate_flevel(&self, sigmeta: &SigMeta) -> Result<(), SigValidationError> {
         // Check the specified vs. the computed feature level
-        if let Some(computed_flevel) = self.computed_feature_level() {
-            if let Some(computed_min_flevel) = computed_flevel.start() {
-                // Some features within this signature have a minimum feature level.
-                // Confirm that the signature specifies it (or a higher level)
-                match &sigmeta.f_level {
-                    Some(f_level) => match f_level.start() {
-                        Some(spec_min_flevel) => {
-                            if spec_min_flevel < computed_min_flevel {
-                                return Err(SigValidationError::SpecifiedMinFLevelTooLow {
-                                    spec_min_flevel,
-                                    computed_min_flevel,
-                                    feature_set: self.features().into(),
-                                });
-                            }
-                        }
-                        None => {
-                            // This is the [unlikely] case where a *maximum* FLevel
-                            // was specified without a minimum, but a minimum is required.
-                            return Err(SigValidationError::MinFLevelNotSpecified {
-                                computed_min_flevel,
-                                feature_set: self.features().into(),
-                            });
-                        }
-                    },
-                    None => {
-                        return Err(SigValidationError::MinFLevelNotSpecified {
-                            computed_min_flevel,
-                            feature_set: self.features().into(),
-                        });
-                    }
+        if let Some(computed_min_flevel) = self
+            .computed_feature_level()
+            .as_ref()
+            .and_then(Range::start)
+        {
+            // Some features within this signature have a minimum feature
+            // level.  Confirm that the signature specifies it (or a higher
+            // level).  A specified *maximum* without a minimum provides no
+            // minimum, and is reported the same as absent metadata.
+            match sigmeta.f_level.as_ref().and_then(Range::start) {
+                Some(spec_min_flevel) if spec_min_flevel < computed_min_flevel => {
+                    return Err(SigValidationError::SpecifiedMinFLevelTooLow {
+                        spec_min_flevel,
+                        computed_min_flevel,
+                        feature_set: self.features().into(),
+                    });
+                }
+                Some(_) => (),
+                None => {
+                    return Err(SigValidationError::MinFLevelNotSpecified {
+                        computed_min_flevel,
+                        feature_set: self.features().into(),
+                    });
                 }
             }
             // TODO: check maximum, as well (but maximums are not presently computed)
@@ -125,6 +318,61 @@ pub trait Signature: std::fmt::Debug + EngineReq + AppendSigBytes + Downcast {
 
 impl_downcast!(Signature);
 
+/// A canonical ordering key for a signature, as produced by
+/// [`Signature::sort_key()`].  Keys order by signature name, then signature
+/// type, then a hash of the signature's exported form.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SortKey {
+    name: String,
+    sig_type: u8,
+    body_hash: u64,
+}
+
+/// A breakdown of the matching-cost drivers within a signature, as produced by
+/// [`Signature::complexity`].  Hash-based signatures report minimal values;
+/// body-based signatures report the totals across their body patterns (and,
+/// for logical signatures, across all sub-signatures).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Complexity {
+    /// Number of body patterns
+    pub pattern_count: usize,
+    /// Number of fully-specified (non-wildcard) bytes across all patterns
+    pub total_static_bytes: usize,
+    /// Number of `*` wildcards
+    pub wildcard_count: usize,
+    /// Number of open-ended (`{n-}`) byte ranges
+    pub unbounded_range_count: usize,
+    /// Total number of branches across all alternative-string groups
+    pub alternative_branch_count: usize,
+    /// Number of PCRE sub-signatures
+    pub pcre_subsig_count: usize,
+}
+
+impl Complexity {
+    /// A single weighted score summarizing the breakdown.  Weights are chosen
+    /// so that elements that restart or extend the match search (wildcards,
+    /// unbounded ranges, and especially PCRE) dominate simple pattern counts.
+    #[must_use]
+    pub fn score(&self) -> usize {
+        self.pattern_count
+            + self.alternative_branch_count * 2
+            + self.wildcard_count * 4
+            + self.unbounded_range_count * 8
+            + self.pcre_subsig_count * 16
+    }
+}
+
+impl std::ops::AddAssign for Complexity {
+    fn add_assign(&mut self, rhs: Self) {
+        self.pattern_count += rhs.pattern_count;
+        self.total_static_bytes += rhs.total_static_bytes;
+        self.wildcard_count += rhs.wildcard_count;
+        self.unbounded_range_count += rhs.unbounded_range_count;
+        self.alternative_branch_count += rhs.alternative_branch_count;
+        self.pcre_subsig_count += rhs.pcre_subsig_count;
+    }
+}
+
 pub trait Validate {
     /// Perform additional validation on a signature element
     fn validate(&self) -> Result<(), SigValidationError> {
@@ -134,12 +382,106 @@ pub trait Validate {
 
 /// Additional data obtained from a signature when being parsed, but not
 /// necessary for operation of the signature
-#[derive(Default, Debug, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct SigMeta {
     /// Minimum feature level, or range of valid levels
     pub f_level: Option<Range<u32>>,
 }
 
+impl SigMeta {
+    /// Combine metadata from two sources (e.g., a `TargetDesc` `Engine`
+    /// attribute and subsig feature requirements), taking the stricter bound
+    /// from each side: the higher minimum feature level and the lower
+    /// maximum.  If the sources conflict, the result describes an empty
+    /// range (minimum above maximum), which marks the signature as
+    /// unsatisfiable; check with [`SigMeta::is_satisfiable`].
+    #[must_use]
+    pub fn merge(&self, other: &SigMeta) -> SigMeta {
+        let f_level = match (&self.f_level, &other.f_level) {
+            (None, None) => None,
+            (Some(range), None) | (None, Some(range)) => Some(range.clone()),
+            (Some(a), Some(b)) => {
+                let start = match (a.start(), b.start()) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    (start, None) | (None, start) => start,
+                };
+                let end = match (a.end(), b.end()) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (end, None) | (None, end) => end,
+                };
+                Some(match (start, end) {
+                    (Some(start), Some(end)) => (start..=end).into(),
+                    (Some(start), None) => (start..).into(),
+                    (None, Some(end)) => (..=end).into(),
+                    // Every range form carries at least one bound
+                    (None, None) => a.clone(),
+                })
+            }
+        };
+        SigMeta { f_level }
+    }
+
+    /// The minimum feature level, if one is recorded
+    #[must_use]
+    pub fn min_flevel(&self) -> Option<u32> {
+        self.f_level.as_ref().and_then(Range::start)
+    }
+
+    /// The maximum feature level, if one is recorded
+    #[must_use]
+    pub fn max_flevel(&self) -> Option<u32> {
+        self.f_level.as_ref().and_then(Range::end)
+    }
+
+    /// The `TargetDesc` `Engine` attribute equivalent to this metadata's
+    /// feature-level range, in the canonical `Engine:min-max` form, with 255
+    /// substituted for an open upper bound.  Returns `None` when no feature
+    /// level is recorded, or when only a maximum is known (an `Engine`
+    /// attribute requires a minimum).
+    #[must_use]
+    pub fn engine_attr(&self) -> Option<logical_sig::targetdesc::TargetDescAttr> {
+        let range = self.f_level.as_ref()?;
+        let start = range.start()?;
+        let end = range.end().unwrap_or(255);
+        Some(logical_sig::targetdesc::TargetDescAttr::Engine(
+            (start..=end).into(),
+        ))
+    }
+
+    /// Whether the feature-level range admits any level at all.  A range
+    /// left empty by [`SigMeta::merge`] (minimum above maximum) can be
+    /// satisfied by no engine.
+    #[must_use]
+    pub fn is_satisfiable(&self) -> bool {
+        match &self.f_level {
+            Some(range) => match (range.start(), range.end()) {
+                (Some(start), Some(end)) => start <= end,
+                _ => true,
+            },
+            None => true,
+        }
+    }
+}
+
+impl From<(u32, u32)> for SigMeta {
+    /// Construct metadata covering the inclusive `(min, max)` feature-level
+    /// pair
+    fn from((min, max): (u32, u32)) -> Self {
+        SigMeta {
+            f_level: Some((min..=max).into()),
+        }
+    }
+}
+
+impl From<std::ops::RangeInclusive<u32>> for SigMeta {
+    /// Construct metadata covering the given inclusive feature-level range
+    fn from(range: std::ops::RangeInclusive<u32>) -> Self {
+        SigMeta {
+            f_level: Some(range.into()),
+        }
+    }
+}
+
 /// Errors that can be encountered when exporting a Signature to its CVD format
 #[derive(Debug, Error)]
 pub enum ToSigBytesError {
@@ -167,6 +509,176 @@ pub enum ToSigBytesError {
     TryReserve(#[from] TryReserveError),
 }
 
+/// Caps on attacker-controllable sizes within a single signature record,
+/// enforced by [`parse_from_cvd_with_profile`] before the record is handed to
+/// a parser.  Several parsers allocate in proportion to field counts found in
+/// the input; these limits bound that allocation when loading untrusted
+/// databases.  The defaults mirror the engine's own limits, so records the
+/// engine itself would load are unaffected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Limits {
+    /// Maximum number of delimited fields in a record
+    pub max_fields: usize,
+    /// Maximum number of attributes in a logical signature's TargetDescription
+    pub max_attrs: usize,
+    /// Maximum number of container types in an `Intermediates` chain
+    pub max_intermediates: usize,
+    /// Maximum number of sub-signatures in a logical signature
+    pub max_subsigs: usize,
+    /// Maximum length (in bytes) of a logical signature's expression
+    pub max_expression_len: usize,
+    /// Maximum length (in bytes) of an entire record
+    pub max_line_len: usize,
+    /// Permit ASCII control characters within a record.  No known signature
+    /// format legitimately contains them; this is an escape hatch for
+    /// permissive handling of databases that somehow do.
+    pub allow_control_characters: bool,
+    /// Retain fields beyond those a signature format defines (carrying them
+    /// through to export) rather than rejecting the record.  See
+    /// [`TrailingFieldMode`].
+    pub preserve_trailing_fields: bool,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_fields: 32,
+            max_attrs: 16,
+            max_intermediates: 16,
+            max_subsigs: logical_sig::MAX_SUB_SIGS,
+            max_expression_len: 1024,
+            max_line_len: 16384,
+            allow_control_characters: false,
+            preserve_trailing_fields: false,
+        }
+    }
+}
+
+/// What to do with fields left over after a parser has consumed every field
+/// its format defines.  Database lines in the wild sometimes carry more
+/// fields than documented (e.g. phishing records with content after the
+/// flevel field); historically these were silently discarded, which loses
+/// data on a load/store round trip.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrailingFieldMode {
+    /// Reject the record with [`FromSigBytesParseError::TrailingFields`]
+    #[default]
+    Error,
+    /// Keep the unconsumed fields on the parsed signature, re-emitting them
+    /// on export
+    Preserve,
+}
+
+/// Collect whatever remains of a record's field iterator, and either reject
+/// the record or hand the fields back for retention, per `mode`.
+/// `total_fields` is the record's full field count, used to report the
+/// (0-based) position of the first unconsumed field.
+pub(crate) fn take_trailing_fields<'a, I: Iterator<Item = &'a [u8]>>(
+    fields: I,
+    total_fields: usize,
+    mode: TrailingFieldMode,
+) -> Result<Vec<SigBytes>, FromSigBytesParseError> {
+    let trailing: Vec<SigBytes> = fields.map(SigBytes::from).collect();
+    if trailing.is_empty() || mode == TrailingFieldMode::Preserve {
+        Ok(trailing)
+    } else {
+        Err(FromSigBytesParseError::TrailingFields {
+            count: trailing.len(),
+            first_pos: total_fields - trailing.len(),
+        })
+    }
+}
+
+/// A signature record exceeded one of the caps in [`Limits`]
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
+pub enum LimitExceeded {
+    #[error("record length {found} exceeds limit of {limit} bytes")]
+    LineLen { limit: usize, found: usize },
+
+    #[error("field count {found} exceeds limit of {limit}")]
+    Fields { limit: usize, found: usize },
+
+    #[error("TargetDescription attribute count {found} exceeds limit of {limit}")]
+    Attrs { limit: usize, found: usize },
+
+    #[error("Intermediates container count {found} exceeds limit of {limit}")]
+    Intermediates { limit: usize, found: usize },
+
+    #[error("sub-signature count {found} exceeds limit of {limit}")]
+    SubSigs { limit: usize, found: usize },
+
+    #[error("expression length {found} exceeds limit of {limit} bytes")]
+    ExpressionLen { limit: usize, found: usize },
+}
+
+impl Limits {
+    /// Verify that a raw record respects these limits.  This examines only
+    /// delimiter positions in the input, and is performed before any parsing
+    /// (and hence any input-proportional allocation) takes place.
+    fn check(&self, sig_type: SigType, data: &SigBytes) -> Result<(), LimitExceeded> {
+        let bytes: &[u8] = data.as_bytes();
+
+        if bytes.len() > self.max_line_len {
+            return Err(LimitExceeded::LineLen {
+                limit: self.max_line_len,
+                found: bytes.len(),
+            });
+        }
+
+        if sig_type == SigType::Logical {
+            let fields: Vec<&[u8]> = bytes.split(|&b| b == b';').collect();
+
+            if let Some(targetdesc) = fields.get(1) {
+                let attr_count = targetdesc.split(|&b| b == b',').count();
+                if attr_count > self.max_attrs {
+                    return Err(LimitExceeded::Attrs {
+                        limit: self.max_attrs,
+                        found: attr_count,
+                    });
+                }
+                for attr in targetdesc.split(|&b| b == b',') {
+                    if let Some(containers) = attr.strip_prefix(b"Intermediates:".as_slice()) {
+                        let container_count = containers.split(|&b| b == b'>').count();
+                        if container_count > self.max_intermediates {
+                            return Err(LimitExceeded::Intermediates {
+                                limit: self.max_intermediates,
+                                found: container_count,
+                            });
+                        }
+                    }
+                }
+            }
+
+            if let Some(expression) = fields.get(2) {
+                if expression.len() > self.max_expression_len {
+                    return Err(LimitExceeded::ExpressionLen {
+                        limit: self.max_expression_len,
+                        found: expression.len(),
+                    });
+                }
+            }
+
+            let subsig_count = fields.len().saturating_sub(3);
+            if subsig_count > self.max_subsigs {
+                return Err(LimitExceeded::SubSigs {
+                    limit: self.max_subsigs,
+                    found: subsig_count,
+                });
+            }
+        } else {
+            let field_count = bytes.split(|&b| b == b':').count();
+            if field_count > self.max_fields {
+                return Err(LimitExceeded::Fields {
+                    limit: self.max_fields,
+                    found: field_count,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// Parse a CVD-style (single-line) signature from a CVD database. Since each
 /// signature type has its own format, the format must be specified.
 ///
@@ -218,21 +730,236 @@ pub fn parse_from_cvd_with_meta(
     sig_type: SigType,
     data: &SigBytes,
 ) -> Result<(Box<dyn Signature>, SigMeta), FromSigBytesParseError> {
-    let (sig, sigmeta) = match sig_type {
-        SigType::Extended => ext_sig::ExtendedSig::from_sigbytes(data)?,
-        SigType::Logical => logical_sig::LogicalSig::from_sigbytes(data)?,
-        SigType::FileHash => filehash::FileHashSig::from_sigbytes(data)?,
-        SigType::PESectionHash => pehash::PESectionHashSig::from_sigbytes(data)?,
+    parse_from_cvd_checked(sig_type, data, &Limits::default())
+}
+
+/// As [`parse_from_cvd_with_meta`], but returning the parsed signature as an
+/// [`AnySignature`](any_sig::AnySignature) held by value, for consumers that
+/// pattern-match on the concrete type rather than downcasting a trait object.
+pub fn parse_from_cvd_typed(
+    sig_type: SigType,
+    data: &SigBytes,
+) -> Result<(any_sig::AnySignature, SigMeta), FromSigBytesParseError> {
+    let (sig, sigmeta) = parse_from_cvd_with_meta(sig_type, data)?;
+    // The dispatch above only produces types with a variant, so this
+    // conversion can't fail in practice
+    let sig = any_sig::AnySignature::try_from(sig)
+        .map_err(|_| FromSigBytesParseError::UnsupportedSigType)?;
+    Ok((sig, sigmeta))
+}
+
+/// [`parse_from_cvd_with_meta`], with control-character rejection and
+/// trailing-field handling subject to the caller's profile
+fn parse_from_cvd_checked(
+    sig_type: SigType,
+    data: &SigBytes,
+    limits: &Limits,
+) -> Result<(Box<dyn Signature>, SigMeta), FromSigBytesParseError> {
+    // Reject control characters up front: they're never legitimate, and
+    // produce baffling errors (or silent truncation) if left for the field
+    // parsers to trip over
+    if !limits.allow_control_characters {
+        if let Some((pos, &byte)) = data
+            .as_bytes()
+            .iter()
+            .enumerate()
+            .find(|&(_, &b)| b < 0x20 && b != b'\t')
+        {
+            return Err(FromSigBytesParseError::ControlCharacter { byte, pos });
+        }
+    }
+
+    let trailing_mode = if limits.preserve_trailing_fields {
+        TrailingFieldMode::Preserve
+    } else {
+        TrailingFieldMode::Error
+    };
+
+    let result = match sig_type {
+        SigType::Extended => ext_sig::ExtendedSig::from_sigbytes(data),
+        SigType::Logical => logical_sig::LogicalSig::from_sigbytes(data),
+        SigType::FileHash => filehash::FileHashSig::from_sigbytes(data),
+        SigType::PESectionHash => pehash::PESectionHashSig::from_sigbytes(data),
         SigType::ContainerMetadata => {
-            container_metadata_sig::ContainerMetadataSig::from_sigbytes(data)?
+            container_metadata_sig::ContainerMetadataSig::from_sigbytes_with_mode(
+                data,
+                trailing_mode,
+            )
         }
-        SigType::PhishingURL => phishing_sig::PhishingSig::from_sigbytes(data)?,
-        SigType::FTMagic => ftmagic::FTMagicSig::from_sigbytes(data)?,
-        SigType::DigitalSignature => digital_sig::DigitalSig::from_sigbytes(data)?,
+        SigType::PhishingURL => {
+            phishing_sig::PhishingSig::from_sigbytes_with_mode(data, trailing_mode)
+        }
+        SigType::FTMagic => ftmagic::FTMagicSig::from_sigbytes(data),
+        SigType::Icon => icon_sig::IconSig::from_sigbytes(data),
+        SigType::DigitalSignature => digital_sig::DigitalSig::from_sigbytes(data),
         _ => return Err(FromSigBytesParseError::UnsupportedSigType),
     };
 
-    Ok((sig, sigmeta))
+    // Parse errors arising from the wrong SigType being specified are
+    // baffling on their own; check whether the input resembles a different
+    // format and, if so, say so.
+    result.map_err(|err| match detect_sig_type(data) {
+        Some(looks_like) if looks_like != sig_type => FromSigBytesParseError::SigTypeMismatch {
+            looks_like,
+            source: Box::new(err),
+        },
+        _ => err,
+    })
+}
+
+/// Parse a CVD-style (single-line) signature as [`parse_from_cvd_with_meta`]
+/// does, first checking the raw record against the provided [`Limits`].  Use
+/// this entry point when loading databases from untrusted sources; the checks
+/// run before any parser allocates in proportion to the input's field counts.
+///
+/// # Arguments
+///
+/// * `sig_type` - the signature type being provided
+/// * `data` - signature content
+/// * `limits` - caps on attacker-controllable sizes within the record
+pub fn parse_from_cvd_with_profile(
+    sig_type: SigType,
+    data: &SigBytes,
+    limits: &Limits,
+) -> Result<(Box<dyn Signature>, SigMeta), FromSigBytesParseError> {
+    limits.check(sig_type, data)?;
+    parse_from_cvd_checked(sig_type, data, limits)
+}
+
+/// Guess the format of an unparseable signature record from its shape.  This
+/// is a heuristic used solely to improve parse-error reporting; `None` means
+/// no recognizable shape, not an invalid record.
+fn detect_sig_type(data: &SigBytes) -> Option<SigType> {
+    let bytes: &[u8] = data.as_bytes();
+
+    // Logical signatures are `;`-separated, with an `Engine:`-prefixed
+    // attribute in the TargetDesc field
+    if bytes
+        .split(|&b| b == b';')
+        .any(|field| field.starts_with(b"Engine:"))
+    {
+        return Some(SigType::Logical);
+    }
+
+    // GSB phishing lines lead with their match type
+    if bytes.starts_with(b"S:") || bytes.starts_with(b"S1:") || bytes.starts_with(b"S2:") {
+        return Some(SigType::PhishingURL);
+    }
+
+    let mut fields = bytes.split(|&b| b == b':');
+    let first = fields.next()?;
+    let second = fields.next();
+    let third = fields.next();
+
+    // Hash-based records lead with a digest of recognizable length and a
+    // decimal (or `*`) size field
+    if crate::util::parse_hash(first).is_ok() {
+        if let Some(size) = second {
+            if size == b"*" || crate::util::parse_number_dec::<u64>(size).is_ok() {
+                return Some(SigType::FileHash);
+            }
+        }
+    }
+
+    // Extended signatures carry a small-integer TargetType followed by an
+    // offset expression
+    if let (Some(target_type), Some(offset)) = (second, third) {
+        if crate::util::parse_number_dec::<usize>(target_type).is_ok_and(|n| {
+            <targettype::TargetType as num_traits::FromPrimitive>::from_usize(n).is_some()
+        }) && ext_sig::Offset::try_from(offset).is_ok()
+        {
+            return Some(SigType::Extended);
+        }
+    }
+
+    None
+}
+
+/// Components extracted from a signature name following the dotted
+/// `Platform.Category.Family-VariantId` naming convention, as returned by
+/// [`name_info`].  Names that don't follow the convention yield partial
+/// information, with the unidentifiable components left as `None`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct NameInfo<'a> {
+    pua: bool,
+
+    /// Platform or file format the signature applies to (e.g., `Win`, `Doc`)
+    pub platform: Option<&'a str>,
+
+    /// Threat category (e.g., `Trojan`, `Packer`)
+    pub category: Option<&'a str>,
+
+    /// Malware family name
+    pub family: Option<&'a str>,
+
+    /// Numeric variant identifier following the family name
+    pub variant_id: Option<&'a str>,
+}
+
+impl NameInfo<'_> {
+    /// Whether the name carries the `PUA.` (potentially-unwanted application)
+    /// prefix used by the `.ldu`/`.ndu`/`.hdu` databases
+    #[must_use]
+    pub fn is_pua(&self) -> bool {
+        self.pua
+    }
+}
+
+/// Parse a signature name following the dotted
+/// `[PUA.]Platform.Category.Family-VariantId` naming convention (e.g.,
+/// `PUA.Win.Packer.Upx-57`).
+///
+/// Parsing is tolerant of names that don't follow the convention:
+///
+/// - a name without dots is taken to be a bare family name (e.g.,
+///   `Eicar-Test-Signature`);
+/// - a name with a single dot is taken to be `Platform.Family`, with no
+///   category;
+/// - additional dotted segments between the category and family are ignored.
+///
+/// A variant identifier is split off only when the text following the
+/// family's final dash is entirely decimal digits, so families with embedded
+/// dashes or digits are preserved intact.
+#[must_use]
+pub fn name_info(name: &str) -> NameInfo<'_> {
+    let mut segments: &[&str] = &name.split('.').collect::<Vec<_>>();
+    let pua = segments.first() == Some(&"PUA");
+    if pua {
+        segments = &segments[1..];
+    }
+
+    let mut info = NameInfo {
+        pua,
+        ..NameInfo::default()
+    };
+    match segments {
+        [] => (),
+        [family] => (info.family, info.variant_id) = split_variant(family),
+        [platform, rest @ ..] => {
+            info.platform = Some(platform);
+            if let [category, .., family] = rest {
+                info.category = Some(category);
+                (info.family, info.variant_id) = split_variant(family);
+            } else if let [family] = rest {
+                (info.family, info.variant_id) = split_variant(family);
+            }
+        }
+    }
+    info
+}
+
+/// Split a numeric variant identifier from the end of a family name.  Only an
+/// all-digit suffix after the final dash is treated as a variant id.
+fn split_variant(family: &str) -> (Option<&str>, Option<&str>) {
+    if family.is_empty() {
+        return (None, None);
+    }
+    if let Some((fam, variant)) = family.rsplit_once('-') {
+        if !fam.is_empty() && !variant.is_empty() && variant.bytes().all(|b| b.is_ascii_digit()) {
+            return (Some(fam), Some(variant));
+        }
+    }
+    (Some(family), None)
 }
 
 /// Errors that can be encountered while parsing signature input
@@ -250,8 +977,13 @@ pub enum FromSigBytesParseError {
     #[error("invalid value for: {0}")]
     InvalidValueFor(String),
 
-    #[error("signature name not unicode")]
-    NameNotUnicode(std::str::Utf8Error),
+    #[error("signature name not unicode (valid up to byte {valid_up_to})")]
+    NameNotUnicode {
+        #[source]
+        source: std::str::Utf8Error,
+        valid_up_to: usize,
+        raw: SigBytes,
+    },
 
     #[error("parsing hash-based signature: {0}")]
     HashSig(#[from] hash::ParseError),
@@ -270,9 +1002,76 @@ pub enum FromSigBytesParseError {
 
     #[error("parsing file type magic signature: {0}")]
     FTMagicSig(#[from] ftmagic::FTMagicParseError),
+
+    #[error("parsing icon signature: {0}")]
+    IconSig(#[from] icon_sig::ParseError),
+
+    /// The record exceeded one of the caps configured for untrusted parsing
+    #[error("limit exceeded: {0}")]
+    LimitExceeded(#[from] LimitExceeded),
+
+    /// The record carries more fields than its format defines.  Reported
+    /// only when trailing fields are not being preserved; see
+    /// [`TrailingFieldMode`].
+    #[error("record has {count} unconsumed trailing field(s), beginning at field {first_pos}")]
+    TrailingFields { count: usize, first_pos: usize },
+
+    /// The record contains an ASCII control character (other than tab),
+    /// which no signature format legitimately carries; these typically
+    /// indicate a corrupted download, and silently truncate at C-string
+    /// boundaries in downstream consumers
+    #[error("control character {byte:#04x} at pos {pos}")]
+    ControlCharacter { byte: u8, pos: usize },
+
+    /// The input failed to parse as the specified type, but its shape
+    /// resembles a different signature format
+    #[error("{source} (input looks like a {looks_like:?} signature; was the correct signature type specified?)")]
+    SigTypeMismatch {
+        looks_like: SigType,
+        source: Box<FromSigBytesParseError>,
+    },
 }
 
-#[derive(Error, Debug, PartialEq)]
+/// Decode a signature name field as UTF-8, capturing the raw bytes and the
+/// offending offset on failure so reports can still render the name
+pub(crate) fn parse_sig_name(bytes: &[u8]) -> Result<&str, FromSigBytesParseError> {
+    str::from_utf8(bytes).map_err(|source| FromSigBytesParseError::NameNotUnicode {
+        valid_up_to: source.valid_up_to(),
+        raw: bytes.into(),
+        source,
+    })
+}
+
+impl FromSigBytesParseError {
+    /// For [`NameNotUnicode`](Self::NameNotUnicode) errors, the signature
+    /// name rendered lossily (non-UTF-8 bytes escaped) for display
+    #[must_use]
+    pub fn lossy_name(&self) -> Option<String> {
+        if let FromSigBytesParseError::NameNotUnicode { raw, .. } = self {
+            Some(raw.to_string_lossy())
+        } else {
+            None
+        }
+    }
+
+    /// The 1-based position of the delimited field in which this error arose,
+    /// where known, with the signature name as field 1.  Returns `None` for
+    /// errors not attributable to a single field, or for signature types that
+    /// don't report field positions.
+    #[must_use]
+    pub fn field_index(&self) -> Option<usize> {
+        match self {
+            FromSigBytesParseError::MissingName | FromSigBytesParseError::NameNotUnicode { .. } => {
+                Some(1)
+            }
+            FromSigBytesParseError::ExtendedSig(e) => e.field_index(),
+            FromSigBytesParseError::LogicalSig(e) => e.field_index(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq, Hash)]
 pub enum SigValidationError {
     #[error("validating hash-based signature: {0}")]
     HashSig(#[from] hash::ValidationError),
@@ -283,16 +1082,579 @@ pub enum SigValidationError {
     #[error("validating container metadata signature: {0}")]
     ContainerMetaSig(#[from] container_metadata_sig::ValidationError),
 
-    #[error("specified minimum feature level ({spec_min_flevel}) is lower than computed ({computed_min_flevel}), requires features {feature_set:?}")]
+    #[error("validating phishing signature: {0}")]
+    PhishingSig(#[from] phishing_sig::ValidationError),
+
+    #[error(
+        "specified minimum feature level ({}) is lower than computed ({}), requires features {feature_set:?}",
+        feature::flevel_with_version(*spec_min_flevel),
+        feature::flevel_with_version(*computed_min_flevel)
+    )]
     SpecifiedMinFLevelTooLow {
         spec_min_flevel: u32,
         computed_min_flevel: u32,
         feature_set: feature::SetWithMinFlevel,
     },
 
-    #[error("minimum feature level unspecified; must be at least ({computed_min_flevel}), requires features {feature_set:?}")]
+    #[error(
+        "minimum feature level unspecified; must be at least ({}), requires features {feature_set:?}",
+        feature::flevel_with_version(*computed_min_flevel)
+    )]
     MinFLevelNotSpecified {
         computed_min_flevel: u32,
         feature_set: feature::SetWithMinFlevel,
     },
 }
+
+impl SigValidationError {
+    /// A stable, machine-readable code identifying this error's kind, under
+    /// the same stability guarantee as [`SigWarning::code`]
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            SigValidationError::HashSig(_) => "validation-hash",
+            SigValidationError::LogicalSig(_) => "validation-logical",
+            SigValidationError::ContainerMetaSig(_) => "validation-container-metadata",
+            SigValidationError::PhishingSig(_) => "validation-phishing",
+            SigValidationError::SpecifiedMinFLevelTooLow { .. } => "min-flevel-too-low",
+            SigValidationError::MinFLevelNotSpecified { .. } => "min-flevel-unspecified",
+        }
+    }
+}
+
+/// A non-fatal condition detected within a well-formed signature, as reported
+/// by [`Signature::warnings`]
+#[derive(Error, Debug, PartialEq, Eq, Hash)]
+pub enum SigWarning {
+    /// The signature's body contains `count` unbounded skips (`*` wildcards or
+    /// open-ended byte ranges), each of which forces the matcher to restart
+    /// its search
+    #[error("body signature contains {count} unbounded skips, which may scan slowly")]
+    MultipleWildcards { count: usize },
+
+    /// The body signature constrains no byte values at all, so it would
+    /// match (nearly) every file scanned
+    #[error("body signature is trivially matching (constrains no byte values)")]
+    TriviallyMatching,
+
+    /// The body signature matches on very few distinct byte values (e.g., a
+    /// run of `0x00` padding), so it's likely to match large amounts of
+    /// unrelated content
+    #[error("body signature matches only {distinct} distinct byte value(s)")]
+    LowByteDiversity { distinct: usize },
+
+    /// The logical expression parsed, but has modifier semantics that are
+    /// meaningless or engine-rejected
+    #[error("logical expression: {0}")]
+    Expression(logical_sig::expression::ExprDiagnostic),
+
+    /// A phishing hostname contains non-ASCII (IDN) characters.  The engine
+    /// compares hostnames byte-wise, so the punycode (ACE, `xn--`) form
+    /// should be used instead.
+    #[error("hostname `{host}` is not ASCII; consider the punycode (ACE) form")]
+    NonAsciiHostname { host: String },
+}
+
+impl SigWarning {
+    /// A stable, machine-readable code identifying this warning's kind.
+    /// These codes are part of the versioned report schema (see
+    /// [`crate::audit`]) and are never renamed or removed within a schema
+    /// major version.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        use logical_sig::expression::ExprDiagnostic;
+        match self {
+            SigWarning::MultipleWildcards { .. } => "multiple-wildcards",
+            SigWarning::TriviallyMatching => "trivially-matching",
+            SigWarning::LowByteDiversity { .. } => "low-byte-diversity",
+            SigWarning::Expression(diag) => match diag {
+                ExprDiagnostic::ZeroMatchModifier { .. } => "expr-zero-match-modifier",
+                ExprDiagnostic::MatchUniqExceedsDistinct { .. } => {
+                    "expr-match-uniq-exceeds-distinct"
+                }
+                ExprDiagnostic::MatchUniqOnSingleIndex { .. } => "expr-match-uniq-single-index",
+                ExprDiagnostic::MatchReqExceedsAndGroup { .. } => "expr-match-req-exceeds-group",
+            },
+            SigWarning::NonAsciiHostname { .. } => "non-ascii-hostname",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sigmeta_engine_attr() {
+        use logical_sig::targetdesc::TargetDescAttr;
+
+        // An open upper bound is rendered as 255
+        let sigmeta = SigMeta {
+            f_level: Some((81..).into()),
+        };
+        assert_eq!(
+            sigmeta.engine_attr(),
+            Some(TargetDescAttr::Engine((81..=255).into()))
+        );
+
+        // A closed range is passed through
+        let sigmeta = SigMeta {
+            f_level: Some((81..=200).into()),
+        };
+        assert_eq!(
+            sigmeta.engine_attr(),
+            Some(TargetDescAttr::Engine((81..=200).into()))
+        );
+
+        // No feature level, or a maximum without a minimum, yields no attribute
+        assert_eq!(SigMeta::default().engine_attr(), None);
+        let sigmeta = SigMeta {
+            f_level: Some((..=100).into()),
+        };
+        assert_eq!(sigmeta.engine_attr(), None);
+    }
+
+    #[test]
+    fn sigmeta_flevel_accessors() {
+        // The range-based representation, consumed via the accessors
+        let sigmeta = SigMeta::from((81, 255));
+        assert_eq!(sigmeta.min_flevel(), Some(81));
+        assert_eq!(sigmeta.max_flevel(), Some(255));
+        assert_eq!(sigmeta, SigMeta::from(81..=255));
+
+        let open = SigMeta {
+            f_level: Some((81..).into()),
+        };
+        assert_eq!(open.min_flevel(), Some(81));
+        assert_eq!(open.max_flevel(), None);
+
+        assert_eq!(SigMeta::default().min_flevel(), None);
+        assert_eq!(SigMeta::default().max_flevel(), None);
+    }
+
+    #[test]
+    fn control_characters_rejected_early() {
+        // A NUL mid-name
+        let input = b"44d88612fea8a8f36de82e1278abb02f:68:Eicar\x00Test"
+            .as_slice()
+            .into();
+        assert!(matches!(
+            parse_from_cvd_with_meta(SigType::FileHash, &input),
+            Err(FromSigBytesParseError::ControlCharacter { byte: 0, pos: 41 })
+        ));
+
+        // A 0x01 inside a body field
+        let input = b"TestSig;Engine:51-255,Target:0;(0&1);4141\x0141;424242"
+            .as_slice()
+            .into();
+        assert!(matches!(
+            parse_from_cvd_with_meta(SigType::Logical, &input),
+            Err(FromSigBytesParseError::ControlCharacter { byte: 1, pos: 41 })
+        ));
+
+        // The permissive profile can opt out; the parser then reports its
+        // own (less targeted) error
+        let limits = Limits {
+            allow_control_characters: true,
+            ..Limits::default()
+        };
+        assert!(matches!(
+            parse_from_cvd_with_profile(SigType::Logical, &input, &limits),
+            Err(FromSigBytesParseError::LogicalSig(_))
+        ));
+
+        // Tab doesn't trip the check
+        let input = b"44d88612fea8a8f36de82e1278abb02f:68:Eicar\tTest"
+            .as_slice()
+            .into();
+        assert!(parse_from_cvd_with_meta(SigType::FileHash, &input).is_ok());
+    }
+
+    #[test]
+    fn limits_each_trigger_independently() {
+        const LOGICAL_SIG: &str = concat!(
+            "Test.Sig;Engine:51-255,Target:0,",
+            "Intermediates:CL_TYPE_ZIP>CL_TYPE_RAR;",
+            "(0&1)&(2|3);414141;424242;434343;444444",
+        );
+        const HASH_SIG: &str = "44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature";
+
+        fn err_with(sig_type: SigType, sig: &str, limits: &Limits) -> LimitExceeded {
+            match parse_from_cvd_with_profile(sig_type, &SigBytes::from(sig), limits) {
+                Err(FromSigBytesParseError::LimitExceeded(e)) => e,
+                other => panic!("expected LimitExceeded, got {other:?}"),
+            }
+        }
+
+        // Defaults admit well-formed records unchanged
+        assert!(parse_from_cvd_with_profile(
+            SigType::Logical,
+            &SigBytes::from(LOGICAL_SIG),
+            &Limits::default()
+        )
+        .is_ok());
+        assert!(parse_from_cvd_with_profile(
+            SigType::FileHash,
+            &SigBytes::from(HASH_SIG),
+            &Limits::default()
+        )
+        .is_ok());
+
+        assert_eq!(
+            err_with(
+                SigType::FileHash,
+                HASH_SIG,
+                &Limits {
+                    max_line_len: 16,
+                    ..Limits::default()
+                }
+            ),
+            LimitExceeded::LineLen {
+                limit: 16,
+                found: HASH_SIG.len(),
+            }
+        );
+        assert_eq!(
+            err_with(
+                SigType::FileHash,
+                HASH_SIG,
+                &Limits {
+                    max_fields: 2,
+                    ..Limits::default()
+                }
+            ),
+            LimitExceeded::Fields { limit: 2, found: 3 }
+        );
+        assert_eq!(
+            err_with(
+                SigType::Logical,
+                LOGICAL_SIG,
+                &Limits {
+                    max_attrs: 2,
+                    ..Limits::default()
+                }
+            ),
+            LimitExceeded::Attrs { limit: 2, found: 3 }
+        );
+        assert_eq!(
+            err_with(
+                SigType::Logical,
+                LOGICAL_SIG,
+                &Limits {
+                    max_intermediates: 1,
+                    ..Limits::default()
+                }
+            ),
+            LimitExceeded::Intermediates { limit: 1, found: 2 }
+        );
+        assert_eq!(
+            err_with(
+                SigType::Logical,
+                LOGICAL_SIG,
+                &Limits {
+                    max_subsigs: 3,
+                    ..Limits::default()
+                }
+            ),
+            LimitExceeded::SubSigs { limit: 3, found: 4 }
+        );
+        assert_eq!(
+            err_with(
+                SigType::Logical,
+                LOGICAL_SIG,
+                &Limits {
+                    max_expression_len: 4,
+                    ..Limits::default()
+                }
+            ),
+            LimitExceeded::ExpressionLen {
+                limit: 4,
+                found: "(0&1)&(2|3)".len(),
+            }
+        );
+    }
+
+    #[test]
+    fn wrong_sig_type_reports_likely_format() {
+        fn err_of(sig_type: SigType, sig: &str) -> FromSigBytesParseError {
+            parse_from_cvd(sig_type, &SigBytes::from(sig)).unwrap_err()
+        }
+
+        // A logical signature fed to the extended parser
+        let err = err_of(
+            SigType::Extended,
+            "Test.Sig;Engine:51-255,Target:0;(0&1);414141;424242",
+        );
+        assert!(matches!(
+            err,
+            FromSigBytesParseError::SigTypeMismatch {
+                looks_like: SigType::Logical,
+                ..
+            }
+        ));
+        assert!(err.to_string().contains("looks like a Logical signature"));
+
+        // An extended signature fed to the logical parser
+        let err = err_of(SigType::Logical, "Test.Sig:1:EP+0:aabbccdd");
+        assert!(matches!(
+            err,
+            FromSigBytesParseError::SigTypeMismatch {
+                looks_like: SigType::Extended,
+                ..
+            }
+        ));
+
+        // A file hash record fed to the phishing parser, and vice versa
+        let err = err_of(
+            SigType::PhishingURL,
+            "44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature",
+        );
+        assert!(matches!(
+            err,
+            FromSigBytesParseError::SigTypeMismatch {
+                looks_like: SigType::FileHash,
+                ..
+            }
+        ));
+        let err = err_of(
+            SigType::FileHash,
+            "S1:P:00112233*44d88612fea8a8f36de82e1278abb02f",
+        );
+        assert!(matches!(
+            err,
+            FromSigBytesParseError::SigTypeMismatch {
+                looks_like: SigType::PhishingURL,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn error_source_chains_to_inner_error() {
+        use crate::signature::bodysig::parse::BodySigParseError;
+        use std::error::Error as _;
+
+        // A body signature failure nested inside a logical signature failure:
+        // each level of the chain must be reachable via `source()`
+        let err = FromSigBytesParseError::LogicalSig(logical_sig::ParseError::BodySigParse(
+            1,
+            BodySigParseError::Empty,
+        ));
+        let mid = err.source().expect("logical ParseError as source");
+        assert!(mid.downcast_ref::<logical_sig::ParseError>().is_some());
+        let inner = mid.source().expect("BodySigParseError as source");
+        assert_eq!(
+            inner.downcast_ref::<BodySigParseError>(),
+            Some(&BodySigParseError::Empty)
+        );
+
+        // The root cause of a live parse failure (here, an empty body
+        // sub-signature) is reachable by walking the `source()` chain
+        let err = parse_from_cvd(
+            SigType::Logical,
+            &SigBytes::from("Test.Sig;Engine:51-255,Target:0;0&1;;414141"),
+        )
+        .unwrap_err();
+        let mut cause: &dyn std::error::Error = &err;
+        while let Some(source) = cause.source() {
+            cause = source;
+        }
+        assert_eq!(
+            cause.downcast_ref::<BodySigParseError>(),
+            Some(&BodySigParseError::Empty)
+        );
+    }
+
+    #[test]
+    fn fingerprint_normalizes_formatting() {
+        fn fp(sig: &str) -> [u8; 32] {
+            parse_from_cvd(SigType::Extended, &SigBytes::from(sig))
+                .unwrap()
+                .fingerprint()
+        }
+        // Hex case is normalized away
+        assert_eq!(fp("Test.Sig:0:*:AABB*CCDD"), fp("Test.Sig:0:*:aabb*ccdd"));
+        // A semantic change yields a different fingerprint
+        assert_ne!(fp("Test.Sig:0:*:aabb*ccdd"), fp("Test.Sig:0:*:aabb*ccde"));
+    }
+
+    #[test]
+    fn name_info_pua() {
+        let info = name_info("PUA.Win.Packer.Upx-57");
+        assert!(info.is_pua());
+        assert_eq!(info.platform, Some("Win"));
+        assert_eq!(info.category, Some("Packer"));
+        assert_eq!(info.family, Some("Upx"));
+        assert_eq!(info.variant_id, Some("57"));
+    }
+
+    #[test]
+    fn name_info_conventional() {
+        let info = name_info("Win.Trojan.MSShellcode-6360730");
+        assert!(!info.is_pua());
+        assert_eq!(info.platform, Some("Win"));
+        assert_eq!(info.category, Some("Trojan"));
+        assert_eq!(info.family, Some("MSShellcode"));
+        assert_eq!(info.variant_id, Some("6360730"));
+
+        // Only an all-digit suffix after the final dash is a variant id;
+        // embedded dashes remain part of the family
+        let info = name_info("Doc.Dropper.Agent-revoked-6");
+        assert_eq!(info.family, Some("Agent-revoked"));
+        assert_eq!(info.variant_id, Some("6"));
+    }
+
+    #[test]
+    fn name_info_freeform() {
+        // No dots: the whole name is the family (the dash suffix here isn't
+        // numeric)
+        let info = name_info("Eicar-Test-Signature");
+        assert!(!info.is_pua());
+        assert_eq!(info.platform, None);
+        assert_eq!(info.category, None);
+        assert_eq!(info.family, Some("Eicar-Test-Signature"));
+        assert_eq!(info.variant_id, None);
+
+        // A single dot yields platform and family, but no category
+        let info = name_info("Win.Exploit");
+        assert_eq!(info.platform, Some("Win"));
+        assert_eq!(info.category, None);
+        assert_eq!(info.family, Some("Exploit"));
+
+        // A bare PUA prefix yields no further components
+        let info = name_info("PUA");
+        assert!(info.is_pua());
+        assert_eq!(
+            info,
+            NameInfo {
+                pua: true,
+                ..NameInfo::default()
+            }
+        );
+    }
+
+    #[test]
+    fn name_info_extra_segments() {
+        // Segments between the category and family are ignored
+        let info = name_info("Win.Trojan.Generic.Agent-123");
+        assert_eq!(info.platform, Some("Win"));
+        assert_eq!(info.category, Some("Trojan"));
+        assert_eq!(info.family, Some("Agent"));
+        assert_eq!(info.variant_id, Some("123"));
+    }
+
+    /// Format one instance of representative error variants so that Display
+    /// regressions (wrong positions, swapped fields) show up as test failures
+    #[test]
+    fn error_display_snapshots() {
+        use crate::signature::bodysig::parse::{BodySigParseError, Context};
+        use crate::signature::bodysig::{BodySigLint, LintPosition};
+        use crate::signature::logical_sig::targetdesc::TargetDescValidationError;
+        use crate::util::{ParseNumberError, Position};
+
+        assert_eq!(
+            FromSigBytesParseError::MissingField("Offset".into()).to_string(),
+            "missing field: Offset"
+        );
+        assert_eq!(
+            LimitExceeded::LineLen {
+                limit: 16,
+                found: 512
+            }
+            .to_string(),
+            "record length 512 exceeds limit of 16 bytes"
+        );
+        assert_eq!(
+            logical_sig::ValidationError::SubSigMissingBody { idx: 2 }.to_string(),
+            "subsig 2 has no body; logical subsigs may not be `*`"
+        );
+        assert_eq!(
+            logical_sig::ValidationError::TooManySubSigs { count: 70, max: 64 }.to_string(),
+            "70 subsigs exceeds the engine maximum of 64"
+        );
+        assert_eq!(
+            logical_sig::ConversionError::NotOneSubSig(3).to_string(),
+            "expected exactly one subsig, found 3"
+        );
+        assert_eq!(
+            TargetDescValidationError::EngineNotMinimum { found: 40 }.to_string(),
+            "Engine minimum (40) is lower than allowed minimum (51)"
+        );
+        assert_eq!(
+            BodySigParseError::BracketNotClosed {
+                start_pos: Position::span(4, 6)
+            }
+            .to_string(),
+            "bracket expression from pos 4 to 6 not closed"
+        );
+        assert_eq!(
+            BodySigParseError::UnexpectedChar {
+                context: Context::Pattern,
+                pos: Position::Absolute(7),
+                found: b'q'.into(),
+            }
+            .to_string(),
+            "unexpected character 'q' at pos 7 within pattern"
+        );
+        assert_eq!(
+            ParseNumberError::<i64>::NegativeValue(-3).to_string(),
+            "negative value: -3"
+        );
+        assert_eq!(
+            BodySigLint::RepeatedByteRun {
+                position: LintPosition::Leading,
+                byte: 0x90,
+                count: 12
+            }
+            .to_string(),
+            "leading run of 12 repeated 0x90 bytes is prone to false positives; \
+             anchor with an offset or add more distinctive bytes"
+        );
+        assert_eq!(
+            SigWarning::MultipleWildcards { count: 3 }.to_string(),
+            "body signature contains 3 unbounded skips, which may scan slowly"
+        );
+    }
+
+    #[test]
+    fn sigmeta_merge_takes_stricter_bounds() {
+        let none = SigMeta::default();
+        let low = SigMeta {
+            f_level: Some((51..=120).into()),
+        };
+        let high = SigMeta {
+            f_level: Some((81..=255).into()),
+        };
+
+        assert_eq!(none.merge(&none), none);
+        assert_eq!(none.merge(&high), high);
+        assert_eq!(high.merge(&none), high);
+        assert_eq!(
+            low.merge(&high),
+            SigMeta {
+                f_level: Some((81..=120).into()),
+            }
+        );
+        assert!(low.merge(&high).is_satisfiable());
+
+        // Conflicting sources produce an empty, unsatisfiable range
+        let floor = SigMeta {
+            f_level: Some((121..).into()),
+        };
+        let merged = low.merge(&floor);
+        assert_eq!(merged.f_level.as_ref().and_then(Range::start), Some(121));
+        assert_eq!(merged.f_level.as_ref().and_then(Range::end), Some(120));
+        assert!(!merged.is_satisfiable());
+    }
+
+    /// Identical diagnostics collapse via `Eq`/`Hash`, allowing reports to
+    /// show one entry with a count when the same error recurs across lines
+    #[test]
+    fn identical_errors_collapse_in_hash_set() {
+        let mut seen = std::collections::HashSet::new();
+        seen.insert(logical_sig::ValidationError::SubSigMissingBody { idx: 1 });
+        seen.insert(logical_sig::ValidationError::SubSigMissingBody { idx: 1 });
+        seen.insert(logical_sig::ValidationError::SubSigMissingBody { idx: 2 });
+        assert_eq!(seen.len(), 2);
+    }
+}
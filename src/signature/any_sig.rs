@@ -0,0 +1,264 @@
+/*
+ *  Copyright (C) 2024 Cisco Systems, Inc. and/or its affiliates. All rights reserved.
+ *
+ *  This program is free software; you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License version 2 as
+ *  published by the Free Software Foundation.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program; if not, write to the Free Software
+ *  Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston,
+ *  MA 02110-1301, USA.
+ */
+
+//! An owned, exhaustively-matchable view of a parsed signature.
+//! [`parse_from_cvd`](crate::signature::parse_from_cvd) returns a boxed trait
+//! object, which consumers that care about the concrete type must downcast;
+//! [`AnySignature`] instead carries each supported type as an enum variant, so
+//! such consumers can pattern-match (with exhaustiveness checking) while still
+//! using the [`Signature`] trait methods, which are forwarded.
+
+use super::{
+    container_metadata_sig::ContainerMetadataSig, digital_sig::DigitalSig, ext_sig::ExtendedSig,
+    filehash::FileHashSig, ftmagic::FTMagicSig, icon_sig::IconSig, logical_sig::LogicalSig,
+    pehash::PESectionHashSig, phishing_sig::PhishingSig, SigMeta, SigValidationError, SigWarning,
+    Signature, ToSigBytesError,
+};
+use crate::{
+    feature::EngineReq,
+    sigbytes::{AppendSigBytes, SigBytes},
+    SigType,
+};
+
+/// A parsed signature of any supported type, held by value
+#[derive(Debug)]
+pub enum AnySignature {
+    /// An [extended signature](ExtendedSig)
+    Extended(ExtendedSig),
+    /// A [logical signature](LogicalSig)
+    Logical(LogicalSig),
+    /// A [file hash signature](FileHashSig)
+    FileHash(FileHashSig),
+    /// A [PE section hash signature](PESectionHashSig)
+    PESectionHash(PESectionHashSig),
+    /// A [container metadata signature](ContainerMetadataSig)
+    ContainerMetadata(ContainerMetadataSig),
+    /// A [phishing signature](PhishingSig)
+    Phishing(PhishingSig),
+    /// A [filetype magic signature](FTMagicSig)
+    FTMagic(FTMagicSig),
+    /// A [digital signature](DigitalSig)
+    Digital(DigitalSig),
+    /// An [icon signature](IconSig)
+    Icon(IconSig),
+}
+
+impl AnySignature {
+    /// The contained signature, as a trait object
+    #[must_use]
+    pub fn inner(&self) -> &dyn Signature {
+        match self {
+            Self::Extended(sig) => sig,
+            Self::Logical(sig) => sig,
+            Self::FileHash(sig) => sig,
+            Self::PESectionHash(sig) => sig,
+            Self::ContainerMetadata(sig) => sig,
+            Self::Phishing(sig) => sig,
+            Self::FTMagic(sig) => sig,
+            Self::Digital(sig) => sig,
+            Self::Icon(sig) => sig,
+        }
+    }
+
+    /// The contained signature, as a mutable trait object
+    pub fn inner_mut(&mut self) -> &mut dyn Signature {
+        match self {
+            Self::Extended(sig) => sig,
+            Self::Logical(sig) => sig,
+            Self::FileHash(sig) => sig,
+            Self::PESectionHash(sig) => sig,
+            Self::ContainerMetadata(sig) => sig,
+            Self::Phishing(sig) => sig,
+            Self::FTMagic(sig) => sig,
+            Self::Digital(sig) => sig,
+            Self::Icon(sig) => sig,
+        }
+    }
+}
+
+impl TryFrom<Box<dyn Signature>> for AnySignature {
+    type Error = Box<dyn Signature>;
+
+    /// Unbox a signature into its concrete type.  Returns the original boxed
+    /// signature if its type has no variant here (or if a foreign `Signature`
+    /// implementation misreports its type).
+    fn try_from(sig: Box<dyn Signature>) -> Result<Self, Self::Error> {
+        match sig.sig_type() {
+            SigType::Extended => sig.downcast::<ExtendedSig>().map(|s| Self::Extended(*s)),
+            SigType::Logical => sig.downcast::<LogicalSig>().map(|s| Self::Logical(*s)),
+            SigType::FileHash => sig.downcast::<FileHashSig>().map(|s| Self::FileHash(*s)),
+            SigType::PESectionHash => sig
+                .downcast::<PESectionHashSig>()
+                .map(|s| Self::PESectionHash(*s)),
+            SigType::ContainerMetadata => sig
+                .downcast::<ContainerMetadataSig>()
+                .map(|s| Self::ContainerMetadata(*s)),
+            SigType::PhishingURL => sig.downcast::<PhishingSig>().map(|s| Self::Phishing(*s)),
+            SigType::FTMagic => sig.downcast::<FTMagicSig>().map(|s| Self::FTMagic(*s)),
+            SigType::DigitalSignature => sig.downcast::<DigitalSig>().map(|s| Self::Digital(*s)),
+            SigType::Icon => sig.downcast::<IconSig>().map(|s| Self::Icon(*s)),
+            _ => Err(sig),
+        }
+    }
+}
+
+impl EngineReq for AnySignature {
+    fn features(&self) -> crate::feature::Set {
+        self.inner().features()
+    }
+}
+
+impl AppendSigBytes for AnySignature {
+    fn append_sigbytes(&self, sb: &mut SigBytes) -> Result<(), ToSigBytesError> {
+        self.inner().append_sigbytes(sb)
+    }
+}
+
+impl Signature for AnySignature {
+    fn name(&self) -> &str {
+        self.inner().name()
+    }
+
+    fn sig_type(&self) -> SigType {
+        self.inner().sig_type()
+    }
+
+    fn literal_strings(&self) -> Vec<Vec<u8>> {
+        self.inner().literal_strings()
+    }
+
+    fn body_sigs(&self) -> Vec<&super::bodysig::BodySig> {
+        self.inner().body_sigs()
+    }
+
+    fn to_sigbytes_with_meta(&self, sigmeta: &SigMeta) -> Result<SigBytes, ToSigBytesError> {
+        self.inner().to_sigbytes_with_meta(sigmeta)
+    }
+
+    fn complexity(&self) -> super::Complexity {
+        self.inner().complexity()
+    }
+
+    fn warnings(&self) -> Vec<SigWarning> {
+        self.inner().warnings()
+    }
+
+    fn estimated_scan_cost(&self) -> u64 {
+        self.inner().estimated_scan_cost()
+    }
+
+    fn append_diagnostic_fields(&self, out: &mut String) {
+        self.inner().append_diagnostic_fields(out);
+    }
+
+    fn validate(&self, sigmeta: &SigMeta) -> Result<(), SigValidationError> {
+        self.inner().validate(sigmeta)
+    }
+
+    fn validate_subelements(&self, sigmeta: &SigMeta) -> Result<(), SigValidationError> {
+        self.inner().validate_subelements(sigmeta)
+    }
+
+    fn validate_flevel(&self, sigmeta: &SigMeta) -> Result<(), SigValidationError> {
+        self.inner().validate_flevel(sigmeta)
+    }
+
+    fn walk(&self, visitor: &mut dyn super::visitor::SigVisitor) {
+        self.inner().walk(visitor);
+    }
+
+    fn walk_mut(&mut self, visitor: &mut dyn super::visitor::SigVisitorMut) {
+        self.inner_mut().walk_mut(visitor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signature::parse_from_cvd_typed;
+
+    // No sample for `SigType::DigitalSignature` here, as the format requires a
+    // full PKCS7 blob
+    const SAMPLES: &[(SigType, &[u8])] = &[
+        (SigType::Extended, b"Test.Sig:0:*:aabb*ccdd"),
+        (
+            SigType::Logical,
+            b"TestSig;Engine:51-255,Target:0;(0&1);414141;424242",
+        ),
+        (
+            SigType::FileHash,
+            b"44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature",
+        ),
+        (
+            SigType::PESectionHash,
+            b"45056:f9b304ced34fcce3ab75c6dc58ad59e4d62177ffed35494f79f09bc4e8986c16:Win.Test.EICAR_MSB-1",
+        ),
+        (
+            SigType::ContainerMetadata,
+            br"Email.Trojan.Toa-1:CL_TYPE_ZIP:1337:Courrt.{1,15}\.scr$:220-221:2008:0:2010:*:99:101",
+        ),
+        (SigType::PhishingURL, br"R:.*\.com:.*\.org:99-105"),
+        (
+            SigType::FTMagic,
+            b"0:0:ffd8ff:JPEG:CL_TYPE_ANY:CL_TYPE_GRAPHICS::121",
+        ),
+        (
+            SigType::Icon,
+            b"Test.Icon:grp1:grp2:16:00112233445566778899aabbccddeeff:00112233445566778899aabbccddeeff:00112233445566778899aabbccddeeff",
+        ),
+    ];
+
+    #[test]
+    fn each_sig_type_converts() {
+        for &(sig_type, sample) in SAMPLES {
+            let (sig, _) = parse_from_cvd_typed(sig_type, &sample.into()).unwrap();
+            let matches = matches!(
+                (&sig, sig_type),
+                (AnySignature::Extended(_), SigType::Extended)
+                    | (AnySignature::Logical(_), SigType::Logical)
+                    | (AnySignature::FileHash(_), SigType::FileHash)
+                    | (AnySignature::PESectionHash(_), SigType::PESectionHash)
+                    | (
+                        AnySignature::ContainerMetadata(_),
+                        SigType::ContainerMetadata
+                    )
+                    | (AnySignature::Phishing(_), SigType::PhishingURL)
+                    | (AnySignature::FTMagic(_), SigType::FTMagic)
+                    | (AnySignature::Icon(_), SigType::Icon)
+            );
+            assert!(matches, "{sig_type:?} parsed to wrong variant: {sig:?}");
+            assert_eq!(sig.sig_type(), sig_type);
+        }
+    }
+
+    #[test]
+    fn trait_methods_forward() {
+        let (sig, sigmeta) = parse_from_cvd_typed(
+            SigType::Logical,
+            &b"TestSig;Engine:51-255,Target:0;(0&1);414141;424242"[..].into(),
+        )
+        .unwrap();
+        assert_eq!(sig.name(), "TestSig");
+        assert!(sig.validate(&sigmeta).is_ok());
+        assert_eq!(
+            sig.to_sigbytes().unwrap().to_string(),
+            "TestSig;Engine:51-255,Target:0;(0&1);414141;424242"
+        );
+        assert_eq!(sig.body_sigs().len(), 2);
+    }
+}
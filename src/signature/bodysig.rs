@@ -25,10 +25,92 @@ pub mod pattern_modifier;
 use crate::{
     feature::{EngineReq, Set},
     sigbytes::{AppendSigBytes, SigBytes},
+    signature::Complexity,
+    util::Range,
 };
+use altstr::AlternativeStrings;
 pub use char_class::CharacterClass;
+use enumflags2::BitFlag;
 pub use pattern::Pattern;
-pub use pattern_modifier::PatternModifier;
+pub use pattern_modifier::{BoundaryModifiers, PatternModifier};
+
+/// The minimum length (in bytes) of a fully-static run considered distinctive
+/// enough to report as a literal string
+const LITERAL_STR_MIN_LEN: usize = 4;
+
+/// The number of distinct byte values at or below which a body signature is
+/// considered low-diversity (see [`BodySig::count_unique_bytes`]) and worth a
+/// warning
+pub const LOW_BYTE_DIVERSITY_MAX: usize = 2;
+
+/// Byte sequences so common in scanned content that a signature beginning
+/// with one floods the prefilter: the MZ executable header, the ZIP local
+/// file header, and the OLE2 compound document header
+const UBIQUITOUS_PREFIXES: &[(&[u8], &str)] = &[
+    (&[0x4d, 0x5a, 0x90, 0x00], "4d5a9000"),
+    (&[0x50, 0x4b, 0x03, 0x04], "504b0304"),
+    (&[0xd0, 0xcf, 0x11, 0xe0], "d0cf11e0"),
+];
+
+/// Thresholds for [`BodySig::lint`]
+#[derive(Debug, Clone)]
+pub struct LintOptions {
+    /// The maximum permitted length (in bytes) of a leading or trailing run
+    /// of a single repeated byte value.  Longer runs (e.g., of `0x00` or
+    /// `0xff` padding) are reported as [`BodySigLint::RepeatedByteRun`].
+    pub max_repeated_run: usize,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            max_repeated_run: 4,
+        }
+    }
+}
+
+/// Which end of the body signature a lint refers to
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum LintPosition {
+    Leading,
+    Trailing,
+}
+
+impl std::fmt::Display for LintPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LintPosition::Leading => write!(f, "leading"),
+            LintPosition::Trailing => write!(f, "trailing"),
+        }
+    }
+}
+
+/// High-false-positive byte patterns detected by [`BodySig::lint`].  Unlike
+/// [`SigWarning`](crate::signature::SigWarning)s, these are reported only on
+/// demand, with thresholds configurable via [`LintOptions`].
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Hash)]
+pub enum BodySigLint {
+    #[error("{position} run of {count} repeated {byte:#04x} bytes is prone to false positives; anchor with an offset or add more distinctive bytes")]
+    RepeatedByteRun {
+        position: LintPosition,
+        byte: u8,
+        count: usize,
+    },
+
+    #[error("signature begins with ubiquitous prefix {prefix}; anchor with an offset or add more distinctive bytes")]
+    UbiquitousPrefix { prefix: &'static str },
+}
+
+/// The engine pattern matcher a body signature is routed to, as reported by
+/// [`BodySig::matcher_class`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatcherClass {
+    /// Boyer-Moore: a single static byte string with no wildcards or modifiers
+    BoyerMoore,
+    /// Aho-Corasick: everything else (wildcards, nyble wildcards, alternative
+    /// strings, byte ranges, anchored bytes, or pattern modifiers)
+    AhoCorasick,
+}
 
 /// Body signature.  This is an element of both Extended and Logical signatures,
 /// and contains byte match patterns.
@@ -40,6 +122,610 @@ pub struct BodySig {
     pub patterns: Vec<Pattern>,
 }
 
+/// A deterministic fingerprint over a body signature's canonical
+/// serialization, as computed by [`BodySig::fingerprint`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BodySigFingerprint([u8; 32]);
+
+impl BodySig {
+    /// A deterministic fingerprint of this body signature, suitable for
+    /// detecting body reuse across signatures.  The digest (SHA2-256) is
+    /// computed over the canonical serialization, so formatting differences
+    /// in the original input don't affect it, while any semantic change
+    /// does.
+    #[must_use]
+    pub fn fingerprint(&self) -> BodySigFingerprint {
+        let mut sb = SigBytes::new();
+        self.append_sigbytes(&mut sb)
+            .expect("serializing a parsed body signature cannot fail");
+        let mut buf = Vec::with_capacity(sb.len() + 1);
+        buf.push(crate::signature::FINGERPRINT_VERSION);
+        buf.extend_from_slice(sb.as_bytes());
+        BodySigFingerprint(openssl::sha::sha256(&buf))
+    }
+
+    /// Build a body signature matching the given ASCII text.  With
+    /// `case_insensitive`, each alphabetic byte is expanded into a fixed-width
+    /// alternative-string group covering both cases (e.g., `(46|66)` for `F`).
+    /// Note that within a logical signature, setting the `i` flag
+    /// ([`SubSigModifier`](crate::signature::logical_sig::subsig::SubSigModifier)'s
+    /// `case_insensitive` field) on the sub-signature achieves the same effect
+    /// without inflating the pattern.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`parse::BodySigParseError::NotAscii`](parse::BodySigParseError::NotAscii)
+    /// if the text contains non-ASCII characters, or a parse error if the
+    /// generated signature is not well-formed (e.g., the text is empty).
+    pub fn from_ascii_text(
+        text: &str,
+        case_insensitive: bool,
+    ) -> Result<Self, parse::BodySigParseError> {
+        use std::fmt::Write;
+
+        if !text.is_ascii() {
+            return Err(parse::BodySigParseError::NotAscii);
+        }
+        let mut hex = String::with_capacity(text.len() * 2);
+        for byte in text.bytes() {
+            if case_insensitive && byte.is_ascii_alphabetic() {
+                write!(
+                    hex,
+                    "({:02x}|{:02x})",
+                    byte.to_ascii_uppercase(),
+                    byte.to_ascii_lowercase()
+                )
+                .unwrap();
+            } else {
+                write!(hex, "{byte:02x}").unwrap();
+            }
+        }
+        hex.as_bytes().try_into()
+    }
+
+    /// Build a body signature matching the given bytes exactly
+    #[must_use]
+    pub fn from_bytes_literal(bytes: &[u8]) -> Self {
+        let patterns = if bytes.is_empty() {
+            vec![]
+        } else {
+            vec![Pattern::String(
+                bytes
+                    .iter()
+                    .map(|&b| pattern::MatchByte::Full(b))
+                    .collect::<Vec<_>>()
+                    .into(),
+                PatternModifier::empty(),
+            )]
+        };
+        Self { patterns }
+    }
+
+    /// The pattern at the given position, if present.  Unlike an iterator's
+    /// `nth()`, this indexes directly in O(1).
+    #[must_use]
+    pub fn pattern_at(&self, idx: usize) -> Option<&Pattern> {
+        self.patterns.get(idx)
+    }
+
+    /// The first pattern in this body signature, if any
+    #[must_use]
+    pub fn first_pattern(&self) -> Option<&Pattern> {
+        self.patterns.first()
+    }
+
+    /// The last pattern in this body signature, if any
+    #[must_use]
+    pub fn last_pattern(&self) -> Option<&Pattern> {
+        self.patterns.last()
+    }
+
+    /// Replace the pattern at `idx` with `new_pattern`, returning the
+    /// previous pattern.  The replacement is refused if `idx` is out of
+    /// bounds or if it would leave the body signature beginning or ending
+    /// with a wildcard-type pattern.
+    pub fn replace_pattern(
+        &mut self,
+        idx: usize,
+        new_pattern: Pattern,
+    ) -> Result<Pattern, parse::BodySigParseError> {
+        if idx >= self.patterns.len() {
+            return Err(parse::BodySigParseError::PatternIndexOutOfBounds {
+                idx,
+                len: self.patterns.len(),
+            });
+        }
+        if new_pattern.is_wildcard() {
+            if idx == 0 {
+                return Err(parse::BodySigParseError::LeadingWildcard {
+                    pattern: new_pattern,
+                });
+            }
+            if idx == self.patterns.len() - 1 {
+                return Err(parse::BodySigParseError::TrailingUnsizedPattern {
+                    pattern: new_pattern,
+                });
+            }
+        }
+        Ok(std::mem::replace(&mut self.patterns[idx], new_pattern))
+    }
+
+    /// Swap the patterns at `idx_a` and `idx_b`.  Refused if either index is
+    /// out of bounds or the swap would move a wildcard-type pattern to the
+    /// beginning or end of the signature.
+    pub fn swap_patterns(
+        &mut self,
+        idx_a: usize,
+        idx_b: usize,
+    ) -> Result<(), parse::BodySigParseError> {
+        let len = self.patterns.len();
+        for idx in [idx_a, idx_b] {
+            if idx >= len {
+                return Err(parse::BodySigParseError::PatternIndexOutOfBounds { idx, len });
+            }
+        }
+        for (to, from) in [(idx_a, idx_b), (idx_b, idx_a)] {
+            if self.patterns[from].is_wildcard() {
+                if to == 0 {
+                    return Err(parse::BodySigParseError::LeadingWildcard {
+                        pattern: self.patterns[from].clone(),
+                    });
+                }
+                if to == len - 1 {
+                    return Err(parse::BodySigParseError::TrailingUnsizedPattern {
+                        pattern: self.patterns[from].clone(),
+                    });
+                }
+            }
+        }
+        self.patterns.swap(idx_a, idx_b);
+        Ok(())
+    }
+
+    /// The longest contiguous run of fully-static bytes within any single
+    /// [`Pattern::String`], suitable for seeding an Aho-Corasick prefilter
+    /// that verifies the full pattern only on candidate matches.  Ties go to
+    /// the earliest run; returns `None` if no fully-literal bytes exist.
+    #[must_use]
+    pub fn best_scan_string(&self) -> Option<&[pattern::MatchByte]> {
+        let mut best: Option<&[pattern::MatchByte]> = None;
+        for pattern in &self.patterns {
+            if let Pattern::String(mbs, _) = pattern {
+                let mut runs = mbs.static_runs();
+                while let Some((offset, run)) = runs.next_run() {
+                    if best.is_none_or(|b| run.len() > b.len()) {
+                        best = Some(&mbs.bytes[offset..offset + run.len()]);
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// Return every fully-static byte run (at least 4 bytes long) contained in
+    /// this body signature, decoded to raw bytes.  Alternative-string branches
+    /// are each examined independently.
+    #[must_use]
+    pub fn literal_strings(&self) -> Vec<Vec<u8>> {
+        let mut literals = vec![];
+        for pattern in &self.patterns {
+            match pattern {
+                Pattern::String(mbs, _) => {
+                    literals.extend(pattern::static_runs(mbs, LITERAL_STR_MIN_LEN));
+                }
+                Pattern::AnchoredByte { string, .. } => {
+                    literals.extend(pattern::static_runs(string, LITERAL_STR_MIN_LEN));
+                }
+                Pattern::AlternativeStrings { astrs, .. } => {
+                    for branch in astrs.iter_alternatives() {
+                        literals.extend(pattern::static_runs(branch, LITERAL_STR_MIN_LEN));
+                    }
+                }
+                Pattern::ByteRange(_) | Pattern::Wildcard => (),
+            }
+        }
+        literals
+    }
+
+    /// Count the number of unbounded skips in this body signature: `*`
+    /// wildcards, plus open-ended (`{n-}`) byte ranges, which permit an
+    /// arbitrarily-long skip just as `*` does.
+    #[must_use]
+    pub fn wildcard_count(&self) -> usize {
+        self.patterns
+            .iter()
+            .filter(|p| matches!(p, Pattern::Wildcard | Pattern::ByteRange(Range::From(_))))
+            .count()
+    }
+
+    /// Whether this body signature contains two or more unbounded skips.  Each
+    /// one forces the matcher to restart its search, which can noticeably slow
+    /// scanning.
+    #[must_use]
+    pub fn contains_multiple_wildcards(&self) -> bool {
+        self.wildcard_count() >= 2
+    }
+
+    /// Whether this body signature is trivially matching: none of its
+    /// patterns constrain any byte values, so it would match (nearly) every
+    /// file scanned and should be rejected.  This covers bodies consisting
+    /// only of wildcards and byte ranges (which constrain length but not
+    /// content), match strings made up entirely of `??` wildcards, and
+    /// alternative-string groups that cover every possible byte value.
+    #[must_use]
+    pub fn is_trivially_matching(&self) -> bool {
+        fn all_wildcards(bytes: &[pattern::MatchByte]) -> bool {
+            bytes.iter().all(|mb| {
+                matches!(
+                    mb,
+                    pattern::MatchByte::Any | pattern::MatchByte::WildcardMany { .. }
+                )
+            })
+        }
+
+        self.patterns.iter().all(|p| match p {
+            Pattern::Wildcard | Pattern::ByteRange(_) => true,
+            Pattern::String(mbs, _) => all_wildcards(mbs),
+            Pattern::AnchoredByte { byte, string, .. } => {
+                all_wildcards(std::slice::from_ref(byte)) && all_wildcards(string)
+            }
+            // A negated group constrains its bytes to the complement set
+            Pattern::AlternativeStrings { negated: true, .. } => false,
+            Pattern::AlternativeStrings {
+                negated: false,
+                astrs,
+            } => match astrs {
+                AlternativeStrings::FixedWidth { width: 1, data } => {
+                    // A single-byte group is trivial if a branch is a
+                    // wildcard, or the branches cover every byte value
+                    data.iter()
+                        .any(|mb| all_wildcards(std::slice::from_ref(&mb)))
+                        || data
+                            .iter()
+                            .filter_map(|mb| match mb {
+                                pattern::MatchByte::Full(v) => Some(v),
+                                _ => None,
+                            })
+                            .collect::<std::collections::HashSet<u8>>()
+                            .len()
+                            == 256
+                }
+                _ => astrs.iter_alternatives().any(all_wildcards),
+            },
+        })
+    }
+
+    /// The distinct byte values matched exactly (as [`pattern::MatchByte::Full`]
+    /// elements) across all patterns in this body signature, including
+    /// anchored bytes and alternative-string branches.
+    #[must_use]
+    pub fn unique_bytes(&self) -> std::collections::HashSet<u8> {
+        fn collect(bytes: &[pattern::MatchByte], set: &mut std::collections::HashSet<u8>) {
+            set.extend(bytes.iter().filter_map(|mb| match mb {
+                pattern::MatchByte::Full(v) => Some(*v),
+                _ => None,
+            }));
+        }
+
+        let mut set = std::collections::HashSet::new();
+        for pattern in &self.patterns {
+            match pattern {
+                Pattern::String(mbs, _) => collect(mbs, &mut set),
+                Pattern::AnchoredByte { byte, string, .. } => {
+                    collect(std::slice::from_ref(byte), &mut set);
+                    collect(string, &mut set);
+                }
+                Pattern::AlternativeStrings {
+                    astrs:
+                        AlternativeStrings::FixedWidth { data, .. }
+                        | AlternativeStrings::Generic { data, .. },
+                    ..
+                } => collect(data, &mut set),
+                Pattern::ByteRange(_) | Pattern::Wildcard => (),
+            }
+        }
+        set
+    }
+
+    /// The number of distinct byte values matched exactly within this body
+    /// signature (see [`BodySig::unique_bytes`]).  A signature drawing on very
+    /// few distinct values (e.g., a run of `00` padding) is likely to match
+    /// large amounts of unrelated content.
+    #[must_use]
+    pub fn count_unique_bytes(&self) -> usize {
+        self.unique_bytes().len()
+    }
+
+    /// Whether any anchored-byte pattern in this body signature places its
+    /// single byte on the right side without a match string remaining on the
+    /// left.  The parser enforces a minimum match string size when building
+    /// [`Pattern::AnchoredByte`], so this should never return `true` for a
+    /// parsed signature; it exists as a defensive assertion for signatures
+    /// assembled programmatically.
+    #[must_use]
+    pub fn has_anchored_byte_on_both_sides(&self) -> bool {
+        self.patterns.iter().any(|p| {
+            matches!(
+                p,
+                Pattern::AnchoredByte {
+                    anchor_side: pattern::ByteAnchorSide::Right,
+                    string,
+                    ..
+                } if string.is_empty()
+            )
+        })
+    }
+
+    /// Render this body signature in decoded form, in the style of
+    /// `sigtool --decode-sigs`: full bytes in the printable ASCII range
+    /// appear as characters (with `\\` kept escaped as `\x5c` to stay
+    /// unambiguous), other bytes as `\xNN` escapes, and nyble wildcards keep
+    /// their `?` placeholders.  Pattern-level wildcards keep their `{n-m}`
+    /// and `*` syntax, and alternative-string groups their `(a|b)` grouping
+    /// (prefixed with `!` when negated).
+    #[must_use]
+    pub fn decode_ascii(&self) -> SigBytes {
+        use std::fmt::Write;
+
+        fn decode_bytes(bytes: &[pattern::MatchByte], out: &mut SigBytes) {
+            for mb in bytes {
+                match mb {
+                    pattern::MatchByte::Full(b)
+                        if (b.is_ascii_graphic() || *b == b' ') && *b != b'\\' =>
+                    {
+                        let _ = out.write_char(char::from(*b));
+                    }
+                    pattern::MatchByte::Full(b) => {
+                        let _ = write!(out, r"\x{b:02x}");
+                    }
+                    mb => {
+                        let _ = write!(out, "{mb:?}");
+                    }
+                }
+            }
+        }
+
+        let mut out = SigBytes::new();
+        for pattern in &self.patterns {
+            match pattern {
+                Pattern::String(mbs, _) => decode_bytes(mbs, &mut out),
+                Pattern::AnchoredByte {
+                    anchor_side,
+                    byte,
+                    range,
+                    string,
+                } => match anchor_side {
+                    pattern::ByteAnchorSide::Left => {
+                        decode_bytes(std::slice::from_ref(byte), &mut out);
+                        let _ = write!(out, "[{}-{}]", range.start(), range.end());
+                        decode_bytes(string, &mut out);
+                    }
+                    pattern::ByteAnchorSide::Right => {
+                        decode_bytes(string, &mut out);
+                        let _ = write!(out, "[{}-{}]", range.start(), range.end());
+                        decode_bytes(std::slice::from_ref(byte), &mut out);
+                    }
+                },
+                Pattern::AlternativeStrings { negated, astrs } => {
+                    if *negated {
+                        let _ = out.write_char('!');
+                    }
+                    let _ = out.write_char('(');
+                    for (pos, branch) in astrs.iter_alternatives().enumerate() {
+                        if pos > 0 {
+                            let _ = out.write_char('|');
+                        }
+                        decode_bytes(branch, &mut out);
+                    }
+                    let _ = out.write_char(')');
+                }
+                Pattern::ByteRange(range) => {
+                    let _ = out.write_char('{');
+                    let _ = range.append_sigbytes(&mut out);
+                    let _ = out.write_char('}');
+                }
+                Pattern::Wildcard => {
+                    let _ = out.write_char('*');
+                }
+            }
+        }
+        out
+    }
+
+    /// Check this body signature for leading or trailing byte patterns so
+    /// common in scanned content that they're likely to flood the prefilter:
+    /// long runs of a single repeated byte value (e.g., `0x00` or `0xff`
+    /// padding), or one of a small built-in list of ubiquitous file-format
+    /// prefixes.  Each finding points at the offending end of the signature;
+    /// thresholds are configurable via [`LintOptions`].
+    #[must_use]
+    pub fn lint(&self, options: &LintOptions) -> Vec<BodySigLint> {
+        fn repeated_run(bytes: &[u8]) -> Option<(u8, usize)> {
+            let first = *bytes.first()?;
+            Some((first, bytes.iter().take_while(|&&b| b == first).count()))
+        }
+
+        let mut lints = vec![];
+
+        let leading = self.leading_static_bytes();
+        if let Some((byte, count)) = repeated_run(&leading) {
+            if count > options.max_repeated_run {
+                lints.push(BodySigLint::RepeatedByteRun {
+                    position: LintPosition::Leading,
+                    byte,
+                    count,
+                });
+            }
+        }
+        for &(bytes, prefix) in UBIQUITOUS_PREFIXES {
+            if leading.starts_with(bytes) {
+                lints.push(BodySigLint::UbiquitousPrefix { prefix });
+            }
+        }
+
+        // The trailing bytes are reported in reverse order, so the repeated
+        // run is counted from the very end of the signature
+        let trailing = self.trailing_static_bytes();
+        if let Some((byte, count)) = repeated_run(&trailing) {
+            if count > options.max_repeated_run {
+                lints.push(BodySigLint::RepeatedByteRun {
+                    position: LintPosition::Trailing,
+                    byte,
+                    count,
+                });
+            }
+        }
+
+        lints
+    }
+
+    /// The fully-static bytes at the very start of this body signature
+    fn leading_static_bytes(&self) -> Vec<u8> {
+        match self.patterns.first() {
+            Some(Pattern::String(mbs, _)) => match mbs.static_runs().next_run() {
+                Some((0, run)) => run.to_vec(),
+                _ => vec![],
+            },
+            _ => vec![],
+        }
+    }
+
+    /// The fully-static bytes at the very end of this body signature, in
+    /// reverse order
+    fn trailing_static_bytes(&self) -> Vec<u8> {
+        match self.patterns.last() {
+            Some(Pattern::String(mbs, _)) => mbs
+                .iter()
+                .rev()
+                .map_while(|b| match b {
+                    pattern::MatchByte::Full(v) => Some(v),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// The engine pattern matcher this body signature is routed to.  Only a
+    /// single fully-static byte string (every byte an exact match, with no
+    /// pattern modifiers) qualifies for the cheaper Boyer-Moore matcher;
+    /// anything else requires Aho-Corasick.
+    #[must_use]
+    pub fn matcher_class(&self) -> MatcherClass {
+        match self.patterns.as_slice() {
+            [Pattern::String(mbs, modifiers)]
+                if modifiers.is_empty()
+                    && mbs
+                        .iter()
+                        .all(|mb| matches!(mb, pattern::MatchByte::Full(_))) =>
+            {
+                MatcherClass::BoyerMoore
+            }
+            _ => MatcherClass::AhoCorasick,
+        }
+    }
+
+    /// An abbreviated rendering for diagnostic output and audit logs, where
+    /// full hex output is too verbose.  String patterns are truncated to
+    /// their first eight bytes (with `...` marking elision), and alternative
+    /// string groups are summarized by their branch count.
+    #[must_use]
+    pub fn to_compact_string(&self) -> String {
+        use std::fmt::Write;
+
+        const STRING_PREVIEW_BYTES: usize = 8;
+
+        let mut out = String::new();
+        for pattern in &self.patterns {
+            match pattern {
+                Pattern::String(mbs, _) => {
+                    for mb in mbs.iter().take(STRING_PREVIEW_BYTES) {
+                        write!(out, "{mb:?}").unwrap();
+                    }
+                    if mbs.len() > STRING_PREVIEW_BYTES {
+                        out.push_str("...");
+                    }
+                }
+                Pattern::AnchoredByte {
+                    anchor_side,
+                    byte,
+                    range,
+                    ..
+                } => match anchor_side {
+                    pattern::ByteAnchorSide::Left => {
+                        write!(out, "{byte:?}[{}-{}]...", range.start(), range.end()).unwrap();
+                    }
+                    pattern::ByteAnchorSide::Right => {
+                        write!(out, "...[{}-{}]{byte:?}", range.start(), range.end()).unwrap();
+                    }
+                },
+                Pattern::AlternativeStrings { negated, astrs } => {
+                    let count = match astrs {
+                        AlternativeStrings::FixedWidth { width, data } => data.len() / width,
+                        AlternativeStrings::Generic { ranges, .. } => ranges.len(),
+                    };
+                    if *negated {
+                        out.push('!');
+                    }
+                    write!(out, "({count} alternatives)").unwrap();
+                }
+                Pattern::ByteRange(range) => match range {
+                    Range::Exact(n) => write!(out, "{{{n}}}").unwrap(),
+                    Range::ToInclusive(r) => write!(out, "{{-{}}}", r.end).unwrap(),
+                    Range::From(r) => write!(out, "{{{}-}}", r.start).unwrap(),
+                    Range::Inclusive(r) => {
+                        write!(out, "{{{}-{}}}", r.start(), r.end()).unwrap();
+                    }
+                },
+                Pattern::Wildcard => out.push('*'),
+            }
+        }
+        out
+    }
+
+    /// A breakdown of this body signature's matching-cost drivers
+    #[must_use]
+    pub fn complexity(&self) -> Complexity {
+        fn static_byte_count(bytes: &[pattern::MatchByte]) -> usize {
+            bytes
+                .iter()
+                .filter(|b| matches!(b, pattern::MatchByte::Full(_)))
+                .count()
+        }
+
+        let mut cx = Complexity {
+            pattern_count: self.patterns.len(),
+            ..Complexity::default()
+        };
+        for pattern in &self.patterns {
+            match pattern {
+                Pattern::String(mbs, _) => cx.total_static_bytes += static_byte_count(mbs),
+                Pattern::AnchoredByte { byte, string, .. } => {
+                    cx.total_static_bytes +=
+                        static_byte_count(string) + static_byte_count(&[*byte]);
+                }
+                Pattern::AlternativeStrings { astrs, .. } => match astrs {
+                    AlternativeStrings::FixedWidth { width, data } => {
+                        cx.alternative_branch_count += data.len() / width;
+                        cx.total_static_bytes += static_byte_count(data);
+                    }
+                    AlternativeStrings::Generic { ranges, data } => {
+                        cx.alternative_branch_count += ranges.len();
+                        cx.total_static_bytes += static_byte_count(data);
+                    }
+                },
+                Pattern::Wildcard => cx.wildcard_count += 1,
+                Pattern::ByteRange(range) => {
+                    if matches!(range, crate::util::Range::From(_)) {
+                        cx.unbounded_range_count += 1;
+                    }
+                }
+            }
+        }
+        cx
+    }
+}
+
 impl AppendSigBytes for BodySig {
     fn append_sigbytes(&self, sb: &mut SigBytes) -> Result<(), crate::signature::ToSigBytesError> {
         for pattern in &self.patterns {
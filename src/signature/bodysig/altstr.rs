@@ -16,12 +16,11 @@
  *  MA 02110-1301, USA.
  */
 
-use super::pattern::MatchBytes;
+use super::pattern::{MatchByte, MatchBytes};
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum AlternativeStrings {
     FixedWidth {
-        negated: bool,
         width: usize,
         data: MatchBytes,
     },
@@ -30,3 +29,62 @@ pub enum AlternativeStrings {
         data: MatchBytes,
     },
 }
+
+impl AlternativeStrings {
+    /// Whether any alternative is unconstrained, and would hence match any
+    /// input at its position: a zero-length alternative in a `Generic` set, or
+    /// a `FixedWidth` alternative containing only ignored bytes.  Such
+    /// alternatives are valid, but are usually unintentional, and effectively
+    /// turn the group into a wildcard.
+    #[must_use]
+    pub fn contains_any(&self) -> bool {
+        self.iter_alternatives()
+            .any(|branch| branch.iter().all(|b| matches!(b, MatchByte::Any)))
+    }
+
+    /// Iterate over each alternative as a slice of the stored match bytes,
+    /// without copying: fixed-width groups yield consecutive `width`-sized
+    /// chunks, generic groups the stored sub-ranges of the data
+    #[must_use]
+    pub fn iter_alternatives(&self) -> Alternatives<'_> {
+        Alternatives(match self {
+            AlternativeStrings::FixedWidth { width, data } => {
+                AlternativesInner::FixedWidth(data.chunks(*width))
+            }
+            AlternativeStrings::Generic { ranges, data } => AlternativesInner::Generic {
+                ranges: ranges.iter(),
+                data,
+            },
+        })
+    }
+}
+
+/// Iterator over the alternatives of an [`AlternativeStrings`] group, per
+/// [`AlternativeStrings::iter_alternatives`]
+pub struct Alternatives<'a>(AlternativesInner<'a>);
+
+enum AlternativesInner<'a> {
+    FixedWidth(std::slice::Chunks<'a, MatchByte>),
+    Generic {
+        ranges: std::slice::Iter<'a, std::ops::Range<usize>>,
+        data: &'a [MatchByte],
+    },
+}
+
+impl<'a> Iterator for Alternatives<'a> {
+    type Item = &'a [MatchByte];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            AlternativesInner::FixedWidth(chunks) => chunks.next(),
+            AlternativesInner::Generic { ranges, data } => loop {
+                // Ranges are validated at parse time, but don't panic on a
+                // hand-assembled group with a stray range
+                let range = ranges.next()?;
+                if let Some(branch) = data.get(range.clone()) {
+                    return Some(branch);
+                }
+            },
+        }
+    }
+}
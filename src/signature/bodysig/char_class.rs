@@ -27,7 +27,7 @@ use thiserror::Error;
 /// Body signature "character classes".  These are markers that can appear to
 /// the left or right of a hex signature, and further constrain the match.  They
 /// may be negated when considered as part of a PatternModifier.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum CharacterClass {
     /// Word Boundary (B).  Matches any non-word character
     WordBoundary,
@@ -37,7 +37,7 @@ pub enum CharacterClass {
     NonAlphaChar,
 }
 
-#[derive(Debug, PartialEq, Error)]
+#[derive(Debug, PartialEq, Eq, Hash, Error)]
 pub enum CharacterClassParseError {
     #[error("{byte} not a known character class")]
     Unknown { byte: SigChar },
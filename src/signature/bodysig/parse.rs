@@ -18,7 +18,9 @@
 
 #[cfg(test)]
 mod tests;
+mod tokenize;
 
+use self::tokenize::{Token, Tokenizer};
 use super::{BodySig, CharacterClass};
 use crate::{
     sigbytes::SigChar,
@@ -56,20 +58,39 @@ const PAREN_RIGHT: u8 = b')';
 const PIPE: u8 = b'|';
 const QUESTION_MARK: u8 = b'?';
 
-#[derive(Debug, Error, PartialEq)]
+/// Caps on the bounds of curly-brace wildcard ranges.  The engine stores
+/// these distances in smaller integer types than the `usize` this crate
+/// parses into; bounds beyond what the engine can represent are rejected at
+/// parse time rather than misbehaving at match time.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BraceLimits {
+    /// Maximum value permitted for either bound of a `{n}`/`{n-m}` range
+    pub max_bound: usize,
+}
+
+impl Default for BraceLimits {
+    fn default() -> Self {
+        Self {
+            // The engine's matchers hold these distances in 16-bit fields
+            max_bound: u16::MAX as usize,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
 pub enum BodySigParseError {
     /// The anchored-byte expression at the end of a pattern was incomplete
-    #[error("expecting single byte {pos} after anchored-byte expression starting {start_pos}")]
+    #[error("expecting single byte {pos} after anchored-byte expression {start_pos}")]
     AnchoredByteExpectingSingleByte { start_pos: Position, pos: Position },
 
     /// The lower range bound for the wildcard portion of an anchored-byte match
     /// exceeds the maximum
-    #[error("invalid/missing lower bound {found} for anchored-byte wildcard range opened {bracket_pos} (must be <={ANCHORED_BYTE_RANGE_MAX})")]
+    #[error("invalid/missing lower bound {found} for anchored-byte wildcard range {bracket_pos} (must be <={ANCHORED_BYTE_RANGE_MAX})")]
     AnchoredByteInvalidLowerBound { bracket_pos: Position, found: usize },
 
     /// The upper range bound for the wildcard portion of an anchored-byte match
     /// exceeds the maximum
-    #[error("invalid/missing upper bound {found} for anchored-byte wildcard range opened {bracket_pos} (must be <={ANCHORED_BYTE_RANGE_MAX} and greater than lower bound {lower})")]
+    #[error("invalid/missing upper bound {found} for anchored-byte wildcard range {bracket_pos} (must be <={ANCHORED_BYTE_RANGE_MAX} and greater than lower bound {lower})")]
     AnchoredByteInvalidUpperBound {
         bracket_pos: Position,
         found: usize,
@@ -83,23 +104,23 @@ pub enum BodySigParseError {
 
     /// An anchored-byte match must have a single byte on one side of the
     /// wildcard range
-    #[error("missing single byte on one side of anchored-byte expression starting {start_pos}")]
+    #[error("missing single byte on one side of anchored-byte expression {start_pos}")]
     AnchoredByteMissingSingleByte { start_pos: Position },
 
     /// An anchored-byte match must include a string of minimum size
-    #[error("match string for anchored-byte expression starting {start_pos} too small (min {ANCHORED_BYTE_MATCH_STRING_MIN_BYTES} bytes)")]
+    #[error("match string for anchored-byte expression {start_pos} too small (min {ANCHORED_BYTE_MATCH_STRING_MIN_BYTES} bytes)")]
     AnchoredByteStringTooSmall { start_pos: Position },
 
     // A square bracket opened at the specified position was not closed
-    #[error("bracket opened {start_pos} not closed")]
+    #[error("bracket expression {start_pos} not closed")]
     BracketNotClosed { start_pos: Position },
 
     /// Anchored-byte bracket expressions must contain both bounds
-    #[error("bracket range opened {start_pos} missing lower bound")]
+    #[error("bracket range {start_pos} missing lower bound")]
     BracketRangeMissingLowerBound { start_pos: Position },
 
     /// Anchored-byte bracket expressions must contain both bounds
-    #[error("bracket range opened {start_pos} missing bound(s)")]
+    #[error("bracket range {start_pos} missing bound(s)")]
     BracketRangeEmpty { start_pos: Position },
 
     /// Anchored-byte bracket expressions may contain only decimal bounds and an
@@ -111,14 +132,36 @@ pub enum BodySigParseError {
     #[error("expected closing parenthesis for character class {pos}, found {found}")]
     CharClassExpectCloseParen { pos: Position, found: SigChar },
 
+    /// Only one character class may attach to each side of a string
+    #[error("character class opened {pos} duplicates one already attached to the same side of the string")]
+    CharClassDuplicatedSide { pos: Position },
+
     /// Character classes must modify a neighboring string
     #[error("no string adjacent to character class/pattern modifier ending {pos}")]
     CharClassNothingAdjacent { pos: Position },
 
+    /// A `(W)` character class constrains the word characters adjacent to a
+    /// string, and is meaningless on a string that can't contain any
+    #[error("(W) character class attached to string starting {start_pos} which contains no word characters")]
+    CharClassWordMarkerNonWord { start_pos: Position },
+
     /// A character class is missing its closing parenthesis
     #[error("character class opened {start_pos} not closed")]
     CharClassUnterminated { start_pos: Position },
 
+    /// A brace-range bound exceeds the maximum the engine can represent
+    #[error("brace range bound {bound} {start_pos} exceeds maximum of {limit}")]
+    BraceBoundTooLarge {
+        start_pos: Position,
+        bound: usize,
+        limit: usize,
+    },
+
+    /// A `{0}` (or `{0-0}`) brace expression matches zero bytes exactly, and
+    /// so constrains nothing
+    #[error("brace expression opened {start_pos} specifies a zero-length range")]
+    BraceRangeZero { start_pos: Position },
+
     /// A curly brace opened at the specified position was not closed
     #[error("curly brace opened {start_pos} not closed")]
     CurlyBraceNotClosed { start_pos: Position },
@@ -177,6 +220,11 @@ pub enum BodySigParseError {
     #[error("no bounds specified in brace expression opened {start_pos}")]
     NoBraceBounds { start_pos: Position },
 
+    /// Text provided to [`BodySig::from_ascii_text`](super::BodySig::from_ascii_text)
+    /// contained a non-ASCII character
+    #[error("input text is not ASCII")]
+    NotAscii,
+
     /// Wildcard range bounds must be in order, with the lower bound on the left
     #[error("range {start_pos} has inverted bounds ({end} < {start})")]
     RangeBoundsInverted {
@@ -185,6 +233,10 @@ pub enum BodySigParseError {
         end: usize,
     },
 
+    /// A mutation referenced a pattern index that doesn't exist
+    #[error("pattern index {idx} out of bounds ({len} patterns)")]
+    PatternIndexOutOfBounds { idx: usize, len: usize },
+
     /// The pattern ended with an unsized element (a wildcard or fixed byte range
     /// exceeding 128 bytes)
     #[error("may not end with a wildcard-type pattern (found {pattern:?})")]
@@ -212,24 +264,12 @@ enum State {
     HighNyble,
     // Expecting low hex-encoded nyble of a byte
     LowNyble,
-    // At start of a {curly-brace} expression
-    CurlyBraceLower,
-    // Expecting the upper value of a curly-brace range
-    CurlyBraceUpper,
-
-    // Anchored-byte range parts
-    BracketLower,
-    BracketUpper,
-
-    // In expression following `!`
-    Negate,
-
     // Obviously in a character class
     CharacterClass,
 }
 
 /// Various contexts for error reporting
-#[derive(Debug, PartialEq, Display)]
+#[derive(Debug, PartialEq, Eq, Hash, Display)]
 pub enum Context {
     #[strum(serialize = "curly-brace range")]
     CurlyBraceRange,
@@ -246,11 +286,6 @@ struct ParseContext {
     // Nyble mask for current hex-encoded byte
     mask: MatchMask,
 
-    // Value accumulator for decimal range bounds
-    dec_value: Option<usize>,
-    // Current range being parsed
-    cur_range: Option<Range<usize>>,
-
     // The current set of patterns
     patterns: Vec<Pattern>,
 
@@ -273,12 +308,13 @@ struct ParseContext {
     // Whether negation is in effect (applies to generic alternative strings and match boundaries)
     negated: bool,
 
+    // Whether a `!` has been seen that will apply to the next parenthetical
+    // expression (the tokenizer guarantees one immediately follows)
+    negate_pending: bool,
+
     // Location of the most-recent left bracket
     left_bracket_pos: usize,
 
-    // Location of the most-recent left curly brace
-    left_brace_pos: usize,
-
     // Location of the most-recent left parenthesis
     left_paren_pos: usize,
 }
@@ -293,6 +329,21 @@ impl ParseContext {
             pa.flushed = true;
         }
         if !self.match_bytes.is_empty() {
+            // A non-negated `(W)` class is only meaningful on strings that
+            // can contain word characters; reject it when every byte in the
+            // string is fully-specified and a non-word character.  The
+            // negated form (`!(W)`) remains useful for constraining the
+            // neighborhood of binary strings and is left alone.
+            if self.pattern_modifier.intersects(
+                PatternModifier::word_marker_flags() & !PatternModifier::negative_flags(),
+            ) && self.match_bytes.iter().all(|mb| match mb {
+                MatchByte::Full(b) => !(b.is_ascii_alphanumeric() || *b == b'_'),
+                _ => false,
+            }) {
+                return Err(BodySigParseError::CharClassWordMarkerNonWord {
+                    start_pos: self.match_bytes_start.into(),
+                });
+            }
             self.push_pattern(Pattern::String(
                 MatchBytes {
                     bytes: self.match_bytes.to_vec(),
@@ -314,52 +365,41 @@ impl ParseContext {
         }
     }
 
-    fn handle_anchored_byte_range(&mut self, pos: usize) -> Result<State, BodySigParseError> {
-        if let Some(Range::From(std::ops::RangeFrom { start })) = self.cur_range.take() {
-            let end = self.dec_value.take().unwrap_or(start);
-
-            if !(1..=ANCHORED_BYTE_RANGE_MAX).contains(&end) || end < start {
-                return Err(BodySigParseError::AnchoredByteInvalidUpperBound {
-                    bracket_pos: self.left_bracket_pos.into(),
-                    found: end,
-                    lower: start,
+    // Handle a bracketed wildcard range (already scanned and validated by the
+    // tokenizer), ending at `pos`
+    fn handle_anchored_byte_range(
+        &mut self,
+        range: RangeInclusive<u8>,
+        pos: usize,
+    ) -> Result<(), BodySigParseError> {
+        // Now, determine if the current match_bytes contains one element
+        // If it does, move it into this bracket-match structure as the anchor byte. The next series of bytes will
+        // If it contains more than one
+        match self.match_bytes.len() {
+            0 => return Err(BodySigParseError::AnchoredByteNoLeftBytes { pos: pos.into() }),
+            1 => {
+                // This is the anchor byte
+                self.pending_anchored_byte = Some(PendingAnchoredByte::HaveByte {
+                    span: Position::span(self.left_bracket_pos - 2, pos),
+                    byte: self.match_bytes.pop().unwrap(),
+                    range,
                 });
             }
-            // These are validated above, but don't leave this to chance.
-            let range = u8::try_from(start).unwrap()..=u8::try_from(end).unwrap();
-            // Now, determine if the current match_bytes contains one element
-            // If it does, move it into this bracket-match structure as the anchor byte. The next series of bytes will
-            // If it contains more than one
-            match self.match_bytes.len() {
-                0 => return Err(BodySigParseError::AnchoredByteNoLeftBytes { pos: pos.into() }),
-                1 => {
-                    // This is the anchor byte
-                    self.pending_anchored_byte = Some(PendingAnchoredByte::HaveByte {
-                        start_pos: self.left_bracket_pos - 2,
-                        byte: self.match_bytes.pop().unwrap(),
-                        range,
-                    });
-                }
-                len => {
-                    self.pending_anchored_byte = Some(PendingAnchoredByte::HaveString {
-                        start_pos: self.left_bracket_pos - len * 2,
-                        string: self.match_bytes.to_vec().into(),
-                        range,
-                    });
-                    self.match_bytes.clear();
-                }
+            len => {
+                self.pending_anchored_byte = Some(PendingAnchoredByte::HaveString {
+                    span: Position::span(self.left_bracket_pos - len * 2, pos),
+                    string: self.match_bytes.to_vec().into(),
+                    range,
+                });
+                self.match_bytes.clear();
             }
-            Ok(State::HighNyble)
-        } else {
-            Err(BodySigParseError::BracketRangeEmpty {
-                start_pos: self.left_bracket_pos.into(),
-            })
         }
+        Ok(())
     }
 
     // Handle the closure of a character class
     #[inline]
-    fn handle_cc_close(&mut self) -> State {
+    fn handle_cc_close(&mut self) -> Result<State, BodySigParseError> {
         let pa = self.paren_cxt.take().unwrap();
         if let Some(character_class) = &pa.character_class {
             // If this was the 'B' character class, the partial
@@ -368,32 +408,36 @@ impl ParseContext {
 
             // Assign this character class and the current negation to the correct side.
             // The assumption is left if match_bytes is empty.
-            self.pattern_modifier |=
-                character_class.pattern_modifier(self.match_bytes.is_empty(), self.negated);
+            let is_left_side = self.match_bytes.is_empty();
+            let side_flags = if is_left_side {
+                PatternModifier::left_flags()
+            } else {
+                PatternModifier::right_flags()
+            };
+            // Only one class may attach to each side of a string
+            if self.pattern_modifier.intersects(side_flags) {
+                return Err(BodySigParseError::CharClassDuplicatedSide {
+                    pos: pa.start_pos.into(),
+                });
+            }
+            self.pattern_modifier |= character_class.pattern_modifier(is_left_side, self.negated);
             self.negated = false;
         }
-        State::HighNyble
+        Ok(State::HighNyble)
     }
 
-    // This function is called whenever the state is about to transition
-    // from the default state due to finding a non-hex or non-nyble-wildcard
-    // ('?') character
+    // This function is called whenever a token other than a hex nyble or
+    // nyble wildcard is found in the default state (or with `None` at end of
+    // input, to resolve any pending anchored byte)
     #[allow(clippy::too_many_lines)]
-    fn handle_non_matchbyte(
-        &mut self,
-        pos_and_byte: Option<(usize, u8)>,
-    ) -> Result<State, BodySigParseError> {
+    fn handle_non_matchbyte(&mut self, token: Option<Token>) -> Result<State, BodySigParseError> {
         // Check to see if we were handling the other side of an anchored byte first
         if let Some(pending_anchored_byte) = self.pending_anchored_byte.take() {
             match pending_anchored_byte {
-                PendingAnchoredByte::HaveByte {
-                    start_pos,
-                    byte,
-                    range,
-                } => {
+                PendingAnchoredByte::HaveByte { span, byte, range } => {
                     if self.match_bytes.len() < ANCHORED_BYTE_MATCH_STRING_MIN_BYTES {
                         return Err(BodySigParseError::AnchoredByteStringTooSmall {
-                            start_pos: start_pos.into(),
+                            start_pos: span,
                         });
                     }
                     self.push_pattern(Pattern::AnchoredByte {
@@ -408,14 +452,14 @@ impl ParseContext {
                     self.match_bytes.clear();
                 }
                 PendingAnchoredByte::HaveString {
-                    start_pos,
+                    span,
                     string,
                     range,
                 } => {
                     if let Some(&byte) = self.match_bytes.first() {
                         if self.match_bytes.len() > 1 {
                             return Err(BodySigParseError::AnchoredByteMissingSingleByte {
-                                start_pos: start_pos.into(),
+                                start_pos: span,
                             });
                         }
                         self.push_pattern(Pattern::AnchoredByte {
@@ -429,8 +473,8 @@ impl ParseContext {
                         .unwrap();
                     } else {
                         return Err(BodySigParseError::AnchoredByteExpectingSingleByte {
-                            start_pos: (self.left_bracket_pos - string.len() * 2).into(),
-                            pos: pos_and_byte.map(|(pos, _)| pos).into(),
+                            start_pos: span,
+                            pos: token.as_ref().map(Token::pos).into(),
                         });
                     }
                 }
@@ -438,27 +482,48 @@ impl ParseContext {
             self.match_bytes.clear();
         }
 
-        if let Some((pos, byte)) = pos_and_byte {
-            // Any other character changes state
-            match byte {
-                ASTERISK => {
+        if let Some(token) = token {
+            // Any other token is handled here
+            match token {
+                Token::Star { .. } => {
                     // TODO: return error if wildcard begins signature
                     self.flush_match_bytes()?;
                     self.push_pattern(Pattern::Wildcard)?;
                     Ok(State::HighNyble)
                 }
-                CURLY_LEFT => {
-                    self.left_brace_pos = pos;
-                    self.dec_value = None;
-                    Ok(State::CurlyBraceLower)
+                Token::BraceRange { start_pos, range } => {
+                    match range {
+                        Range::Exact(size) if size <= 128 => self.push_matchbyte(
+                            MatchByte::WildcardMany {
+                                size: (size).try_into().unwrap(),
+                            },
+                            start_pos,
+                        ),
+                        range => {
+                            self.flush_match_bytes()?;
+                            self.push_pattern(Pattern::ByteRange(range))?;
+                        }
+                    }
+                    Ok(State::HighNyble)
                 }
-                BRACKET_LEFT => {
-                    self.left_bracket_pos = pos;
-                    self.dec_value = None;
-                    Ok(State::BracketLower)
+                Token::BracketRange {
+                    bracket_pos,
+                    close_pos,
+                    range,
+                } => {
+                    self.left_bracket_pos = bracket_pos;
+                    self.handle_anchored_byte_range(range, close_pos)?;
+                    Ok(State::HighNyble)
                 }
-                PAREN_LEFT => {
-                    self.flush_match_bytes()?;
+                Token::ParenOpen { pos } => {
+                    if std::mem::take(&mut self.negate_pending) {
+                        // Note that the preceding match bytes are *not*
+                        // flushed here; a negated character class may attach
+                        // to the right side of the string preceding it
+                        self.negated = true;
+                    } else {
+                        self.flush_match_bytes()?;
+                    }
                     self.left_paren_pos = pos;
                     self.paren_cxt = Some(ParentheticalContext {
                         start_pos: pos,
@@ -466,8 +531,11 @@ impl ParseContext {
                     });
                     Ok(State::HighNyble)
                 }
-                BANG => Ok(State::Negate),
-                PIPE => {
+                Token::Bang { .. } => {
+                    self.negate_pending = true;
+                    Ok(State::HighNyble)
+                }
+                Token::Pipe { pos } => {
                     if let Some(pa) = &mut self.paren_cxt {
                         pa.push_alternative_string(&mut self.match_bytes, false)?;
                         Ok(State::HighNyble)
@@ -475,28 +543,29 @@ impl ParseContext {
                         Err(BodySigParseError::UnexpectedPipeChar { pos: pos.into() })
                     }
                 }
-                PAREN_RIGHT => {
+                Token::ParenClose { pos } => {
                     if let Some(pa) = &mut self.paren_cxt.take() {
                         pa.push_alternative_string(&mut self.match_bytes, true)?;
                         let first_range = pa.ranges.first().unwrap();
                         if pa.is_generic {
-                            self.push_pattern(Pattern::AlternativeStrings(
-                                AlternativeStrings::Generic {
+                            self.push_pattern(Pattern::AlternativeStrings {
+                                negated: self.negated,
+                                astrs: AlternativeStrings::Generic {
                                     data: pa.astr_data.clone().into(),
                                     ranges: pa.ranges.clone(),
                                 },
-                            ))?;
+                            })?;
                         } else {
                             // + 1 here to account for the fact that
                             // inclusive ranges reference the upper *index*
                             let width = first_range.end;
-                            self.push_pattern(Pattern::AlternativeStrings(
-                                AlternativeStrings::FixedWidth {
-                                    negated: self.negated,
+                            self.push_pattern(Pattern::AlternativeStrings {
+                                negated: self.negated,
+                                astrs: AlternativeStrings::FixedWidth {
                                     width,
                                     data: pa.astr_data.clone().into(),
                                 },
-                            ))
+                            })
                             // There are no failures currently possible here, so
                             // `.unwrap()` to make code coverage happy.
                             .unwrap();
@@ -507,11 +576,15 @@ impl ParseContext {
                         Err(BodySigParseError::UnmatchedClosingParen { pos: pos.into() })
                     }
                 }
-                other => Err(BodySigParseError::UnexpectedChar {
+                Token::Unknown { pos, byte } => Err(BodySigParseError::UnexpectedChar {
                     context: Context::Pattern,
                     pos: pos.into(),
-                    found: other.into(),
+                    found: byte.into(),
                 }),
+                // Match-byte tokens are handled by the caller
+                Token::HexNyble { .. } | Token::NybleWildcard { .. } | Token::ClassChar { .. } => {
+                    unreachable!()
+                }
             }
         } else {
             Ok(State::HighNyble)
@@ -560,12 +633,12 @@ impl ParseContext {
             }
             // No additional error checking required for AnchoredByte
             Pattern::AnchoredByte { .. } => (),
-            Pattern::AlternativeStrings(altstr) => {
-                match altstr {
+            Pattern::AlternativeStrings { negated, astrs } => {
+                match astrs {
                     // No additional checking required
                     AlternativeStrings::FixedWidth { .. } => (),
                     AlternativeStrings::Generic { .. } => {
-                        if self.negated {
+                        if *negated {
                             return Err(BodySigParseError::NegatedGenericAltStr {
                                 start_pos: self.left_paren_pos.into(),
                             });
@@ -584,31 +657,21 @@ impl ParseContext {
         self.patterns.push(pattern);
         Ok(())
     }
-
-    // Contribute a digit to an accumulating decimal value
-    #[inline]
-    fn update_dec_value(&mut self, byte: u8, pos: usize) -> Result<(), BodySigParseError> {
-        self.dec_value = Some(
-            self.dec_value
-                .unwrap_or_default()
-                .checked_mul(10)
-                .ok_or(BodySigParseError::DecimalOverflow { pos: pos.into() })?
-                .checked_add((byte - b'0') as usize)
-                .ok_or(BodySigParseError::DecimalOverflow { pos: pos.into() })?,
-        );
-        Ok(())
-    }
 }
 
 // When reading an anchored byte subpattern, it can be in one of two states after the range is read
 enum PendingAnchoredByte {
     HaveByte {
-        start_pos: usize,
+        // Span of the whole expression so far (anchor byte through the
+        // closing bracket), for error reporting
+        span: Position,
         byte: MatchByte,
         range: RangeInclusive<u8>,
     },
     HaveString {
-        start_pos: usize,
+        // Span of the whole expression so far (match string through the
+        // closing bracket), for error reporting
+        span: Position,
         string: MatchBytes,
         range: RangeInclusive<u8>,
     },
@@ -675,20 +738,24 @@ impl ParentheticalContext {
     }
 }
 
-impl TryFrom<&[u8]> for BodySig {
-    type Error = BodySigParseError;
-
+impl BodySig {
+    /// Parse a body signature as via the `TryFrom<&[u8]>` implementation,
+    /// but with the specified [`BraceLimits`] in place of the defaults
     #[allow(clippy::too_many_lines)]
-    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+    pub fn parse_with_brace_limits(
+        value: &[u8],
+        limits: BraceLimits,
+    ) -> Result<Self, BodySigParseError> {
         let mut pc = ParseContext::default();
 
         let mut state = State::HighNyble;
 
-        for (pos, &byte) in value.iter().enumerate() {
+        for token in Tokenizer::with_brace_limits(value, limits) {
+            let token = token?;
             match state {
                 State::HighNyble => {
-                    match byte {
-                        b'0'..=b'9' | b'a'..=b'f' | b'A'..=b'F' => {
+                    match token {
+                        Token::HexNyble { byte, .. } => {
                             // TODO: make sure no right-side pattern modifiers have been set
                             pc.mask = MatchMask::None;
                             pc.cur_byte = hex_nyble(byte, true);
@@ -700,7 +767,7 @@ impl TryFrom<&[u8]> for BodySig {
                             }
                             state = State::LowNyble;
                         }
-                        b'L' | b'W' => {
+                        Token::ClassChar { byte, .. } => {
                             // b'B' is handled as part of of a pending byte
                             if let Some(pa) = &mut pc.paren_cxt {
                                 pa.character_class = Some(CharacterClass::try_from(byte).unwrap());
@@ -708,31 +775,29 @@ impl TryFrom<&[u8]> for BodySig {
                             }
                         }
                         // byte-level wildcard.  May cover an entire byte or just one nyble
-                        QUESTION_MARK => {
+                        Token::NybleWildcard { .. } => {
                             pc.cur_byte = 0;
                             pc.mask = MatchMask::High;
                             state = State::LowNyble;
                         }
-                        _ => state = pc.handle_non_matchbyte(Some((pos, byte)))?,
+                        other => state = pc.handle_non_matchbyte(Some(other))?,
                     }
                 }
                 State::LowNyble => {
-                    match byte {
-                        b'0'..=b'9' | b'a'..=b'f' | b'A'..=b'F' => {
+                    let pos = token.pos();
+                    match token {
+                        Token::HexNyble { byte, .. } => {
                             if pc.paren_cxt.is_some() {
                                 // This byte completes the low nybble of a new byte.
                                 // If we were inside a parenthetical expression, any
                                 // bytes need to be flushed to the prior match first.
-
-                                // This never fails in parenthetical context
-                                pc.flush_match_bytes().unwrap();
+                                pc.flush_match_bytes()?;
                             }
                             pc.cur_byte |= hex_nyble(byte, false);
                         }
-                        QUESTION_MARK => {
+                        Token::NybleWildcard { .. } => {
                             if pc.paren_cxt.is_some() {
-                                // This never fails in parenthetical context
-                                pc.flush_match_bytes().unwrap();
+                                pc.flush_match_bytes()?;
                             }
                             pc.mask = if let MatchMask::High = pc.mask {
                                 // ??
@@ -742,14 +807,14 @@ impl TryFrom<&[u8]> for BodySig {
                                 MatchMask::Low
                             };
                         }
-                        PAREN_RIGHT => {
-                            state = pc.handle_cc_close();
+                        Token::ParenClose { .. } => {
+                            state = pc.handle_cc_close()?;
                             continue;
                         }
                         other => {
                             return Err(BodySigParseError::ExpectingLowNyble {
                                 pos: pos.into(),
-                                found: Some(other.into()),
+                                found: Some(other.sig_char()),
                             })
                         }
                     }
@@ -764,174 +829,21 @@ impl TryFrom<&[u8]> for BodySig {
                     );
                     state = State::HighNyble;
                 }
-                State::CurlyBraceLower => match byte {
-                    b'0'..=b'9' => {
-                        pc.update_dec_value(byte, pos)?;
-                    }
-                    MINUS_SIGN => {
-                        pc.cur_range = pc.dec_value.take().map(|dec_value| (dec_value..).into());
-                        state = State::CurlyBraceUpper;
-                    }
-                    CURLY_RIGHT => {
-                        if let Some(dec_value) = pc.dec_value.take() {
-                            pc.cur_range = Some(Range::Exact(dec_value));
-                        } else {
-                            return Err(BodySigParseError::EmptyBraces {
-                                start_pos: pc.left_brace_pos.into(),
-                            });
-                        }
-                        match pc.cur_range.take().unwrap() {
-                            Range::Exact(size) if size <= 128 => pc.push_matchbyte(
-                                MatchByte::WildcardMany {
-                                    size: (size).try_into().unwrap(),
-                                },
-                                pc.left_brace_pos,
-                            ),
-                            range => {
-                                pc.flush_match_bytes()?;
-                                pc.push_pattern(Pattern::ByteRange(range))?;
-                                pc.cur_range.take();
-                            }
-                        }
-                        state = State::HighNyble;
-                    }
-                    other => {
-                        return Err(BodySigParseError::UnexpectedChar {
-                            context: Context::CurlyBraceRange,
-                            pos: pos.into(),
-                            found: other.into(),
-                        })
-                    }
-                },
-                State::CurlyBraceUpper =>
-                // This state is in effect on the other side of a `-` within a curly-brace range
-                {
-                    match byte {
-                        b'0'..=b'9' => {
-                            pc.update_dec_value(byte, pos)?;
-                        }
-                        CURLY_RIGHT => {
-                            let range = if let Some(Range::From(range_from)) = pc.cur_range.take() {
-                                // Lower bound was specified
-                                if let Some(dec_value) = pc.dec_value.take() {
-                                    // Upper bound was specified
-                                    if dec_value < range_from.start {
-                                        return Err(BodySigParseError::RangeBoundsInverted {
-                                            start_pos: pc.left_brace_pos.into(),
-                                            start: range_from.start,
-                                            end: dec_value,
-                                        });
-                                    }
-                                    (range_from.start..=dec_value).into()
-                                } else {
-                                    // Only lower bound was specified
-                                    range_from.into()
-                                }
-                            } else {
-                                // No lower bound was specified
-                                if let Some(dec_value) = pc.dec_value.take() {
-                                    (..=dec_value).into()
-                                } else {
-                                    return Err(BodySigParseError::NoBraceBounds {
-                                        start_pos: pc.left_brace_pos.into(),
-                                    });
-                                }
-                            };
-                            pc.flush_match_bytes()?;
-                            pc.push_pattern(Pattern::ByteRange(range))?;
-                            state = State::HighNyble;
-                        }
-                        other => {
-                            return Err(BodySigParseError::UnexpectedChar {
-                                context: Context::CurlyBraceRange,
-                                pos: pos.into(),
-                                found: other.into(),
-                            })
-                        }
-                    }
-                }
-                State::BracketLower =>
-                // This state is in effect on the other side of a `-` within a square-bracket range
-                {
-                    match byte {
-                        b'0'..=b'9' => {
-                            pc.update_dec_value(byte, pos)?;
-                        }
-                        MINUS_SIGN | BRACKET_RIGHT => {
-                            // FIXME: logic is screwy here.  Notice the repetition below
-                            if let Some(dec_value) = pc.dec_value.take() {
-                                if dec_value > ANCHORED_BYTE_RANGE_MAX {
-                                    return Err(BodySigParseError::AnchoredByteInvalidLowerBound {
-                                        bracket_pos: pc.left_bracket_pos.into(),
-                                        found: dec_value,
-                                    });
-                                }
-                                pc.cur_range = Some((dec_value..).into());
-                                state = State::BracketUpper;
-                            } else if byte == MINUS_SIGN {
-                                return Err(BodySigParseError::BracketRangeMissingLowerBound {
-                                    start_pos: pc.left_bracket_pos.into(),
-                                });
-                            } else {
-                                // Found closing bracket
-                                state = pc.handle_anchored_byte_range(pos)?;
-                            }
-                            if byte == BRACKET_RIGHT {
-                                // No upper bound specified, which is apparently OK
-                                state = pc.handle_anchored_byte_range(pos)?;
-                            }
-                        }
-                        other => {
-                            return Err(BodySigParseError::BracketRangeUnexpectedChar {
-                                pos: pos.into(),
-                                found: other.into(),
-                            })
-                        }
-                    }
-                }
-                State::BracketUpper => match byte {
-                    b'0'..=b'9' => {
-                        pc.update_dec_value(byte, pos)?;
-                    }
-                    BRACKET_RIGHT => state = pc.handle_anchored_byte_range(pos)?,
-                    other => {
-                        return Err(BodySigParseError::BracketRangeUnexpectedChar {
-                            pos: pos.into(),
-                            found: other.into(),
-                        })
-                    }
-                },
-                State::Negate => match byte {
-                    PAREN_LEFT => {
-                        pc.left_paren_pos = pos;
-                        pc.negated = true;
-                        pc.paren_cxt = Some(ParentheticalContext {
-                            start_pos: pos,
-                            ..Default::default()
-                        });
-                        state = State::HighNyble;
-                    }
-                    other => {
-                        return Err(BodySigParseError::NegateUnexpectedChar {
-                            pos: pos.into(),
-                            found: other.into(),
-                        })
-                    }
-                },
                 State::CharacterClass => {
-                    if byte == PAREN_RIGHT {
-                        state = pc.handle_cc_close();
+                    if let Token::ParenClose { .. } = token {
+                        state = pc.handle_cc_close()?;
                     } else {
                         return Err(BodySigParseError::CharClassExpectCloseParen {
-                            pos: pos.into(),
-                            found: byte.into(),
+                            pos: token.pos().into(),
+                            found: token.sig_char(),
                         });
                     }
                 }
             }
         }
 
-        // Check final state
+        // Check final state.  Unterminated brace, bracket and negation
+        // expressions have already been reported by the tokenizer.
         match state {
             State::HighNyble => {
                 pc.handle_non_matchbyte(None)?;
@@ -943,17 +855,6 @@ impl TryFrom<&[u8]> for BodySig {
                     found: None,
                 })
             }
-            State::CurlyBraceLower | State::CurlyBraceUpper => {
-                return Err(BodySigParseError::CurlyBraceNotClosed {
-                    start_pos: pc.left_brace_pos.into(),
-                })
-            }
-            State::BracketLower | State::BracketUpper => {
-                return Err(BodySigParseError::BracketNotClosed {
-                    start_pos: pc.left_bracket_pos.into(),
-                })
-            }
-            State::Negate => return Err(BodySigParseError::NegationTargetless),
             State::CharacterClass => {
                 return Err(BodySigParseError::CharClassUnterminated {
                     start_pos: pc.left_paren_pos.into(),
@@ -983,3 +884,11 @@ impl TryFrom<&[u8]> for BodySig {
         })
     }
 }
+
+impl TryFrom<&[u8]> for BodySig {
+    type Error = BodySigParseError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse_with_brace_limits(value, BraceLimits::default())
+    }
+}
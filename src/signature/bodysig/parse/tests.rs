@@ -18,7 +18,7 @@
 
 use super::{
     super::{pattern::ByteAnchorSide, *},
-    BodySigParseError, Context,
+    BodySigParseError, BraceLimits, Context,
 };
 use crate::{
     signature::bodysig::{
@@ -264,11 +264,49 @@ fn anchored_byte_right_with_leading() {
     );
 }
 
+#[test]
+fn anchored_byte_on_both_sides() {
+    // A left anchor places the byte before the string; a right anchor places
+    // it after.  Neither parsed form leaves the string empty, so the
+    // defensive predicate holds for both.
+    let left = BodySig::try_from(b"aa[1-2]bbcc".as_slice()).unwrap();
+    assert!(matches!(
+        left.patterns[0],
+        Pattern::AnchoredByte {
+            anchor_side: ByteAnchorSide::Left,
+            ..
+        }
+    ));
+    assert!(!left.has_anchored_byte_on_both_sides());
+
+    let right = BodySig::try_from(b"aabb[1-2]cc".as_slice()).unwrap();
+    assert!(matches!(
+        right.patterns[0],
+        Pattern::AnchoredByte {
+            anchor_side: ByteAnchorSide::Right,
+            ..
+        }
+    ));
+    assert!(!right.has_anchored_byte_on_both_sides());
+
+    // A right-side anchor with no match string on the left can't be produced
+    // by the parser, but is representable when built by hand
+    let degenerate = BodySig {
+        patterns: vec![Pattern::AnchoredByte {
+            anchor_side: ByteAnchorSide::Right,
+            byte: MatchByte::Full(0xcc),
+            range: 1..=2,
+            string: vec![].into(),
+        }],
+    };
+    assert!(degenerate.has_anchored_byte_on_both_sides());
+}
+
 #[test]
 fn anchored_byte_left_string_too_small() {
     assert_eq!(
         Err(BodySigParseError::AnchoredByteStringTooSmall {
-            start_pos: 5.into(),
+            start_pos: Position::span(5, 11),
         }),
         BodySig::try_from(b"abcd*00[2-4]01*e0f0".as_slice())
     );
@@ -278,17 +316,29 @@ fn anchored_byte_left_string_too_small() {
 fn anchored_byte_string_too_small() {
     assert_eq!(
         Err(BodySigParseError::AnchoredByteStringTooSmall {
-            start_pos: 5.into(),
+            start_pos: Position::span(5, 11),
         }),
         BodySig::try_from(b"abcd*00[2-4]01*e0f0".as_slice())
     );
 }
 
+#[test]
+fn anchored_byte_error_spans_whole_expression() {
+    // The reported span runs from the first anchor byte through the closing
+    // bracket, and renders as a range rather than a single position
+    let err = BodySig::try_from(b"abcd*00[2-4]01*e0f0".as_slice()).unwrap_err();
+    let BodySigParseError::AnchoredByteStringTooSmall { start_pos } = err else {
+        panic!("expected AnchoredByteStringTooSmall, got {err:?}");
+    };
+    assert_eq!(start_pos, Position::Range(5..=11));
+    assert_eq!(start_pos.to_string(), "from pos 5 to 11");
+}
+
 #[test]
 fn anchored_byte_missing_single_byte() {
     assert_eq!(
         Err(BodySigParseError::AnchoredByteMissingSingleByte {
-            start_pos: 5.into(),
+            start_pos: Position::span(5, 13),
         }),
         BodySig::try_from(b"abcd*0001[2-4]0203*e0f0".as_slice())
     );
@@ -300,14 +350,14 @@ fn anchored_byte_expecting_single_byte() {
     // after the bracket expression (i.e., some other kind of pattern was starting)
     assert_eq!(
         Err(BodySigParseError::AnchoredByteExpectingSingleByte {
-            start_pos: 5.into(),
+            start_pos: Position::span(5, 13),
             pos: 14.into(),
         }),
         BodySig::try_from(b"abcd*0001[2-4]x".as_slice())
     );
     assert_eq!(
         Err(BodySigParseError::AnchoredByteExpectingSingleByte {
-            start_pos: 5.into(),
+            start_pos: Position::span(5, 13),
             pos: Position::End,
         }),
         BodySig::try_from(b"abcd*0001[2-4]".as_slice())
@@ -318,7 +368,7 @@ fn anchored_byte_expecting_single_byte() {
 fn anchored_byte_invalid_lower_bound() {
     assert_eq!(
         Err(BodySigParseError::AnchoredByteInvalidLowerBound {
-            bracket_pos: 9.into(),
+            bracket_pos: Position::span(9, 12),
             found: 33
         }),
         BodySig::try_from(b"abcd*0001[33-4]aa".as_slice())
@@ -329,7 +379,7 @@ fn anchored_byte_invalid_lower_bound() {
 fn anchored_byte_invalid_upper_bound() {
     assert_eq!(
         Err(BodySigParseError::AnchoredByteInvalidUpperBound {
-            bracket_pos: 9.into(),
+            bracket_pos: Position::span(9, 13),
             found: 1,
             lower: 2,
         }),
@@ -337,7 +387,7 @@ fn anchored_byte_invalid_upper_bound() {
     );
     assert_eq!(
         Err(BodySigParseError::AnchoredByteInvalidUpperBound {
-            bracket_pos: 9.into(),
+            bracket_pos: Position::span(9, 14),
             found: 40,
             lower: 2,
         }),
@@ -345,7 +395,7 @@ fn anchored_byte_invalid_upper_bound() {
     );
     assert_eq!(
         Err(BodySigParseError::AnchoredByteInvalidUpperBound {
-            bracket_pos: 9.into(),
+            bracket_pos: Position::span(9, 13),
             found: 0,
             lower: 3,
         }),
@@ -366,11 +416,13 @@ fn astrs_single_byte() {
     assert_eq!(
         Ok(BodySig {
             patterns: vec![
-                Pattern::AlternativeStrings(AlternativeStrings::FixedWidth {
+                Pattern::AlternativeStrings {
                     negated: false,
-                    width: 1,
-                    data: hex!("aabbcc").into(),
-                }),
+                    astrs: AlternativeStrings::FixedWidth {
+                        width: 1,
+                        data: hex!("aabbcc").into(),
+                    },
+                },
                 Pattern::String(hex!("ffff").into(), PatternModifier::empty())
             ],
         }),
@@ -383,11 +435,13 @@ fn astrs_multi_byte() {
     assert_eq!(
         Ok(BodySig {
             patterns: vec![
-                Pattern::AlternativeStrings(AlternativeStrings::FixedWidth {
+                Pattern::AlternativeStrings {
                     negated: false,
-                    width: 2,
-                    data: hex!("aa01bb02cc03").into(),
-                }),
+                    astrs: AlternativeStrings::FixedWidth {
+                        width: 2,
+                        data: hex!("aa01bb02cc03").into(),
+                    },
+                },
                 Pattern::String(hex!("ffff").into(), PatternModifier::empty())
             ],
         }),
@@ -401,15 +455,18 @@ fn astrs_generic_wildcard() {
         Ok(BodySig {
             patterns: vec![
                 Pattern::String(hex!("aaaa").into(), PatternModifier::empty()),
-                Pattern::AlternativeStrings(AlternativeStrings::Generic {
-                    ranges: vec![0..1, 1..2, 2..3],
-                    data: vec![
-                        MatchByte::HighNyble(0x00),
-                        MatchByte::Full(0x02),
-                        MatchByte::Full(0x03),
-                    ]
-                    .into()
-                }),
+                Pattern::AlternativeStrings {
+                    negated: false,
+                    astrs: AlternativeStrings::Generic {
+                        ranges: vec![0..1, 1..2, 2..3],
+                        data: vec![
+                            MatchByte::HighNyble(0x00),
+                            MatchByte::Full(0x02),
+                            MatchByte::Full(0x03),
+                        ]
+                        .into()
+                    },
+                },
                 Pattern::String(hex!("bbbb").into(), PatternModifier::empty()),
             ],
         }),
@@ -423,10 +480,13 @@ fn astrs_generic_variable() {
         Ok(BodySig {
             patterns: vec![
                 Pattern::String(hex!("aaaa").into(), PatternModifier::empty()),
-                Pattern::AlternativeStrings(AlternativeStrings::Generic {
-                    ranges: vec![0..2, 2..3],
-                    data: hex!("010203").into(),
-                }),
+                Pattern::AlternativeStrings {
+                    negated: false,
+                    astrs: AlternativeStrings::Generic {
+                        ranges: vec![0..2, 2..3],
+                        data: hex!("010203").into(),
+                    },
+                },
                 Pattern::String(hex!("bbbb").into(), PatternModifier::empty()),
             ],
         }),
@@ -446,10 +506,13 @@ fn empty_parens() {
 fn empty_alternative_string() {
     assert_eq!(
         Ok(BodySig {
-            patterns: vec![Pattern::AlternativeStrings(AlternativeStrings::Generic {
-                ranges: vec![0..0, 0..1, 1..2],
-                data: hex!("1234").into()
-            })]
+            patterns: vec![Pattern::AlternativeStrings {
+                negated: false,
+                astrs: AlternativeStrings::Generic {
+                    ranges: vec![0..0, 0..1, 1..2],
+                    data: hex!("1234").into()
+                }
+            }]
         }),
         BodySig::try_from(b"(|12|34)".as_slice()),
     );
@@ -461,11 +524,13 @@ fn single_alternative_string() {
         Ok(BodySig {
             patterns: vec![
                 Pattern::String(hex!("aaaa").into(), PatternModifier::empty()),
-                Pattern::AlternativeStrings(AlternativeStrings::FixedWidth {
+                Pattern::AlternativeStrings {
                     negated: true,
-                    width: 1,
-                    data: hex!("12").into()
-                }),
+                    astrs: AlternativeStrings::FixedWidth {
+                        width: 1,
+                        data: hex!("12").into()
+                    },
+                },
                 Pattern::String(hex!("bbbb").into(), PatternModifier::empty()),
             ],
         }),
@@ -539,7 +604,7 @@ fn bracket_upper_unexpected_char() {
 fn bracket_lower_missing() {
     assert_eq!(
         Err(BodySigParseError::BracketRangeMissingLowerBound {
-            start_pos: 2.into()
+            start_pos: Position::span(2, 3)
         }),
         BodySig::try_from(b"01[-1]abcd".as_slice())
     );
@@ -560,7 +625,7 @@ fn brackets_only_one_bound() {
     );
     assert_eq!(
         Err(BodySigParseError::AnchoredByteInvalidLowerBound {
-            bracket_pos: 2.into(),
+            bracket_pos: Position::span(2, 5),
             found: 50,
         }),
         BodySig::try_from(b"01[50]abcd".as_slice())
@@ -571,7 +636,7 @@ fn brackets_only_one_bound() {
 fn brackets_empty() {
     assert_eq!(
         Err(BodySigParseError::BracketRangeEmpty {
-            start_pos: 2.into()
+            start_pos: Position::span(2, 3)
         }),
         BodySig::try_from(b"01[]abcd".as_slice())
     );
@@ -633,6 +698,39 @@ fn word_boundary() {
     dbg!(bs);
 }
 
+#[test]
+fn cc_duplicated_side() {
+    // Two classes on the left side of the same string
+    assert_eq!(
+        Err(BodySigParseError::CharClassDuplicatedSide { pos: 3.into() }),
+        BodySig::try_from(b"(L)(B)aabb".as_slice())
+    );
+    // ...or on the right side
+    assert_eq!(
+        Err(BodySigParseError::CharClassDuplicatedSide { pos: 9.into() }),
+        BodySig::try_from(b"aabb!(B)!(W)".as_slice())
+    );
+    // A class on each side of a string remains legal
+    assert!(BodySig::try_from(b"(B)6162!(L)".as_slice()).is_ok());
+}
+
+#[test]
+fn cc_word_marker_on_non_word_string() {
+    // `(W)` attached to a string of entirely non-word bytes can't be
+    // satisfied in its non-negated form
+    assert_eq!(
+        Err(BodySigParseError::CharClassWordMarkerNonWord {
+            start_pos: 3.into()
+        }),
+        BodySig::try_from(b"(W)2e2e2e2e".as_slice())
+    );
+    // Word characters (or bytes that could match one) make it legal
+    assert!(BodySig::try_from(b"(W)4142".as_slice()).is_ok());
+    assert!(BodySig::try_from(b"(W)2e2e?e".as_slice()).is_ok());
+    // ...as does the negated form, regardless of content
+    assert!(BodySig::try_from(b"!(W)2e2e".as_slice()).is_ok());
+}
+
 #[test]
 fn christmas_tree() {
     let bs = BodySig::try_from(
@@ -642,6 +740,167 @@ fn christmas_tree() {
     dbg!(bs);
 }
 
+#[test]
+fn christmas_tree_complexity() {
+    let bs = BodySig::try_from(
+        b"0102{3}0405*0607{8-}090a{-12}0c0d*0e0f{120}*aabb[1-2]cc*(B)deadbeef!(W)".as_slice(),
+    )
+    .unwrap();
+    let cx = bs.complexity();
+    assert_eq!(
+        cx,
+        crate::signature::Complexity {
+            pattern_count: 13,
+            total_static_bytes: 19,
+            wildcard_count: 4,
+            unbounded_range_count: 1,
+            alternative_branch_count: 0,
+            pcre_subsig_count: 0,
+        }
+    );
+    assert_eq!(cx.score(), 37);
+}
+
+#[test]
+fn christmas_tree_wildcard_count() {
+    let bs = BodySig::try_from(
+        b"0102{3}0405*0607{8-}090a{-12}0c0d*0e0f{120}*aabb[1-2]cc*(B)deadbeef!(W)".as_slice(),
+    )
+    .unwrap();
+    // Four `*` wildcards plus the open-ended `{8-}` byte range
+    assert_eq!(bs.wildcard_count(), 5);
+    assert!(bs.contains_multiple_wildcards());
+
+    let single = BodySig::try_from(b"0102*0304{-12}0506".as_slice()).unwrap();
+    assert_eq!(single.wildcard_count(), 1);
+    assert!(!single.contains_multiple_wildcards());
+}
+
+#[test]
+fn pattern_accessors() {
+    let bs = BodySig::try_from(b"0102*aabb[1-2]cc{3-4}0304".as_slice()).unwrap();
+    assert_eq!(
+        bs.first_pattern(),
+        Some(&Pattern::String(
+            hex!("0102").into(),
+            PatternModifier::empty()
+        ))
+    );
+    assert_eq!(
+        bs.last_pattern(),
+        Some(&Pattern::String(
+            hex!("0304").into(),
+            PatternModifier::empty()
+        ))
+    );
+    assert_eq!(bs.pattern_at(1), Some(&Pattern::Wildcard));
+    assert_eq!(bs.pattern_at(bs.patterns.len()), None);
+}
+
+#[test]
+fn from_ascii_text_case_sensitive() {
+    let bs = BodySig::from_ascii_text("FedEx", false).unwrap();
+    assert_eq!(
+        bs,
+        BodySig {
+            patterns: vec![Pattern::String(
+                hex!("4665644578").into(),
+                PatternModifier::empty()
+            )],
+        }
+    );
+    // ...and it exports as plain hex
+    let mut sb = SigBytes::new();
+    bs.append_sigbytes(&mut sb).unwrap();
+    assert_eq!(sb.to_string(), "4665644578");
+}
+
+#[test]
+fn from_ascii_text_case_insensitive() {
+    let bs = BodySig::from_ascii_text("FedEx", true).unwrap();
+    // Each alphabetic byte becomes a two-branch fixed-width group; the
+    // export round-trips through the parser
+    let mut sb = SigBytes::new();
+    bs.append_sigbytes(&mut sb).unwrap();
+    assert_eq!(sb.to_string(), "(46|66)(45|65)(44|64)(45|65)(58|78)");
+    assert_eq!(BodySig::try_from(sb.as_bytes()), Ok(bs));
+
+    assert_eq!(
+        BodySig::from_ascii_text("Fedéx", true),
+        Err(BodySigParseError::NotAscii)
+    );
+}
+
+#[test]
+fn from_bytes_literal() {
+    let bs = BodySig::from_bytes_literal(b"\x00\x01\xfe\xff");
+    assert_eq!(
+        bs,
+        BodySig {
+            patterns: vec![Pattern::String(
+                hex!("0001feff").into(),
+                PatternModifier::empty()
+            )],
+        }
+    );
+    assert!(BodySig::from_bytes_literal(b"").patterns.is_empty());
+}
+
+#[test]
+fn lint_flags_ubiquitous_prefixes() {
+    let opts = LintOptions::default();
+    for (sig, prefix) in [
+        (b"4d5a90000304deadbeef".as_slice(), "4d5a9000"), // MZ header
+        (b"504b0304deadbeef".as_slice(), "504b0304"),     // ZIP local file header
+        (b"d0cf11e0deadbeef".as_slice(), "d0cf11e0"),     // OLE2 header
+    ] {
+        let bs = BodySig::try_from(sig).unwrap();
+        assert_eq!(
+            bs.lint(&opts),
+            vec![BodySigLint::UbiquitousPrefix { prefix }]
+        );
+    }
+}
+
+#[test]
+fn lint_flags_repeated_byte_runs() {
+    let opts = LintOptions::default();
+
+    let bs = BodySig::try_from(b"000000000041deadbeef".as_slice()).unwrap();
+    assert_eq!(
+        bs.lint(&opts),
+        vec![BodySigLint::RepeatedByteRun {
+            position: LintPosition::Leading,
+            byte: 0x00,
+            count: 5,
+        }]
+    );
+
+    let bs = BodySig::try_from(b"deadbeefffffffffffff".as_slice()).unwrap();
+    assert_eq!(
+        bs.lint(&opts),
+        vec![BodySigLint::RepeatedByteRun {
+            position: LintPosition::Trailing,
+            byte: 0xff,
+            count: 6,
+        }]
+    );
+
+    // Thresholds are configurable
+    let bs = BodySig::try_from(b"000000000041deadbeef".as_slice()).unwrap();
+    assert!(bs
+        .lint(&LintOptions {
+            max_repeated_run: 8
+        })
+        .is_empty());
+}
+
+#[test]
+fn lint_clean_signature() {
+    let bs = BodySig::try_from(b"deadbeef*1337c0de".as_slice()).unwrap();
+    assert!(bs.lint(&LintOptions::default()).is_empty());
+}
+
 #[test]
 fn low_nyble_bad() {
     assert_eq!(
@@ -696,25 +955,25 @@ fn curly_brace_not_closed() {
 fn bracket_not_closed() {
     assert_eq!(
         Err(BodySigParseError::BracketNotClosed {
-            start_pos: 11.into(),
+            start_pos: Position::span(11, 11),
         }),
         BodySig::try_from(b"abcd{6}0123[".as_slice())
     );
     assert_eq!(
         Err(BodySigParseError::BracketNotClosed {
-            start_pos: 13.into(),
+            start_pos: Position::span(13, 14),
         }),
         BodySig::try_from(b"abcd{6}012345[5".as_slice())
     );
     assert_eq!(
         Err(BodySigParseError::BracketNotClosed {
-            start_pos: 15.into(),
+            start_pos: Position::span(15, 17),
         }),
         BodySig::try_from(b"abcd{6}01234567[5-".as_slice())
     );
     assert_eq!(
         Err(BodySigParseError::BracketNotClosed {
-            start_pos: 17.into(),
+            start_pos: Position::span(17, 20),
         }),
         BodySig::try_from(b"abcd{6}0123456789[5-6".as_slice())
     );
@@ -1023,3 +1282,396 @@ fn legal_static_bytes_with_small_fixed_range() {
         BodySig::try_from(b"00{2}abab".as_slice()),
     );
 }
+
+#[test]
+fn astrs_contains_any() {
+    // An empty alternative in a generic set is unconstrained
+    let astrs = AlternativeStrings::Generic {
+        ranges: vec![0..0, 0..1, 1..2],
+        data: hex!("1234").into(),
+    };
+    assert!(astrs.contains_any());
+
+    let astrs = AlternativeStrings::Generic {
+        ranges: vec![0..2, 2..3],
+        data: hex!("010203").into(),
+    };
+    assert!(!astrs.contains_any());
+
+    // A fixed-width alternative consisting only of ignored bytes is
+    // unconstrained
+    let astrs = AlternativeStrings::FixedWidth {
+        width: 1,
+        data: vec![MatchByte::Full(0x12), MatchByte::Any].into(),
+    };
+    assert!(astrs.contains_any());
+
+    let astrs = AlternativeStrings::FixedWidth {
+        width: 1,
+        data: hex!("1234").into(),
+    };
+    assert!(!astrs.contains_any());
+}
+
+#[test]
+fn matcher_class_static_string() {
+    let bs = BodySig::try_from(b"deadbeef0102".as_slice()).unwrap();
+    assert_eq!(bs.matcher_class(), MatcherClass::BoyerMoore);
+}
+
+#[test]
+fn matcher_class_nyble_wildcard() {
+    // A single `?` nyble forces the Aho-Corasick matcher
+    let bs = BodySig::try_from(b"deadbe?f0102".as_slice()).unwrap();
+    assert_eq!(bs.matcher_class(), MatcherClass::AhoCorasick);
+}
+
+#[test]
+fn matcher_class_inline_byte_range() {
+    // A short `{n}` range folds into the match string as ignored bytes, but
+    // still requires Aho-Corasick
+    let bs = BodySig::try_from(b"deadbeef{3}0102".as_slice()).unwrap();
+    assert_eq!(bs.matcher_class(), MatcherClass::AhoCorasick);
+
+    // ...as does a range large enough to remain a separate pattern
+    let bs = BodySig::try_from(b"deadbeef{200}0102".as_slice()).unwrap();
+    assert_eq!(bs.matcher_class(), MatcherClass::AhoCorasick);
+}
+
+#[test]
+fn compact_string_short_sig_shown_fully() {
+    let bs = BodySig::try_from(b"aabbccdd{5-10}*e0e1e2e3".as_slice()).unwrap();
+    assert_eq!(bs.to_compact_string(), "aabbccdd{5-10}*e0e1e2e3");
+}
+
+#[test]
+fn compact_string_truncates_long_strings() {
+    let bs = BodySig::try_from(b"00112233445566778899aabb".as_slice()).unwrap();
+    assert_eq!(bs.to_compact_string(), "0011223344556677...");
+}
+
+#[test]
+fn compact_string_summarizes_alternatives() {
+    let bs = BodySig::try_from(b"deadbeef(aa|bb|cc)0102".as_slice()).unwrap();
+    assert_eq!(bs.to_compact_string(), "deadbeef(3 alternatives)0102");
+}
+
+#[test]
+fn best_scan_string_longest_run() {
+    // `ccddee` is the longest fully-static run
+    let bs = BodySig::try_from(b"aabb??ccddee".as_slice()).unwrap();
+    assert_eq!(
+        bs.best_scan_string(),
+        Some(hex!("ccddee").map(MatchByte::Full).as_slice())
+    );
+
+    // Runs split by a wildcard pattern are considered independently
+    let bs = BodySig::try_from(b"aabbccdd*eeff".as_slice()).unwrap();
+    assert_eq!(
+        bs.best_scan_string(),
+        Some(hex!("aabbccdd").map(MatchByte::Full).as_slice())
+    );
+}
+
+#[test]
+fn best_scan_string_tie_takes_first() {
+    let bs = BodySig::try_from(b"aabb??ccdd".as_slice()).unwrap();
+    assert_eq!(
+        bs.best_scan_string(),
+        Some(hex!("aabb").map(MatchByte::Full).as_slice())
+    );
+}
+
+#[test]
+fn best_scan_string_no_literals() {
+    // The parser enforces a static-byte minimum, so build directly
+    let bs = BodySig {
+        patterns: vec![Pattern::String(
+            vec![MatchByte::Any, MatchByte::LowNyble(0x0a)].into(),
+            PatternModifier::empty(),
+        )],
+    };
+    assert_eq!(bs.best_scan_string(), None);
+}
+
+#[test]
+fn replace_pattern_guards_invariants() {
+    let mut bs = BodySig::try_from(b"aabbccdd*e0e1e2e3".as_slice()).unwrap();
+
+    // A wildcard may not take the leading or trailing position
+    assert_eq!(
+        bs.replace_pattern(0, Pattern::Wildcard),
+        Err(BodySigParseError::LeadingWildcard {
+            pattern: Pattern::Wildcard
+        })
+    );
+    assert_eq!(
+        bs.replace_pattern(2, Pattern::Wildcard),
+        Err(BodySigParseError::TrailingUnsizedPattern {
+            pattern: Pattern::Wildcard
+        })
+    );
+    assert_eq!(
+        bs.replace_pattern(3, Pattern::Wildcard),
+        Err(BodySigParseError::PatternIndexOutOfBounds { idx: 3, len: 3 })
+    );
+
+    // An interior replacement returns the previous pattern
+    let old = bs
+        .replace_pattern(1, Pattern::ByteRange((2..=4).into()))
+        .unwrap();
+    assert_eq!(old, Pattern::Wildcard);
+    let mut sb = SigBytes::new();
+    bs.append_sigbytes(&mut sb).unwrap();
+    assert_eq!(sb.to_string(), "aabbccdd{2-4}e0e1e2e3");
+}
+
+#[test]
+fn swap_patterns_guards_invariants() {
+    let mut bs = BodySig::try_from(b"aabbccdd*e0e1e2e3".as_slice()).unwrap();
+
+    // Swapping the wildcard to either end is refused
+    assert!(bs.swap_patterns(0, 1).is_err());
+    assert!(bs.swap_patterns(1, 2).is_err());
+    assert!(bs.swap_patterns(0, 3).is_err());
+
+    // Swapping the two strings is fine
+    bs.swap_patterns(0, 2).unwrap();
+    let mut sb = SigBytes::new();
+    bs.append_sigbytes(&mut sb).unwrap();
+    assert_eq!(sb.to_string(), "e0e1e2e3*aabbccdd");
+}
+
+#[test]
+fn count_unique_bytes_counts_distinct_full_bytes() {
+    let bs = BodySig::try_from(b"00001122".as_slice()).unwrap();
+    assert_eq!(bs.count_unique_bytes(), 3);
+    assert_eq!(bs.unique_bytes(), [0x00, 0x11, 0x22].into_iter().collect());
+
+    let bs = BodySig::try_from(b"0102030405060708".as_slice()).unwrap();
+    assert_eq!(bs.count_unique_bytes(), 8);
+}
+
+#[test]
+fn count_unique_bytes_ignores_nyble_wildcards() {
+    // Nyble and full-byte wildcards don't contribute distinct values
+    let bs = BodySig::try_from(b"0000??1?22".as_slice()).unwrap();
+    assert_eq!(bs.count_unique_bytes(), 2);
+}
+
+#[test]
+fn decode_ascii_mixes_text_escapes_and_wildcards() {
+    let bs = BodySig::try_from(b"deadbeef*4142{3-4}434445??46".as_slice()).unwrap();
+    assert_eq!(
+        bs.decode_ascii().to_string(),
+        r"\xde\xad\xbe\xef*AB{3-4}CDE??F"
+    );
+
+    // Alternatives decode branch-by-branch
+    let bs = BodySig::try_from(b"48454c4c4f(41|61)".as_slice()).unwrap();
+    assert_eq!(bs.decode_ascii().to_string(), "HELLO(A|a)");
+}
+
+#[test]
+fn trivially_matching_detection() {
+    // All-wildcard bodies never survive the parser (it enforces a static
+    // byte minimum), so construct them directly
+    let bs = BodySig {
+        patterns: vec![Pattern::Wildcard],
+    };
+    assert!(bs.is_trivially_matching());
+
+    let bs = BodySig {
+        patterns: vec![Pattern::String(
+            vec![MatchByte::Any; 4].into(),
+            PatternModifier::empty(),
+        )],
+    };
+    assert!(bs.is_trivially_matching());
+
+    let bs = BodySig {
+        patterns: vec![
+            Pattern::Wildcard,
+            Pattern::ByteRange((3..=4).into()),
+            Pattern::String(vec![MatchByte::Any; 2].into(), PatternModifier::empty()),
+        ],
+    };
+    assert!(bs.is_trivially_matching());
+
+    // A single constrained byte (even a nyble wildcard) is enough to escape
+    let bs = BodySig {
+        patterns: vec![Pattern::String(
+            vec![MatchByte::Any, MatchByte::LowNyble(0x0a)].into(),
+            PatternModifier::empty(),
+        )],
+    };
+    assert!(!bs.is_trivially_matching());
+
+    let bs = BodySig::try_from(b"dead*beef".as_slice()).unwrap();
+    assert!(!bs.is_trivially_matching());
+}
+
+#[test]
+fn boundary_modifier_view_roundtrips_all_parser_combinations() {
+    use crate::sigbytes::{AppendSigBytes, SigBytes};
+    use enumflags2::BitFlags;
+
+    // A class written after a string attaches to its right side only in
+    // negated form; a bare `(X)` there opens a class that must attach to a
+    // following string instead
+    let lefts = ["", "(B)", "!(B)", "(L)", "!(L)", "(W)", "!(W)"];
+    let rights = ["", "!(B)", "!(L)", "!(W)"];
+    for left in lefts {
+        for right in rights {
+            let sig = format!("{left}41424344{right}");
+            let bs = BodySig::try_from(sig.as_bytes()).unwrap();
+            let Pattern::String(_, pmod) = &bs.patterns[0] else {
+                panic!("expected a string pattern for {sig}");
+            };
+            let bm = bs.patterns[0].boundary_modifiers().unwrap();
+
+            // Each side of the view reflects what was written there
+            assert_eq!(bm.left.is_some(), !left.is_empty(), "in {sig}");
+            assert_eq!(bm.left_negated, left.starts_with('!'), "in {sig}");
+            assert_eq!(bm.right.is_some(), !right.is_empty(), "in {sig}");
+            assert_eq!(bm.right_negated, right.starts_with('!'), "in {sig}");
+
+            // Converting the view back yields the stored flags
+            assert_eq!(BitFlags::<PatternModifier>::from(bm), *pmod, "in {sig}");
+
+            // ... and the serializer reproduces the original text on both
+            // sides of the string
+            let mut sb = SigBytes::new();
+            bs.append_sigbytes(&mut sb).unwrap();
+            assert_eq!(sb.to_string(), sig);
+        }
+    }
+}
+
+#[test]
+fn boundary_modifier_view_maps_each_class() {
+    use crate::signature::bodysig::CharacterClass;
+
+    for (text, class) in [
+        ("(B)", CharacterClass::WordBoundary),
+        ("(L)", CharacterClass::LineOrFileBoundary),
+        ("(W)", CharacterClass::NonAlphaChar),
+    ] {
+        let bs = BodySig::try_from(format!("{text}41424344").as_bytes()).unwrap();
+        let bm = bs.patterns[0].boundary_modifiers().unwrap();
+        assert_eq!(bm.left, Some(class));
+        assert!(!bm.left_negated);
+        assert_eq!(bm.right, None);
+    }
+}
+
+#[test]
+fn boundary_modifier_view_absent_for_non_string_patterns() {
+    let bs = BodySig::try_from(b"4142*4344".as_slice()).unwrap();
+    assert!(bs.patterns[1].boundary_modifiers().is_none());
+}
+
+#[test]
+fn brace_range_zero_rejected() {
+    assert_eq!(
+        Err(BodySigParseError::BraceRangeZero {
+            start_pos: 8.into()
+        }),
+        BodySig::try_from(b"deadbeef{0}aabb".as_slice())
+    );
+    // `{0-0}` collapses to `{0}` and is rejected the same way
+    assert_eq!(
+        Err(BodySigParseError::BraceRangeZero {
+            start_pos: 8.into()
+        }),
+        BodySig::try_from(b"deadbeef{0-0}aabb".as_slice())
+    );
+}
+
+#[test]
+fn brace_bound_above_limit_rejected() {
+    // The default cap reflects the engine's 16-bit distance fields
+    assert_eq!(
+        Err(BodySigParseError::BraceBoundTooLarge {
+            start_pos: 8.into(),
+            bound: 65536,
+            limit: 65535,
+        }),
+        BodySig::try_from(b"deadbeef{65536}aabb".as_slice())
+    );
+    assert!(BodySig::try_from(b"deadbeef{65535}aabb".as_slice()).is_ok());
+
+    // The cap applies to either bound, and is configurable
+    assert_eq!(
+        Err(BodySigParseError::BraceBoundTooLarge {
+            start_pos: 8.into(),
+            bound: 300,
+            limit: 256,
+        }),
+        BodySig::parse_with_brace_limits(
+            b"deadbeef{100-300}aabb".as_slice(),
+            BraceLimits { max_bound: 256 },
+        )
+    );
+}
+
+#[test]
+fn brace_equal_bounds_collapse_to_exact() {
+    use crate::sigbytes::{AppendSigBytes, SigBytes};
+
+    // `{n-n}` exports as `{n}`, both below the inline-expansion threshold
+    // (where it becomes part of the surrounding string)...
+    let bs = BodySig::try_from(b"deadbeef{5-5}aabb".as_slice()).unwrap();
+    let mut sb = SigBytes::new();
+    bs.append_sigbytes(&mut sb).unwrap();
+    assert_eq!(sb.to_string(), "deadbeef{5}aabb");
+
+    // ...and above it (where it remains a discrete byte-range pattern)
+    let bs = BodySig::try_from(b"deadbeef{200-200}aabb".as_slice()).unwrap();
+    assert_eq!(bs.patterns[1], Pattern::ByteRange(Range::Exact(200)));
+    let mut sb = SigBytes::new();
+    bs.append_sigbytes(&mut sb).unwrap();
+    assert_eq!(sb.to_string(), "deadbeef{200}aabb");
+}
+
+#[test]
+fn static_runs_over_mixed_string() {
+    let bs = BodySig::try_from(b"aabb??ccddee{4}ff".as_slice()).unwrap();
+    let Pattern::String(mbs, _) = &bs.patterns[0] else {
+        panic!("expected string pattern");
+    };
+    let mut runs = mbs.static_runs();
+    assert_eq!(runs.next_run(), Some((0, [0xaa, 0xbb].as_slice())));
+    assert_eq!(runs.next_run(), Some((3, [0xcc, 0xdd, 0xee].as_slice())));
+    assert_eq!(runs.next_run(), Some((7, [0xff].as_slice())));
+    assert_eq!(runs.next_run(), None);
+}
+
+#[test]
+fn iter_alternatives_yields_each_branch() {
+    // Fixed-width groups chunk the stored data by width
+    let bs = BodySig::try_from(b"deadbeef(bbcc|ddee)ffff".as_slice()).unwrap();
+    let Pattern::AlternativeStrings { astrs, .. } = &bs.patterns[1] else {
+        panic!("expected alternative-string pattern");
+    };
+    assert_eq!(
+        astrs.iter_alternatives().collect::<Vec<_>>(),
+        vec![
+            [MatchByte::Full(0xbb), MatchByte::Full(0xcc)].as_slice(),
+            [MatchByte::Full(0xdd), MatchByte::Full(0xee)].as_slice(),
+        ]
+    );
+
+    // Generic groups follow their stored ranges
+    let bs = BodySig::try_from(b"deadbeef(bb|ccdd)ffff".as_slice()).unwrap();
+    let Pattern::AlternativeStrings { astrs, .. } = &bs.patterns[1] else {
+        panic!("expected alternative-string pattern");
+    };
+    assert_eq!(
+        astrs.iter_alternatives().collect::<Vec<_>>(),
+        vec![
+            [MatchByte::Full(0xbb)].as_slice(),
+            [MatchByte::Full(0xcc), MatchByte::Full(0xdd)].as_slice(),
+        ]
+    );
+}
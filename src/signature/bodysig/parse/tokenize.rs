@@ -0,0 +1,617 @@
+/*
+ *  Copyright (C) 2024 Cisco Systems, Inc. and/or its affiliates. All rights reserved.
+ *
+ *  This program is free software; you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License version 2 as
+ *  published by the Free Software Foundation.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program; if not, write to the Free Software
+ *  Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston,
+ *  MA 02110-1301, USA.
+ */
+
+use super::{
+    BodySigParseError, BraceLimits, Context, ANCHORED_BYTE_RANGE_MAX, ASTERISK, BANG, BRACKET_LEFT,
+    BRACKET_RIGHT, CURLY_LEFT, CURLY_RIGHT, MINUS_SIGN, PAREN_LEFT, PAREN_RIGHT, PIPE,
+    QUESTION_MARK,
+};
+use crate::{
+    sigbytes::SigChar,
+    util::{Position, Range},
+};
+use std::ops::RangeInclusive;
+
+/// A single lexical element of a body signature, along with where it was
+/// found (for error reporting).
+///
+/// Hex digits are reported per-nyble rather than per-byte: whether a nyble
+/// stands alone can't be determined lexically (e.g., the `B` in a `(B)`
+/// character class is indistinguishable from a hex digit until the closing
+/// parenthesis is seen), so pairing nybles into bytes is left to the builder.
+#[derive(Debug, PartialEq)]
+pub(super) enum Token {
+    /// A hex digit contributing one nyble to a match byte.  The original
+    /// character is retained since `B` may later resolve to a character class
+    HexNyble { pos: usize, byte: u8 },
+
+    /// A `?`, matching any value in one nyble of a byte
+    NybleWildcard { pos: usize },
+
+    /// An `L` or `W`, which is only meaningful as a character class within a
+    /// parenthetical expression
+    ClassChar { pos: usize, byte: u8 },
+
+    /// A `*` (unbounded wildcard)
+    Star { pos: usize },
+
+    /// A curly-brace wildcard range (`{n}`, `{n-}`, `{-m}` or `{n-m}`),
+    /// scanned through its closing brace with the bounds parsed
+    BraceRange {
+        start_pos: usize,
+        range: Range<usize>,
+    },
+
+    /// An anchored-byte wildcard range (`[n]`, `[n-]` or `[n-m]`), scanned
+    /// through its closing bracket with the bounds parsed and validated.
+    /// What the range anchors to is the builder's concern
+    BracketRange {
+        bracket_pos: usize,
+        close_pos: usize,
+        range: RangeInclusive<u8>,
+    },
+
+    /// A `(`, opening either a character class or alternative strings
+    ParenOpen { pos: usize },
+
+    /// A `)`
+    ParenClose { pos: usize },
+
+    /// A `|`, separating alternative strings
+    Pipe { pos: usize },
+
+    /// A `!`.  The tokenizer guarantees that a `(` immediately follows
+    Bang { pos: usize },
+
+    /// Any other character.  Whether this is an error (and which) depends on
+    /// the builder's state
+    Unknown { pos: usize, byte: u8 },
+}
+
+impl Token {
+    /// The position of the token's first character within the signature
+    pub(super) fn pos(&self) -> usize {
+        match self {
+            Token::HexNyble { pos, .. }
+            | Token::NybleWildcard { pos }
+            | Token::ClassChar { pos, .. }
+            | Token::Star { pos }
+            | Token::ParenOpen { pos }
+            | Token::ParenClose { pos }
+            | Token::Pipe { pos }
+            | Token::Bang { pos }
+            | Token::Unknown { pos, .. } => *pos,
+            Token::BraceRange { start_pos, .. } => *start_pos,
+            Token::BracketRange { bracket_pos, .. } => *bracket_pos,
+        }
+    }
+
+    /// The character to name when this token is reported in an error
+    pub(super) fn sig_char(&self) -> SigChar {
+        match self {
+            Token::HexNyble { byte, .. }
+            | Token::ClassChar { byte, .. }
+            | Token::Unknown { byte, .. } => (*byte).into(),
+            Token::NybleWildcard { .. } => QUESTION_MARK.into(),
+            Token::Star { .. } => ASTERISK.into(),
+            Token::BraceRange { .. } => CURLY_LEFT.into(),
+            Token::BracketRange { .. } => BRACKET_LEFT.into(),
+            Token::ParenOpen { .. } => PAREN_LEFT.into(),
+            Token::ParenClose { .. } => PAREN_RIGHT.into(),
+            Token::Pipe { .. } => PIPE.into(),
+            Token::Bang { .. } => BANG.into(),
+        }
+    }
+}
+
+/// An iterator producing [`Token`]s (or errors) from raw body signature
+/// bytes.  The iterator fuses after reporting an error.
+pub(super) struct Tokenizer<'a> {
+    input: &'a [u8],
+    pos: usize,
+    brace_limits: BraceLimits,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub(super) fn with_brace_limits(input: &'a [u8], brace_limits: BraceLimits) -> Self {
+        Self {
+            input,
+            pos: 0,
+            brace_limits,
+        }
+    }
+
+    // Verify that a single brace-range bound doesn't exceed the configured
+    // maximum
+    fn check_brace_bound(&self, bound: usize, start_pos: usize) -> Result<(), BodySigParseError> {
+        if bound > self.brace_limits.max_bound {
+            return Err(BodySigParseError::BraceBoundTooLarge {
+                start_pos: start_pos.into(),
+                bound,
+                limit: self.brace_limits.max_bound,
+            });
+        }
+        Ok(())
+    }
+
+    // Scan the remainder of a curly-brace wildcard range.  The opening brace
+    // (at `start_pos`) has already been consumed.
+    fn scan_brace_range(&mut self, start_pos: usize) -> Result<Token, BodySigParseError> {
+        let mut dec_value = None;
+        let mut lower_bound = None;
+        let mut saw_hyphen = false;
+        while let Some(&byte) = self.input.get(self.pos) {
+            let pos = self.pos;
+            self.pos += 1;
+            match byte {
+                b'0'..=b'9' => update_dec_value(&mut dec_value, byte, pos)?,
+                MINUS_SIGN if !saw_hyphen => {
+                    saw_hyphen = true;
+                    lower_bound = dec_value.take();
+                }
+                CURLY_RIGHT => {
+                    let range = if saw_hyphen {
+                        match (lower_bound, dec_value) {
+                            (Some(start), Some(end)) => {
+                                if end < start {
+                                    return Err(BodySigParseError::RangeBoundsInverted {
+                                        start_pos: start_pos.into(),
+                                        start,
+                                        end,
+                                    });
+                                }
+                                self.check_brace_bound(end, start_pos)?;
+                                if start == end {
+                                    // `{n-n}` is an exact length; collapse it
+                                    // so that both spellings of the same
+                                    // constraint export identically
+                                    Range::Exact(start)
+                                } else {
+                                    (start..=end).into()
+                                }
+                            }
+                            // Only lower bound was specified
+                            (Some(start), None) => {
+                                self.check_brace_bound(start, start_pos)?;
+                                (start..).into()
+                            }
+                            // No lower bound was specified
+                            (None, Some(end)) => {
+                                self.check_brace_bound(end, start_pos)?;
+                                (..=end).into()
+                            }
+                            (None, None) => {
+                                return Err(BodySigParseError::NoBraceBounds {
+                                    start_pos: start_pos.into(),
+                                })
+                            }
+                        }
+                    } else if let Some(size) = dec_value {
+                        self.check_brace_bound(size, start_pos)?;
+                        Range::Exact(size)
+                    } else {
+                        return Err(BodySigParseError::EmptyBraces {
+                            start_pos: start_pos.into(),
+                        });
+                    };
+                    // A zero-length exact range (`{0}` or `{0-0}`) matches
+                    // nothing and constrains nothing
+                    if range == Range::Exact(0) {
+                        return Err(BodySigParseError::BraceRangeZero {
+                            start_pos: start_pos.into(),
+                        });
+                    }
+                    return Ok(Token::BraceRange { start_pos, range });
+                }
+                other => {
+                    return Err(BodySigParseError::UnexpectedChar {
+                        context: Context::CurlyBraceRange,
+                        pos: pos.into(),
+                        found: other.into(),
+                    })
+                }
+            }
+        }
+        Err(BodySigParseError::CurlyBraceNotClosed {
+            start_pos: start_pos.into(),
+        })
+    }
+
+    // Scan the remainder of an anchored-byte wildcard range.  The opening
+    // bracket (at `bracket_pos`) has already been consumed.
+    fn scan_bracket_range(&mut self, bracket_pos: usize) -> Result<Token, BodySigParseError> {
+        let mut dec_value = None;
+        let mut lower_bound = None;
+        while let Some(&byte) = self.input.get(self.pos) {
+            let pos = self.pos;
+            self.pos += 1;
+            match byte {
+                b'0'..=b'9' => update_dec_value(&mut dec_value, byte, pos)?,
+                MINUS_SIGN if lower_bound.is_none() => {
+                    let Some(start) = dec_value.take() else {
+                        return Err(BodySigParseError::BracketRangeMissingLowerBound {
+                            start_pos: Position::span(bracket_pos, pos),
+                        });
+                    };
+                    if start > ANCHORED_BYTE_RANGE_MAX {
+                        return Err(BodySigParseError::AnchoredByteInvalidLowerBound {
+                            bracket_pos: Position::span(bracket_pos, pos),
+                            found: start,
+                        });
+                    }
+                    lower_bound = Some(start);
+                }
+                BRACKET_RIGHT => {
+                    let start = if let Some(start) = lower_bound {
+                        start
+                    } else {
+                        let Some(start) = dec_value.take() else {
+                            return Err(BodySigParseError::BracketRangeEmpty {
+                                start_pos: Position::span(bracket_pos, pos),
+                            });
+                        };
+                        if start > ANCHORED_BYTE_RANGE_MAX {
+                            return Err(BodySigParseError::AnchoredByteInvalidLowerBound {
+                                bracket_pos: Position::span(bracket_pos, pos),
+                                found: start,
+                            });
+                        }
+                        start
+                    };
+                    // No upper bound specified (`[n]` or `[n-]`) is apparently OK
+                    let end = dec_value.take().unwrap_or(start);
+                    if !(1..=ANCHORED_BYTE_RANGE_MAX).contains(&end) || end < start {
+                        return Err(BodySigParseError::AnchoredByteInvalidUpperBound {
+                            bracket_pos: Position::span(bracket_pos, pos),
+                            found: end,
+                            lower: start,
+                        });
+                    }
+                    // These are validated above, but don't leave this to chance.
+                    let range = u8::try_from(start).unwrap()..=u8::try_from(end).unwrap();
+                    return Ok(Token::BracketRange {
+                        bracket_pos,
+                        close_pos: pos,
+                        range,
+                    });
+                }
+                other => {
+                    return Err(BodySigParseError::BracketRangeUnexpectedChar {
+                        pos: pos.into(),
+                        found: other.into(),
+                    })
+                }
+            }
+        }
+        Err(BodySigParseError::BracketNotClosed {
+            start_pos: Position::span(bracket_pos, self.pos - 1),
+        })
+    }
+}
+
+impl Iterator for Tokenizer<'_> {
+    type Item = Result<Token, BodySigParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.pos;
+        let &byte = self.input.get(pos)?;
+        self.pos += 1;
+        let token = match byte {
+            b'0'..=b'9' | b'a'..=b'f' | b'A'..=b'F' => Ok(Token::HexNyble { pos, byte }),
+            b'L' | b'W' => Ok(Token::ClassChar { pos, byte }),
+            QUESTION_MARK => Ok(Token::NybleWildcard { pos }),
+            ASTERISK => Ok(Token::Star { pos }),
+            CURLY_LEFT => self.scan_brace_range(pos),
+            BRACKET_LEFT => self.scan_bracket_range(pos),
+            PAREN_LEFT => Ok(Token::ParenOpen { pos }),
+            PAREN_RIGHT => Ok(Token::ParenClose { pos }),
+            PIPE => Ok(Token::Pipe { pos }),
+            BANG => match self.input.get(self.pos) {
+                // Negation may only precede a parenthetical expression; report
+                // this lexically so the builder may assume it
+                Some(&PAREN_LEFT) => Ok(Token::Bang { pos }),
+                Some(&other) => Err(BodySigParseError::NegateUnexpectedChar {
+                    pos: self.pos.into(),
+                    found: other.into(),
+                }),
+                None => Err(BodySigParseError::NegationTargetless),
+            },
+            other => Ok(Token::Unknown { pos, byte: other }),
+        };
+        if token.is_err() {
+            // Fuse the iterator once an error has been reported
+            self.pos = self.input.len();
+        }
+        Some(token)
+    }
+}
+
+// Contribute a digit to an accumulating decimal value
+#[inline]
+fn update_dec_value(
+    dec_value: &mut Option<usize>,
+    byte: u8,
+    pos: usize,
+) -> Result<(), BodySigParseError> {
+    *dec_value = Some(
+        dec_value
+            .unwrap_or_default()
+            .checked_mul(10)
+            .ok_or(BodySigParseError::DecimalOverflow { pos: pos.into() })?
+            .checked_add((byte - b'0') as usize)
+            .ok_or(BodySigParseError::DecimalOverflow { pos: pos.into() })?,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokenize(input: &[u8]) -> Vec<Result<Token, BodySigParseError>> {
+        Tokenizer::with_brace_limits(input, BraceLimits::default()).collect()
+    }
+
+    #[test]
+    fn tokens_with_positions() {
+        assert_eq!(
+            tokenize(b"ab?cd*{4}(aa|bb)!(L)"),
+            vec![
+                Ok(Token::HexNyble { pos: 0, byte: b'a' }),
+                Ok(Token::HexNyble { pos: 1, byte: b'b' }),
+                Ok(Token::NybleWildcard { pos: 2 }),
+                Ok(Token::HexNyble { pos: 3, byte: b'c' }),
+                Ok(Token::HexNyble { pos: 4, byte: b'd' }),
+                Ok(Token::Star { pos: 5 }),
+                Ok(Token::BraceRange {
+                    start_pos: 6,
+                    range: Range::Exact(4)
+                }),
+                Ok(Token::ParenOpen { pos: 9 }),
+                Ok(Token::HexNyble {
+                    pos: 10,
+                    byte: b'a'
+                }),
+                Ok(Token::HexNyble {
+                    pos: 11,
+                    byte: b'a'
+                }),
+                Ok(Token::Pipe { pos: 12 }),
+                Ok(Token::HexNyble {
+                    pos: 13,
+                    byte: b'b'
+                }),
+                Ok(Token::HexNyble {
+                    pos: 14,
+                    byte: b'b'
+                }),
+                Ok(Token::ParenClose { pos: 15 }),
+                Ok(Token::Bang { pos: 16 }),
+                Ok(Token::ParenOpen { pos: 17 }),
+                Ok(Token::ClassChar {
+                    pos: 18,
+                    byte: b'L'
+                }),
+                Ok(Token::ParenClose { pos: 19 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn hex_b_is_a_nyble() {
+        // `B` can't be classified lexically; it may turn out to be the
+        // word-boundary character class.  The builder decides.
+        assert_eq!(
+            tokenize(b"B"),
+            vec![Ok(Token::HexNyble { pos: 0, byte: b'B' })]
+        );
+    }
+
+    #[test]
+    fn brace_range_forms() {
+        assert_eq!(
+            tokenize(b"{5}"),
+            vec![Ok(Token::BraceRange {
+                start_pos: 0,
+                range: Range::Exact(5)
+            })]
+        );
+        assert_eq!(
+            tokenize(b"{5-}"),
+            vec![Ok(Token::BraceRange {
+                start_pos: 0,
+                range: (5..).into()
+            })]
+        );
+        assert_eq!(
+            tokenize(b"{-7}"),
+            vec![Ok(Token::BraceRange {
+                start_pos: 0,
+                range: (..=7).into()
+            })]
+        );
+        assert_eq!(
+            tokenize(b"{3-7}"),
+            vec![Ok(Token::BraceRange {
+                start_pos: 0,
+                range: (3..=7).into()
+            })]
+        );
+    }
+
+    #[test]
+    fn brace_range_errors() {
+        assert_eq!(
+            tokenize(b"{}"),
+            vec![Err(BodySigParseError::EmptyBraces {
+                start_pos: 0.into()
+            })]
+        );
+        assert_eq!(
+            tokenize(b"{-}"),
+            vec![Err(BodySigParseError::NoBraceBounds {
+                start_pos: 0.into()
+            })]
+        );
+        assert_eq!(
+            tokenize(b"{7-3}"),
+            vec![Err(BodySigParseError::RangeBoundsInverted {
+                start_pos: 0.into(),
+                start: 7,
+                end: 3
+            })]
+        );
+        assert_eq!(
+            tokenize(b"{1x}"),
+            vec![Err(BodySigParseError::UnexpectedChar {
+                context: Context::CurlyBraceRange,
+                pos: 2.into(),
+                found: b'x'.into()
+            })]
+        );
+        // A second hyphen isn't part of the range syntax
+        assert_eq!(
+            tokenize(b"{1-2-}"),
+            vec![Err(BodySigParseError::UnexpectedChar {
+                context: Context::CurlyBraceRange,
+                pos: 4.into(),
+                found: b'-'.into()
+            })]
+        );
+        assert_eq!(
+            tokenize(b"{3"),
+            vec![Err(BodySigParseError::CurlyBraceNotClosed {
+                start_pos: 0.into()
+            })]
+        );
+        assert_eq!(
+            tokenize(b"{99999999999999999999999}"),
+            vec![Err(BodySigParseError::DecimalOverflow { pos: 20.into() })]
+        );
+    }
+
+    #[test]
+    fn bracket_range_forms() {
+        assert_eq!(
+            tokenize(b"[5]"),
+            vec![Ok(Token::BracketRange {
+                bracket_pos: 0,
+                close_pos: 2,
+                range: 5..=5
+            })]
+        );
+        assert_eq!(
+            tokenize(b"[5-]"),
+            vec![Ok(Token::BracketRange {
+                bracket_pos: 0,
+                close_pos: 3,
+                range: 5..=5
+            })]
+        );
+        assert_eq!(
+            tokenize(b"[1-3]"),
+            vec![Ok(Token::BracketRange {
+                bracket_pos: 0,
+                close_pos: 4,
+                range: 1..=3
+            })]
+        );
+    }
+
+    #[test]
+    fn bracket_range_errors() {
+        assert_eq!(
+            tokenize(b"[]"),
+            vec![Err(BodySigParseError::BracketRangeEmpty {
+                start_pos: Position::span(0, 1)
+            })]
+        );
+        assert_eq!(
+            tokenize(b"[-1]"),
+            vec![Err(BodySigParseError::BracketRangeMissingLowerBound {
+                start_pos: Position::span(0, 1)
+            })]
+        );
+        assert_eq!(
+            tokenize(b"[50]"),
+            vec![Err(BodySigParseError::AnchoredByteInvalidLowerBound {
+                bracket_pos: Position::span(0, 3),
+                found: 50
+            })]
+        );
+        assert_eq!(
+            tokenize(b"[1-50]"),
+            vec![Err(BodySigParseError::AnchoredByteInvalidUpperBound {
+                bracket_pos: Position::span(0, 5),
+                found: 50,
+                lower: 1
+            })]
+        );
+        assert_eq!(
+            tokenize(b"[3-1]"),
+            vec![Err(BodySigParseError::AnchoredByteInvalidUpperBound {
+                bracket_pos: Position::span(0, 4),
+                found: 1,
+                lower: 3
+            })]
+        );
+        assert_eq!(
+            tokenize(b"[1x]"),
+            vec![Err(BodySigParseError::BracketRangeUnexpectedChar {
+                pos: 2.into(),
+                found: b'x'.into()
+            })]
+        );
+        assert_eq!(
+            tokenize(b"[1"),
+            vec![Err(BodySigParseError::BracketNotClosed {
+                start_pos: Position::span(0, 1)
+            })]
+        );
+    }
+
+    #[test]
+    fn bang_requires_paren() {
+        assert_eq!(
+            tokenize(b"!x"),
+            vec![Err(BodySigParseError::NegateUnexpectedChar {
+                pos: 1.into(),
+                found: b'x'.into()
+            })]
+        );
+        assert_eq!(
+            tokenize(b"!"),
+            vec![Err(BodySigParseError::NegationTargetless)]
+        );
+    }
+
+    #[test]
+    fn unknown_char() {
+        assert_eq!(
+            tokenize(b"z"),
+            vec![Ok(Token::Unknown { pos: 0, byte: b'z' })]
+        );
+    }
+
+    #[test]
+    fn fuses_after_error() {
+        let mut tokenizer = Tokenizer::with_brace_limits(b"{x}00", BraceLimits::default());
+        assert!(matches!(tokenizer.next(), Some(Err(_))));
+        assert!(tokenizer.next().is_none());
+    }
+}
@@ -16,7 +16,7 @@
  *  MA 02110-1301, USA.
  */
 
-use super::{altstr::AlternativeStrings, PatternModifier};
+use super::{altstr::AlternativeStrings, pattern_modifier::BoundaryModifiers, PatternModifier};
 use crate::{
     feature::EngineReq,
     sigbytes::{AppendSigBytes, SigBytes},
@@ -28,13 +28,13 @@ use std::{
     ops::RangeInclusive,
 };
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum ByteAnchorSide {
     Left,
     Right,
 }
 
-#[derive(PartialEq)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum Pattern {
     /// A series of bytes, possible containing fixed-size wildcards. Represented
     /// as `xx`, `x?`, `?x` or `??`, where `x` is a hexadecimal digit, and `?` is
@@ -50,8 +50,14 @@ pub enum Pattern {
     },
 
     /// Alternative strings.  A parenthetical group of one or more strings
-    /// separated with the pipe (`|`) character
-    AlternativeStrings(AlternativeStrings),
+    /// separated with the pipe (`|`) character.  When `negated` (serialized
+    /// with a leading `!`), the group matches any byte sequence of the given
+    /// width *not* in the set; negation is supported only for fixed-width
+    /// groups, which is enforced when the body signature is parsed.
+    AlternativeStrings {
+        negated: bool,
+        astrs: AlternativeStrings,
+    },
 
     /// A range of bytes that are ignored, but anchored to neighboring matches
     /// This is represented in signatures as `*` (for any size); or as `{-n}`,
@@ -62,7 +68,7 @@ pub enum Pattern {
     Wildcard,
 }
 
-#[derive(Clone, Copy, PartialEq, Default)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum MatchByte {
     // A match of the full byte value (e.g., "af")
     Full(u8),
@@ -93,7 +99,7 @@ impl From<u8> for MatchByte {
     }
 }
 
-#[derive(Default, PartialEq)]
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
 pub struct MatchBytes {
     pub bytes: Vec<MatchByte>,
 }
@@ -128,6 +134,30 @@ impl std::ops::Deref for MatchBytes {
     }
 }
 
+impl MatchBytes {
+    /// Iterate over the match bytes by value
+    pub fn iter(&self) -> std::iter::Copied<std::slice::Iter<'_, MatchByte>> {
+        self.bytes.iter().copied()
+    }
+
+    /// A lending iterator over the maximal runs of consecutive
+    /// [`MatchByte::Full`] bytes, each decoded into a buffer that's reused
+    /// from run to run.  See [`StaticRuns::next_run`].
+    #[must_use]
+    pub fn static_runs(&self) -> StaticRuns<'_> {
+        StaticRuns::new(&self.bytes)
+    }
+}
+
+impl<'a> IntoIterator for &'a MatchBytes {
+    type Item = MatchByte;
+    type IntoIter = std::iter::Copied<std::slice::Iter<'a, MatchByte>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 impl std::fmt::Debug for MatchBytes {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "MatchBytes(\"{self}\")")
@@ -143,13 +173,70 @@ impl AppendSigBytes for MatchBytes {
 
 impl std::fmt::Display for MatchBytes {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for byte in self.iter() {
+        for byte in self {
             write!(f, "{byte:?}")?;
         }
         Ok(())
     }
 }
 
+/// A lending iterator over the maximal runs of consecutive
+/// [`MatchByte::Full`] bytes within a match-byte sequence, decoding each run
+/// into an internal buffer that's reused from run to run (so iterating
+/// allocates at most once, however many runs are found)
+pub struct StaticRuns<'a> {
+    bytes: &'a [MatchByte],
+    pos: usize,
+    buf: Vec<u8>,
+}
+
+impl<'a> StaticRuns<'a> {
+    pub(crate) fn new(bytes: &'a [MatchByte]) -> Self {
+        Self {
+            bytes,
+            pos: 0,
+            buf: vec![],
+        }
+    }
+
+    /// The next run of fully-static bytes, as its element offset within the
+    /// sequence and the decoded raw bytes.  The returned slice borrows the
+    /// iterator's internal buffer, and is overwritten by the next call (which
+    /// is why this can't implement [`Iterator`]).
+    pub fn next_run(&mut self) -> Option<(usize, &[u8])> {
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|mb| !matches!(mb, MatchByte::Full(_)))
+        {
+            self.pos += 1;
+        }
+        if self.pos == self.bytes.len() {
+            return None;
+        }
+        let offset = self.pos;
+        self.buf.clear();
+        while let Some(MatchByte::Full(b)) = self.bytes.get(self.pos) {
+            self.buf.push(*b);
+            self.pos += 1;
+        }
+        Some((offset, &self.buf))
+    }
+}
+
+/// Return each maximal run of fully-static bytes within `bytes` that is at
+/// least `min_len` bytes long, decoded to raw bytes
+pub(crate) fn static_runs(bytes: &[MatchByte], min_len: usize) -> Vec<Vec<u8>> {
+    let mut runs = vec![];
+    let mut iter = StaticRuns::new(bytes);
+    while let Some((_, run)) = iter.next_run() {
+        if run.len() >= min_len {
+            runs.push(run.to_vec());
+        }
+    }
+    runs
+}
+
 #[derive(Default)]
 pub enum MatchMask {
     // Match any value
@@ -182,6 +269,17 @@ impl Pattern {
     pub fn is_wildcard(&self) -> bool {
         matches!(self, Pattern::Wildcard | Pattern::ByteRange(..))
     }
+
+    /// The structured per-side view of this pattern's character-class
+    /// modifiers.  Returns `None` for pattern types other than
+    /// [`Pattern::String`], which is the only type they may attach to.
+    #[must_use]
+    pub fn boundary_modifiers(&self) -> Option<BoundaryModifiers> {
+        match self {
+            Pattern::String(_, pmod) => Some((*pmod).into()),
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Debug for Pattern {
@@ -209,7 +307,13 @@ impl std::fmt::Debug for Pattern {
                 .field("string", string)
                 .finish(),
             Self::ByteRange(arg0) => f.debug_tuple("Range").field(arg0).finish(),
-            Self::AlternativeStrings(arg0) => f.debug_tuple("AltStrs").field(arg0).finish(),
+            Self::AlternativeStrings { negated, astrs } => {
+                let mut tfmt = f.debug_tuple("AltStrs");
+                if *negated {
+                    tfmt.field(negated);
+                }
+                tfmt.field(astrs).finish()
+            }
         }
     }
 }
@@ -218,10 +322,10 @@ impl AppendSigBytes for Pattern {
     fn append_sigbytes(&self, sb: &mut SigBytes) -> Result<(), crate::signature::ToSigBytesError> {
         match self {
             Pattern::String(s, pmod) => {
-                for pm in PatternModifier::left_flags().intersection_c(*pmod) {
-                    pm.append_sigbytes(sb)?;
-                }
+                let modifiers = BoundaryModifiers::from(*pmod);
+                modifiers.append_left(sb)?;
                 s.append_sigbytes(sb)?;
+                modifiers.append_right(sb)?;
             }
             Pattern::Wildcard => sb.write_char('*')?,
             Pattern::AnchoredByte {
@@ -242,45 +346,27 @@ impl AppendSigBytes for Pattern {
                 range.append_sigbytes(sb)?;
                 sb.write_char('}')?;
             }
-            Pattern::AlternativeStrings(astrs) => match astrs {
-                AlternativeStrings::FixedWidth {
-                    negated,
-                    width,
-                    data,
-                } => {
-                    if *negated {
-                        sb.write_char('!')?;
-                    }
-                    sb.write_char('(')?;
-                    for (pos, bytes) in data.chunks(*width).enumerate() {
-                        if pos > 0 {
-                            sb.write_char('|')?;
-                        }
-                        for byte in bytes {
-                            write!(sb, "{byte:?}")?;
-                        }
-                    }
-                    sb.write_char(')')?;
+            Pattern::AlternativeStrings { negated, astrs } => {
+                if *negated {
+                    sb.write_char('!')?;
                 }
-                AlternativeStrings::Generic { ranges, data } => {
-                    sb.write_char('(')?;
-                    for (pos, range) in ranges.iter().enumerate() {
-                        if pos > 0 {
-                            sb.write_char('|')?;
-                        }
-                        for byte in data.get(range.clone()).unwrap() {
-                            write!(sb, "{byte:?}")?;
-                        }
+                sb.write_char('(')?;
+                for (pos, bytes) in astrs.iter_alternatives().enumerate() {
+                    if pos > 0 {
+                        sb.write_char('|')?;
+                    }
+                    for byte in bytes {
+                        write!(sb, "{byte:?}")?;
                     }
-                    sb.write_char(')')?;
                 }
-            },
+                sb.write_char(')')?;
+            }
         }
         Ok(())
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum AnyBytes {
     Infinite,
     Range(RangeInclusive<usize>),
@@ -20,14 +20,17 @@ use std::fmt::Write;
 
 use enumflags2::{bitflags, make_bitflags, BitFlags};
 
-use crate::sigbytes::{AppendSigBytes, SigBytes};
+use crate::{
+    sigbytes::{AppendSigBytes, SigBytes},
+    signature::bodysig::char_class::CharacterClass,
+};
 
 /// Character classes, as they attach to strings. Combined with a negation flag,
 /// they can contribute to a PatternModifier.  This maps directly to the way
 /// ClamAV associates these with byte patterns.
 #[bitflags]
 #[repr(u32)]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum PatternModifier {
     BoundaryLeft = 0x0001,
     BoundaryLeftNegative = 0x0002,
@@ -56,6 +59,14 @@ impl PatternModifier {
         make_bitflags!(PatternModifier::{ BoundaryRight | BoundaryRightNegative | LineMarkerRight |LineMarkerRightNegative | WordMarkerRight | WordMarkerRightNegative})
     }
 
+    /// Return a mask containing all `(W)` (word marker) pattern modifiers
+    #[must_use]
+    pub const fn word_marker_flags() -> BitFlags<PatternModifier> {
+        make_bitflags!(PatternModifier::{
+         WordMarkerLeft | WordMarkerLeftNegative | WordMarkerRight | WordMarkerRightNegative
+        })
+    }
+
     /// Return a mask containing all negated pattern modifiers
     #[must_use]
     pub const fn negative_flags() -> BitFlags<PatternModifier> {
@@ -66,6 +77,104 @@ impl PatternModifier {
     }
 }
 
+/// A structured view of the character-class modifiers attached to a
+/// [`Pattern::String`](crate::signature::bodysig::Pattern::String), with each
+/// side of the string attributed explicitly.  The compact
+/// [`BitFlags<PatternModifier>`] form remains the stored representation; this
+/// view exists for consumers (including the serializer) for which reasoning
+/// about individual side/negation bits is error-prone.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BoundaryModifiers {
+    /// The character class anchored to the left of the string, if any
+    pub left: Option<CharacterClass>,
+    /// The character class anchored to the right of the string, if any
+    pub right: Option<CharacterClass>,
+    /// Whether the left-side class is negated (serialized with a leading `!`)
+    pub left_negated: bool,
+    /// Whether the right-side class is negated
+    pub right_negated: bool,
+}
+
+impl From<BitFlags<PatternModifier>> for BoundaryModifiers {
+    fn from(flags: BitFlags<PatternModifier>) -> Self {
+        use self::CharacterClass::{LineOrFileBoundary, NonAlphaChar, WordBoundary};
+        use self::PatternModifier::{
+            BoundaryLeft, BoundaryLeftNegative, BoundaryRight, BoundaryRightNegative,
+            LineMarkerLeft, LineMarkerLeftNegative, LineMarkerRight, LineMarkerRightNegative,
+            WordMarkerLeft, WordMarkerLeftNegative, WordMarkerRight, WordMarkerRightNegative,
+        };
+
+        // The parser enforces at most one class per side, so each side is
+        // written at most once here
+        let mut bm = BoundaryModifiers::default();
+        for flag in flags {
+            let (class, is_left_side, negated) = match flag {
+                BoundaryLeft => (WordBoundary, true, false),
+                BoundaryLeftNegative => (WordBoundary, true, true),
+                BoundaryRight => (WordBoundary, false, false),
+                BoundaryRightNegative => (WordBoundary, false, true),
+                LineMarkerLeft => (LineOrFileBoundary, true, false),
+                LineMarkerLeftNegative => (LineOrFileBoundary, true, true),
+                LineMarkerRight => (LineOrFileBoundary, false, false),
+                LineMarkerRightNegative => (LineOrFileBoundary, false, true),
+                WordMarkerLeft => (NonAlphaChar, true, false),
+                WordMarkerLeftNegative => (NonAlphaChar, true, true),
+                WordMarkerRight => (NonAlphaChar, false, false),
+                WordMarkerRightNegative => (NonAlphaChar, false, true),
+            };
+            if is_left_side {
+                bm.left = Some(class);
+                bm.left_negated = negated;
+            } else {
+                bm.right = Some(class);
+                bm.right_negated = negated;
+            }
+        }
+        bm
+    }
+}
+
+impl From<BoundaryModifiers> for BitFlags<PatternModifier> {
+    fn from(bm: BoundaryModifiers) -> Self {
+        let mut flags = BitFlags::empty();
+        if let Some(class) = bm.left {
+            flags |= class.pattern_modifier(true, bm.left_negated);
+        }
+        if let Some(class) = bm.right {
+            flags |= class.pattern_modifier(false, bm.right_negated);
+        }
+        flags
+    }
+}
+
+impl BoundaryModifiers {
+    /// Append the left-side class (if any), with its negation prefix, in the
+    /// position preceding the string
+    pub fn append_left(&self, sb: &mut SigBytes) -> Result<(), crate::signature::ToSigBytesError> {
+        Self::append_side(self.left, self.left_negated, sb)
+    }
+
+    /// Append the right-side class (if any), with its negation prefix, in the
+    /// position following the string
+    pub fn append_right(&self, sb: &mut SigBytes) -> Result<(), crate::signature::ToSigBytesError> {
+        Self::append_side(self.right, self.right_negated, sb)
+    }
+
+    fn append_side(
+        class: Option<CharacterClass>,
+        negated: bool,
+        sb: &mut SigBytes,
+    ) -> Result<(), crate::signature::ToSigBytesError> {
+        if let Some(class) = class {
+            if negated {
+                sb.write_char('!')?;
+            }
+            class.append_sigbytes(sb)?;
+        }
+        Ok(())
+    }
+}
+
 impl AppendSigBytes for PatternModifier {
     fn append_sigbytes(&self, sb: &mut SigBytes) -> Result<(), crate::signature::ToSigBytesError> {
         if PatternModifier::negative_flags().contains(*self) {
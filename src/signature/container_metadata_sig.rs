@@ -22,13 +22,13 @@ mod container_type;
 use crate::{
     feature::{EngineReq, Set},
     regexp::Match,
-    sigbytes::{AppendSigBytes, FromSigBytes},
-    signature::{FromSigBytesParseError, SigMeta, Signature},
+    sigbytes::{AppendSigBytes, FromSigBytes, SigBytes},
+    signature::{FromSigBytesParseError, SigMeta, Signature, TrailingFieldMode},
     util::{
         parse_bool_from_int, parse_field, parse_number_dec, unescaped_element,
         ParseBoolFromIntError, ParseNumberError, Range, RangeParseError,
     },
-    Feature,
+    Feature, SigType,
 };
 use container_size::{parse, ContainerSize};
 use container_type::ContainerType;
@@ -47,6 +47,18 @@ pub struct ContainerMetadataSig {
     is_encrypted: Option<bool>,
     file_pos: Option<usize>,
     res1: Option<u32>,
+    /// Fields beyond those the format defines, retained when parsing with
+    /// [`TrailingFieldMode::Preserve`] so that export doesn't lose them
+    trailing: Vec<SigBytes>,
+}
+
+impl ContainerMetadataSig {
+    /// Fields beyond those the format defines, as retained by
+    /// [`TrailingFieldMode::Preserve`]
+    #[must_use]
+    pub fn trailing_fields(&self) -> &[SigBytes] {
+        &self.trailing
+    }
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -67,13 +79,13 @@ pub enum ParseError {
     MissingFilenameRegexp,
 
     #[error("FileNameREGEX not unicode: {0}")]
-    FilenameRegexp(crate::regexp::ParseError),
+    FilenameRegexp(#[source] crate::regexp::ParseError),
 
     #[error("missing FileSizeInContainer field")]
     MissingFSIC,
 
     #[error("invalid FileSizeInContainer field: {0}")]
-    InvalidFSIC(RangeParseError<usize>),
+    InvalidFSIC(#[source] RangeParseError<usize>),
 
     #[error("invalid FileSizeInContainer field: only exact or inclusive ranges allowed")]
     FSICRangeType,
@@ -82,7 +94,7 @@ pub enum ParseError {
     MissingFSReal,
 
     #[error("invalid FileSizeReal field: {0}")]
-    InvalidFSReal(RangeParseError<usize>),
+    InvalidFSReal(#[source] RangeParseError<usize>),
 
     #[error("invalid FileSizeReal field: only exact or inclusive ranges allowed")]
     FSRealRangeType,
@@ -91,50 +103,56 @@ pub enum ParseError {
     MissingIsEnc,
 
     #[error("invalid IsEncrypted field: {0}")]
-    InvalidIsEnc(ParseBoolFromIntError),
+    InvalidIsEnc(#[source] ParseBoolFromIntError),
 
     #[error("missing FilePos field")]
     MissingFilePos,
 
     #[error("invalid FilePos field: {0}")]
-    InvalidFilePos(ParseNumberError<usize>),
+    InvalidFilePos(#[source] ParseNumberError<usize>),
 
     #[error("missing Res1 field")]
     MissingRes1,
 
     #[error("invalid Res1 field: {0}")]
-    InvalidRes1(ParseNumberError<u32>),
+    InvalidRes1(#[source] ParseNumberError<u32>),
 
     #[error("missing Res2 field")]
     MissingRes2,
 
     #[error("invalid Res2 field: {0}")]
-    InvalidRes2(ParseNumberError<isize>),
+    InvalidRes2(#[source] ParseNumberError<isize>),
 
     #[error("Parsing min_flevel: {0}")]
-    ParseMinFlevel(ParseNumberError<u32>),
+    ParseMinFlevel(#[source] ParseNumberError<u32>),
 
     #[error("Parsing max_flevel: {0}")]
-    ParseMaxFlevel(ParseNumberError<u32>),
+    ParseMaxFlevel(#[source] ParseNumberError<u32>),
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
 pub enum ValidationError {}
 
-impl FromSigBytes for ContainerMetadataSig {
+impl ContainerMetadataSig {
+    /// As [`from_sigbytes`](FromSigBytes::from_sigbytes), but with fields
+    /// beyond the optional min/max flevel handled per `trailing_mode` rather
+    /// than always rejected
     #[allow(clippy::too_many_lines)]
-    fn from_sigbytes<'a, SB: Into<&'a crate::sigbytes::SigBytes>>(
+    pub fn from_sigbytes_with_mode<'a, SB: Into<&'a crate::sigbytes::SigBytes>>(
         sb: SB,
+        trailing_mode: TrailingFieldMode,
     ) -> Result<(Box<dyn Signature>, super::SigMeta), FromSigBytesParseError> {
         let mut sigmeta = SigMeta::default();
 
         // Split on colons, but taking care to ignore escaped ones in case the regexp contains some
-        let mut fields = sb.into().as_bytes().split(unescaped_element(b'\\', b':'));
+        let sb = sb.into();
+        let total_fields = sb.as_bytes().split(unescaped_element(b'\\', b':')).count();
+        let mut fields = sb.as_bytes().split(unescaped_element(b'\\', b':'));
 
         // Field 1
-        let name = str::from_utf8(fields.next().ok_or(FromSigBytesParseError::MissingName)?)
-            .map_err(FromSigBytesParseError::NameNotUnicode)?
-            .to_owned();
+        let name =
+            super::parse_sig_name(fields.next().ok_or(FromSigBytesParseError::MissingName)?)?
+                .to_owned();
 
         // Field 2
         let container_type = parse_field!(
@@ -222,22 +240,29 @@ impl FromSigBytes for ContainerMetadataSig {
             ParseError::InvalidRes1
         )?;
 
-        // Parse optional min/max flevel
-        if let Some(min_flevel) = fields.next() {
-            if !min_flevel.is_empty() {
-                let min_flevel =
-                    parse_number_dec(min_flevel).map_err(ParseError::ParseMinFlevel)?;
-
-                if let Some(max_flevel) = fields.next() {
-                    let max_flevel =
-                        parse_number_dec(max_flevel).map_err(ParseError::ParseMaxFlevel)?;
-                    sigmeta.f_level = Some((min_flevel..=max_flevel).into());
-                } else {
-                    sigmeta.f_level = Some((min_flevel..).into());
-                }
+        // Parse optional min/max flevel, which may be absent entirely
+        let min_flevel = parse_field!(
+            TRAILING
+            fields,
+            parse_number_dec::<u32>,
+            ParseError::ParseMinFlevel
+        )?;
+        let max_flevel = parse_field!(
+            TRAILING
+            fields,
+            parse_number_dec::<u32>,
+            ParseError::ParseMaxFlevel
+        )?;
+        match (min_flevel, max_flevel) {
+            (Some(min_flevel), Some(max_flevel)) => {
+                sigmeta.f_level = Some((min_flevel..=max_flevel).into());
             }
+            (Some(min_flevel), None) => sigmeta.f_level = Some((min_flevel..).into()),
+            (None, _) => (),
         }
 
+        let trailing = super::take_trailing_fields(fields, total_fields, trailing_mode)?;
+
         Ok((
             Box::new(Self {
                 name,
@@ -249,16 +274,63 @@ impl FromSigBytes for ContainerMetadataSig {
                 is_encrypted,
                 file_pos,
                 res1,
+                trailing,
             }),
             sigmeta,
         ))
     }
 }
 
+impl FromSigBytes for ContainerMetadataSig {
+    fn from_sigbytes<'a, SB: Into<&'a crate::sigbytes::SigBytes>>(
+        sb: SB,
+    ) -> Result<(Box<dyn Signature>, super::SigMeta), FromSigBytesParseError> {
+        Self::from_sigbytes_with_mode(sb, TrailingFieldMode::Error)
+    }
+}
+
+#[cfg(feature = "regexp-eval")]
+impl ContainerMetadataSig {
+    /// Test whether the given filename would match this signature's
+    /// `FilenameREGEX` field.  Returns `None` if the signature specifies no
+    /// filename regexp, or if the regexp can't be evaluated (e.g., it
+    /// contains non-unicode content).
+    #[must_use]
+    pub fn matches_filename(&self, filename: &str) -> Option<bool> {
+        let regexp = self.filename_regexp.as_ref()?;
+        let pattern = str::from_utf8(&regexp.raw).ok()?;
+        let regexp = regex::Regex::new(pattern).ok()?;
+        Some(regexp.is_match(filename))
+    }
+}
+
 impl Signature for ContainerMetadataSig {
     fn name(&self) -> &str {
         &self.name
     }
+
+    fn sig_type(&self) -> SigType {
+        SigType::ContainerMetadata
+    }
+
+    fn literal_strings(&self) -> Vec<Vec<u8>> {
+        // The only literal content in this signature type is the leading
+        // portion of the filename regexp (up to the first metacharacter)
+        let Some(regexp) = &self.filename_regexp else {
+            return vec![];
+        };
+        let prefix: Vec<u8> = regexp
+            .raw
+            .iter()
+            .copied()
+            .take_while(|b| !br".^$*+?()[]{}|\".contains(b))
+            .collect();
+        if prefix.is_empty() {
+            vec![]
+        } else {
+            vec![prefix]
+        }
+    }
 }
 
 impl EngineReq for ContainerMetadataSig {
@@ -275,39 +347,19 @@ impl AppendSigBytes for ContainerMetadataSig {
         sb.write_str(&self.name)?;
         sb.write_char(':')?;
 
-        if let Some(container_type) = &self.container_type {
-            container_type.append_sigbytes(sb)?;
-        } else {
-            sb.write_char('*')?;
-        }
+        self.container_type.append_sigbytes(sb)?;
         sb.write_char(':')?;
 
-        if let Some(container_size) = &self.container_size {
-            container_size.append_sigbytes(sb)?;
-        } else {
-            sb.write_char('*')?;
-        }
+        self.container_size.append_sigbytes(sb)?;
         sb.write_char(':')?;
 
-        if let Some(filename_regexp) = &self.filename_regexp {
-            filename_regexp.append_sigbytes(sb)?;
-        } else {
-            sb.write_char('*')?;
-        }
+        self.filename_regexp.append_sigbytes(sb)?;
         sb.write_char(':')?;
 
-        if let Some(file_size_in_container) = &self.file_size_in_container {
-            file_size_in_container.append_sigbytes(sb)?;
-        } else {
-            sb.write_char('*')?;
-        }
+        self.file_size_in_container.append_sigbytes(sb)?;
         sb.write_char(':')?;
 
-        if let Some(file_size_real) = &self.file_size_real {
-            file_size_real.append_sigbytes(sb)?;
-        } else {
-            sb.write_char('*')?;
-        }
+        self.file_size_real.append_sigbytes(sb)?;
         sb.write_char(':')?;
 
         sb.write_char(if let Some(is_encrypted) = self.is_encrypted {
@@ -340,6 +392,14 @@ impl AppendSigBytes for ContainerMetadataSig {
         // are appended.
         sb.write_char(':')?;
 
+        // Trailing fields continue from the (empty) Res2 slot's separator
+        for (i, t) in self.trailing.iter().enumerate() {
+            if i > 0 {
+                sb.write_char(':')?;
+            }
+            write!(sb, "{t}")?;
+        }
+
         Ok(())
     }
 }
@@ -368,6 +428,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn nine_field_sig_without_flevel() {
+        // Trailing flevel fields (and the empty Res2 separator) entirely absent
+        let bytes = SigBytes::from(
+            br"Email.Trojan.Toa-1:CL_TYPE_ZIP:1337:Courrt.{1,15}\.scr$:220-221:2008:0:2010:*",
+        );
+        let (sig, meta) = ContainerMetadataSig::from_sigbytes(&bytes).unwrap();
+        assert_eq!(sig.name(), "Email.Trojan.Toa-1");
+        assert_eq!(meta, SigMeta::default());
+    }
+
+    #[test]
+    fn eleven_field_sig_with_flevel() {
+        let (_, meta) = ContainerMetadataSig::from_sigbytes(&SAMPLE_SIG.into()).unwrap();
+        assert_eq!(
+            meta,
+            SigMeta {
+                f_level: Some((99..=101).into()),
+            }
+        );
+    }
+
     #[test]
     fn bad_filename_regex() {
         // This signature has an 8-bit ASCII '¢' sign in the regexp
@@ -386,6 +468,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn bad_name_reports_position_and_lossy_name() {
+        // This signature has an 8-bit ASCII '¢' sign in the *name*
+        let bytes = SigBytes::from(
+            b"Sane\xa2curity.Foxhole:CL_TYPE_ZIP:1337:Courrt.{1,15}\\.scr$:220-221:2008:0:2010:*"
+                .as_slice(),
+        );
+        let err = ContainerMetadataSig::from_sigbytes(&bytes).unwrap_err();
+        if let FromSigBytesParseError::NameNotUnicode { valid_up_to, .. } = &err {
+            assert_eq!(*valid_up_to, 4);
+        } else {
+            panic!("expected NameNotUnicode, got {err:?}");
+        }
+        assert_eq!(err.lossy_name().unwrap(), r"Sane\xa2curity.Foxhole");
+    }
+
     #[test]
     fn export() {
         let input = SAMPLE_SIG_WITHOUT_FLEVEL.into();
@@ -393,4 +491,118 @@ mod tests {
         let exported = sig.to_sigbytes().unwrap();
         assert_eq!(&input, &exported);
     }
+
+    #[test]
+    fn container_type_legal_container() {
+        let (sig, _) = ContainerMetadataSig::from_sigbytes(&SAMPLE_SIG.into()).unwrap();
+        let sig = sig.downcast_ref::<ContainerMetadataSig>().unwrap();
+        assert_eq!(
+            sig.container_type,
+            Some(container_type::ContainerType(
+                crate::filetype::FileType::CL_TYPE_ZIP
+            ))
+        );
+    }
+
+    #[test]
+    fn container_type_must_be_container() {
+        // CL_TYPE_GRAPHICS is a known file type, but not a container
+        let bytes = SigBytes::from(
+            br"Email.Trojan.Toa-1:CL_TYPE_GRAPHICS:1337:Courrt.{1,15}\.scr$:220-221:2008:0:2010:*",
+        );
+        assert_eq!(
+            ContainerMetadataSig::from_sigbytes(&bytes).unwrap_err(),
+            ParseError::ContainerType(container_type::ParseError::NotContainer(
+                crate::filetype::FileType::CL_TYPE_GRAPHICS
+            ))
+            .into()
+        );
+    }
+
+    #[test]
+    fn container_type_wildcard() {
+        let bytes =
+            SigBytes::from(br"Email.Trojan.Toa-1:*:1337:Courrt.{1,15}\.scr$:220-221:2008:0:2010:*");
+        let (sig, _) = ContainerMetadataSig::from_sigbytes(&bytes).unwrap();
+        let sig = sig.downcast_ref::<ContainerMetadataSig>().unwrap();
+        assert_eq!(sig.container_type, None);
+    }
+
+    #[test]
+    fn optional_field_serializes_as_star() {
+        let mut sb = SigBytes::new();
+        None::<ContainerType>.append_sigbytes(&mut sb).unwrap();
+        assert_eq!(sb.to_string(), "*");
+    }
+
+    #[cfg(feature = "regexp-eval")]
+    #[test]
+    fn matches_filename() {
+        let (sig, _) = ContainerMetadataSig::from_sigbytes(&SAMPLE_SIG.into()).unwrap();
+        let sig = sig.downcast_ref::<ContainerMetadataSig>().unwrap();
+        // The sample sig's pattern requires a `.scr` extension
+        assert_eq!(sig.matches_filename("Courrt-invoice.scr"), Some(true));
+        assert_eq!(sig.matches_filename("Courrt.exe"), Some(false));
+    }
+
+    #[cfg(feature = "regexp-eval")]
+    #[test]
+    fn matches_filename_without_regexp() {
+        let bytes = SigBytes::from(br"Email.Trojan.Toa-1:CL_TYPE_ZIP:1337:*:220-221:2008:0:2010:*");
+        let (sig, _) = ContainerMetadataSig::from_sigbytes(&bytes).unwrap();
+        let sig = sig.downcast_ref::<ContainerMetadataSig>().unwrap();
+        assert_eq!(sig.matches_filename("anything.scr"), None);
+    }
+
+    #[test]
+    fn trailing_fields_rejected_by_default() {
+        let with_one = [SAMPLE_SIG, b":junk"].concat();
+        let result = ContainerMetadataSig::from_sigbytes(&with_one.into());
+        assert!(matches!(
+            result,
+            Err(FromSigBytesParseError::TrailingFields {
+                count: 1,
+                first_pos: 11
+            })
+        ));
+
+        let with_three = [SAMPLE_SIG, b":one:two:three"].concat();
+        let result = ContainerMetadataSig::from_sigbytes(&with_three.into());
+        assert!(matches!(
+            result,
+            Err(FromSigBytesParseError::TrailingFields {
+                count: 3,
+                first_pos: 11
+            })
+        ));
+    }
+
+    #[test]
+    fn trailing_fields_preserved_when_permitted() {
+        for extra in [&b":junk"[..], &b":one:two:three"[..]] {
+            let input = [SAMPLE_SIG, extra].concat();
+            let (sig, meta) = ContainerMetadataSig::from_sigbytes_with_mode(
+                &input.into(),
+                TrailingFieldMode::Preserve,
+            )
+            .unwrap();
+            // The flevel fields are still consumed as metadata; only fields
+            // beyond them are trailing
+            assert_eq!(
+                meta,
+                SigMeta {
+                    f_level: Some((99..=101).into()),
+                }
+            );
+            let csig = sig.downcast_ref::<ContainerMetadataSig>().unwrap();
+            assert_eq!(
+                csig.trailing_fields().len(),
+                extra.split(|&b| b == b':').count() - 1
+            );
+            // Export drops the flevel fields (those live in the metadata) but
+            // carries the trailing fields through
+            let expected = [SAMPLE_SIG_WITHOUT_FLEVEL, &extra[1..]].concat();
+            assert_eq!(sig.to_sigbytes().unwrap().as_bytes(), expected);
+        }
+    }
 }
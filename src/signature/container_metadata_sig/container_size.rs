@@ -29,7 +29,7 @@ pub enum ContainerSize {
     Range(RangeInclusive<usize>),
 }
 
-#[derive(Debug, thiserror::Error, PartialEq)]
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Hash)]
 pub enum ParseError {
     #[error("parsing range: {0}")]
     ParseRange(#[from] RangeInclusiveParseError<usize>),
@@ -0,0 +1,411 @@
+/*
+ *  Copyright (C) 2024 Cisco Systems, Inc. and/or its affiliates. All rights reserved.
+ *
+ *  This program is free software; you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License version 2 as
+ *  published by the Free Software Foundation.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program; if not, write to the Free Software
+ *  Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston,
+ *  MA 02110-1301, USA.
+ */
+
+use crate::{
+    signature::{parse_from_cvd_with_meta, sigset::SigSet, FromSigBytesParseError, SigMeta},
+    SigType, Signature,
+};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+use thiserror::Error;
+
+#[cfg(feature = "async")]
+pub mod async_load;
+
+/// The set of signatures obtained by loading an entire database file
+pub type SignatureCollection = SigSet;
+
+/// A single parsed signature together with its metadata, as produced for each
+/// database line
+type ParsedSignature = (Box<dyn Signature>, SigMeta);
+
+/// Errors that can be encountered while loading a signature database file
+#[derive(Debug, Error)]
+pub enum LoadError {
+    /// The file has no extension, or its extension doesn't map to a known
+    /// signature type
+    #[error("file extension does not map to a known signature type")]
+    UnknownExtension,
+
+    #[error("reading database: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A signature failed to parse.  The first element is the line number
+    /// (1-based) at which the failure occurred.
+    #[error("parsing line {0}: {1}")]
+    ParseError(usize, #[source] FromSigBytesParseError),
+
+    /// The operation was cancelled via a [`ProgressSink`].  The signatures
+    /// parsed before cancellation are retained so that callers may still
+    /// examine the partial result.
+    #[error("cancelled at line {line_no}")]
+    Cancelled {
+        line_no: usize,
+        // Boxed to keep the error (and hence every load result) small
+        partial: Box<SignatureCollection>,
+    },
+}
+
+/// A receiver for progress reports from long-running database operations,
+/// which may additionally request cancellation.  Sinks must be `Send + Sync`
+/// so that a single sink can be shared by operations that process multiple
+/// files in parallel.
+pub trait ProgressSink: Send + Sync {
+    /// Report that `done` units (lines or files) out of `total` have been
+    /// processed.  `total` is `None` when the overall size isn't known.
+    fn on_progress(&self, done: usize, total: Option<usize>);
+
+    /// Poll whether the operation should be abandoned.  This is consulted at
+    /// the same granularity as [`ProgressSink::on_progress`]; returning `true`
+    /// causes the operation to stop early with [`LoadError::Cancelled`].
+    fn should_cancel(&self) -> bool {
+        false
+    }
+}
+
+/// Strip the line terminator from a raw database line and parse it as the
+/// specified signature type.  Returns `Ok(None)` for empty lines and comment
+/// lines (beginning with `#`), which database loaders skip.
+///
+/// `line_no` (1-based) is recorded in any resulting [`LoadError::ParseError`].
+fn parse_db_line(
+    sig_type: SigType,
+    line_no: usize,
+    raw: &[u8],
+) -> Result<Option<ParsedSignature>, LoadError> {
+    let line = raw
+        .strip_suffix(b"\r\n")
+        .or_else(|| raw.strip_suffix(b"\n"))
+        .unwrap_or(raw);
+    if line.is_empty() || line.starts_with(b"#") {
+        return Ok(None);
+    }
+    parse_from_cvd_with_meta(sig_type, &line.into())
+        .map(Some)
+        .map_err(|e| LoadError::ParseError(line_no, e))
+}
+
+/// Load a complete signature database file, determining the signature type from
+/// the file extension.  Comment lines (beginning with `#`) and empty lines are
+/// skipped.
+///
+/// # Arguments
+///
+/// * `path` - location of the database file to load
+///
+/// # Errors
+///
+/// Returns [`LoadError::UnknownExtension`] if the file extension doesn't map to
+/// a known signature type, [`LoadError::Io`] if the file can't be read, or
+/// [`LoadError::ParseError`] (with the offending line number) if any signature
+/// fails to parse.
+pub fn load(path: &Path) -> Result<SignatureCollection, LoadError> {
+    let sig_type = SigType::from_file_path(path).ok_or(LoadError::UnknownExtension)?;
+    load_with_sig_type(path, sig_type)
+}
+
+/// Load a complete signature database file as the specified signature type,
+/// without consulting the file extension.
+///
+/// # Errors
+///
+/// Returns [`LoadError::Io`] if the file can't be read, or
+/// [`LoadError::ParseError`] (with the offending line number) if any signature
+/// fails to parse.
+pub fn load_with_sig_type(
+    path: &Path,
+    sig_type: SigType,
+) -> Result<SignatureCollection, LoadError> {
+    let mut sigs = SignatureCollection::new();
+    let mut fh = BufReader::new(File::open(path)?);
+    let mut sigbuf = vec![];
+    let mut line_no = 0;
+
+    loop {
+        sigbuf.clear();
+        if fh.read_until(b'\n', &mut sigbuf)? == 0 {
+            break;
+        }
+        line_no += 1;
+        if let Some((sig, _)) = parse_db_line(sig_type, line_no, &sigbuf)? {
+            sigs.push(sig);
+        }
+    }
+
+    Ok(sigs)
+}
+
+/// How many lines are parsed between progress callbacks in
+/// [`parse_database_file_with_progress`]
+const PROGRESS_INTERVAL: usize = 256;
+
+/// Load a complete signature database file as the specified signature type,
+/// reporting progress periodically via the provided callback.  The file is
+/// first scanned to determine its total line count, after which
+/// `on_progress(current_line, total_lines)` is invoked every
+/// [`PROGRESS_INTERVAL`] lines and once after the final line.
+///
+/// # Errors
+///
+/// Returns [`LoadError::Io`] if the file can't be read, or
+/// [`LoadError::ParseError`] (with the offending line number) if any signature
+/// fails to parse.
+pub fn parse_database_file_with_progress(
+    path: &Path,
+    sig_type: SigType,
+    on_progress: impl Fn(usize, usize),
+) -> Result<SignatureCollection, LoadError> {
+    let total_lines = count_lines(path)?;
+
+    let mut sigs = SignatureCollection::new();
+    let mut fh = BufReader::new(File::open(path)?);
+    let mut sigbuf = vec![];
+    let mut line_no = 0;
+
+    loop {
+        sigbuf.clear();
+        if fh.read_until(b'\n', &mut sigbuf)? == 0 {
+            break;
+        }
+        line_no += 1;
+        if line_no % PROGRESS_INTERVAL == 0 {
+            on_progress(line_no, total_lines);
+        }
+        if let Some((sig, _)) = parse_db_line(sig_type, line_no, &sigbuf)? {
+            sigs.push(sig);
+        }
+    }
+    on_progress(line_no, total_lines);
+
+    Ok(sigs)
+}
+
+/// Load a complete signature database file as the specified signature type,
+/// reporting per-line progress to the provided [`ProgressSink`].  The sink is
+/// consulted for cancellation at the same per-line granularity.
+///
+/// # Errors
+///
+/// As with [`load_with_sig_type`], plus [`LoadError::Cancelled`] (carrying the
+/// signatures parsed so far) if the sink requests cancellation.
+pub fn load_with_progress_sink(
+    path: &Path,
+    sig_type: SigType,
+    sink: &dyn ProgressSink,
+) -> Result<SignatureCollection, LoadError> {
+    let total_lines = count_lines(path)?;
+
+    let mut sigs = SignatureCollection::new();
+    let mut fh = BufReader::new(File::open(path)?);
+    let mut sigbuf = vec![];
+    let mut line_no = 0;
+
+    loop {
+        sigbuf.clear();
+        if fh.read_until(b'\n', &mut sigbuf)? == 0 {
+            break;
+        }
+        line_no += 1;
+        sink.on_progress(line_no, Some(total_lines));
+        if sink.should_cancel() {
+            return Err(LoadError::Cancelled {
+                line_no,
+                partial: Box::new(sigs),
+            });
+        }
+        if let Some((sig, _)) = parse_db_line(sig_type, line_no, &sigbuf)? {
+            sigs.push(sig);
+        }
+    }
+
+    Ok(sigs)
+}
+
+/// Count the number of lines in a file, counting a trailing line that lacks a
+/// newline terminator
+// Not worth a `bytecount` dependency for a once-per-load scan
+#[allow(clippy::naive_bytecount)]
+fn count_lines(path: &Path) -> Result<usize, std::io::Error> {
+    let mut fh = BufReader::new(File::open(path)?);
+    let mut lines = 0;
+    let mut last_byte = b'\n';
+
+    loop {
+        let buf = fh.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        lines += buf.iter().filter(|&&b| b == b'\n').count();
+        last_byte = *buf.last().unwrap();
+        let len = buf.len();
+        fh.consume(len);
+    }
+    if last_byte != b'\n' {
+        lines += 1;
+    }
+
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// Write `content` to a uniquely-named file with the given extension in the
+    /// system temp directory, returning its path
+    fn temp_db(name: &str, content: &str) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("clam-sigutil-test-{}-{name}", std::process::id()));
+        let mut fh = File::create(&path).unwrap();
+        fh.write_all(content.as_bytes()).unwrap();
+        path
+    }
+
+    #[test]
+    fn load_hdb() {
+        let path = temp_db(
+            "load.hdb",
+            concat!(
+                "# a comment\n",
+                "44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature\n",
+                "\n",
+                "00112233445566778899aabbccddeeff:68:Other-Test-Signature\n"
+            ),
+        );
+        let sigs = load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(sigs.len(), 2);
+        assert_eq!(sigs.iter().next().unwrap().name(), "Eicar-Test-Signature");
+    }
+
+    #[test]
+    fn load_unknown_extension() {
+        let path = temp_db("load.nosuch", "");
+        let result = load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(LoadError::UnknownExtension)));
+    }
+
+    #[test]
+    fn load_with_progress() {
+        let path = temp_db(
+            "progress.hdb",
+            concat!(
+                "44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature\n",
+                "00112233445566778899aabbccddeeff:68:Other-Test-Signature\n",
+                "# trailing comment"
+            ),
+        );
+        let calls = std::cell::RefCell::new(vec![]);
+        let sigs = parse_database_file_with_progress(&path, SigType::FileHash, |current, total| {
+            calls.borrow_mut().push((current, total));
+        })
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(sigs.len(), 2);
+        let calls = calls.into_inner();
+        assert!(!calls.is_empty());
+        assert_eq!(calls.last(), Some(&(3, 3)));
+    }
+
+    #[test]
+    fn sink_sees_every_line() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[derive(Default)]
+        struct CountingSink {
+            calls: AtomicUsize,
+        }
+
+        impl ProgressSink for CountingSink {
+            fn on_progress(&self, _done: usize, total: Option<usize>) {
+                assert_eq!(total, Some(2));
+                self.calls.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let path = temp_db(
+            "sink.hdb",
+            concat!(
+                "44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature\n",
+                "00112233445566778899aabbccddeeff:68:Other-Test-Signature\n"
+            ),
+        );
+        let sink = CountingSink::default();
+        let sigs = load_with_progress_sink(&path, SigType::FileHash, &sink).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(sigs.len(), 2);
+        assert_eq!(sink.calls.load(std::sync::atomic::Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn sink_cancellation_stops_early() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Requests cancellation after the first line has been reported
+        #[derive(Default)]
+        struct CancellingSink {
+            seen: AtomicUsize,
+        }
+
+        impl ProgressSink for CancellingSink {
+            fn on_progress(&self, done: usize, _total: Option<usize>) {
+                self.seen.store(done, Ordering::Relaxed);
+            }
+
+            fn should_cancel(&self) -> bool {
+                self.seen.load(Ordering::Relaxed) >= 1
+            }
+        }
+
+        let path = temp_db(
+            "cancel.hdb",
+            concat!(
+                "44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature\n",
+                "00112233445566778899aabbccddeeff:68:Other-Test-Signature\n"
+            ),
+        );
+        let result = load_with_progress_sink(&path, SigType::FileHash, &CancellingSink::default());
+        std::fs::remove_file(&path).unwrap();
+        match result {
+            Err(LoadError::Cancelled { line_no, partial }) => {
+                assert_eq!(line_no, 1);
+                assert!(partial.is_empty());
+            }
+            other => panic!("expected cancellation, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn load_reports_line_number() {
+        let path = temp_db(
+            "badline.hdb",
+            concat!(
+                "44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature\n",
+                "not-a-signature\n"
+            ),
+        );
+        let result = load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(LoadError::ParseError(2, _))));
+    }
+}
@@ -0,0 +1,205 @@
+/*
+ *  Copyright (C) 2024 Cisco Systems, Inc. and/or its affiliates. All rights reserved.
+ *
+ *  This program is free software; you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License version 2 as
+ *  published by the Free Software Foundation.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program; if not, write to the Free Software
+ *  Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston,
+ *  MA 02110-1301, USA.
+ */
+
+//! Asynchronous database loading, available with the `async` feature.
+//!
+//! Only the line splitting performed here is asynchronous; each individual
+//! line is parsed synchronously via the same core as the blocking loaders in
+//! the parent module.
+
+use crate::{
+    signature::{
+        database::{parse_db_line, LoadError, SignatureCollection},
+        SigMeta,
+    },
+    SigType, Signature,
+};
+use std::{
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tokio::io::{AsyncBufRead, AsyncRead, BufReader};
+use tokio_stream::Stream;
+
+/// Parse a signature database as the specified signature type from an
+/// asynchronous reader, yielding one item per signature as lines become
+/// available.  Comment lines (beginning with `#`) and empty lines are
+/// skipped; successful items carry the 1-based line number at which the
+/// signature was found.
+///
+/// A parse or I/O error ends the stream after the error is yielded.
+pub fn parse_db_stream<R: AsyncBufRead + Unpin>(sig_type: SigType, reader: R) -> DbStream<R> {
+    DbStream {
+        sig_type,
+        reader,
+        sigbuf: vec![],
+        line_no: 0,
+        done: false,
+    }
+}
+
+/// The [`Stream`] of parsed signatures returned by [`parse_db_stream`]
+pub struct DbStream<R> {
+    sig_type: SigType,
+    reader: R,
+    /// Accumulates the current line across partially-filled read buffers
+    sigbuf: Vec<u8>,
+    line_no: usize,
+    done: bool,
+}
+
+impl<R: AsyncBufRead + Unpin> Stream for DbStream<R> {
+    type Item = Result<(usize, Box<dyn Signature>, SigMeta), LoadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+            // Accumulate into sigbuf through the next newline (or EOF)
+            let eof = loop {
+                let buf = match ready!(Pin::new(&mut this.reader).poll_fill_buf(cx)) {
+                    Ok(buf) => buf,
+                    Err(e) => {
+                        this.done = true;
+                        return Poll::Ready(Some(Err(e.into())));
+                    }
+                };
+                if buf.is_empty() {
+                    break true;
+                }
+                if let Some(pos) = buf.iter().position(|&b| b == b'\n') {
+                    this.sigbuf.extend_from_slice(&buf[..=pos]);
+                    Pin::new(&mut this.reader).consume(pos + 1);
+                    break false;
+                }
+                this.sigbuf.extend_from_slice(buf);
+                let len = buf.len();
+                Pin::new(&mut this.reader).consume(len);
+            };
+            if eof {
+                this.done = true;
+                if this.sigbuf.is_empty() {
+                    return Poll::Ready(None);
+                }
+            }
+            this.line_no += 1;
+            let result = parse_db_line(this.sig_type, this.line_no, &this.sigbuf);
+            this.sigbuf.clear();
+            match result {
+                Ok(Some((sig, meta))) => return Poll::Ready(Some(Ok((this.line_no, sig, meta)))),
+                // Comment or empty line; move on to the next one
+                Ok(None) => (),
+                Err(e) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            }
+        }
+    }
+}
+
+/// Load a complete signature database as the specified signature type from an
+/// asynchronous reader, such as a file or network stream being extracted from
+/// a CVD.
+///
+/// # Errors
+///
+/// Returns [`LoadError::Io`] if the reader fails, or [`LoadError::ParseError`]
+/// (with the offending line number) if any signature fails to parse.
+pub async fn load_from_async_reader<R: AsyncRead + Unpin>(
+    sig_type: SigType,
+    reader: R,
+) -> Result<SignatureCollection, LoadError> {
+    use tokio_stream::StreamExt;
+
+    let mut stream = parse_db_stream(sig_type, BufReader::new(reader));
+    let mut sigs = SignatureCollection::new();
+    while let Some(item) = stream.next().await {
+        let (_, sig, _) = item?;
+        sigs.push(sig);
+    }
+    Ok(sigs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_stream::StreamExt;
+
+    const FIXTURE: &[u8] = concat!(
+        "# a comment\n",
+        "44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature\n",
+        "\n",
+        "00112233445566778899aabbccddeeff:68:Other-Test-Signature"
+    )
+    .as_bytes();
+
+    #[tokio::test]
+    async fn stream_yields_signatures_with_line_numbers() {
+        let reader = tokio::io::BufReader::new(FIXTURE);
+        let mut stream = parse_db_stream(SigType::FileHash, reader);
+        let mut found = vec![];
+        while let Some(item) = stream.next().await {
+            let (line_no, sig, _) = item.unwrap();
+            found.push((line_no, sig.name().to_owned()));
+        }
+        assert_eq!(
+            found,
+            vec![
+                (2, "Eicar-Test-Signature".to_owned()),
+                (4, "Other-Test-Signature".to_owned()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_handles_lines_split_across_buffers() {
+        // A tiny buffer forces every line to span several fill_buf calls
+        let reader = tokio::io::BufReader::with_capacity(4, FIXTURE);
+        let sigs: Vec<_> = parse_db_stream(SigType::FileHash, reader)
+            .collect::<Result<_, _>>()
+            .await
+            .unwrap();
+        assert_eq!(sigs.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn stream_reports_parse_error_line() {
+        let content: &[u8] =
+            b"44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature\nnot-a-signature\n";
+        let reader = tokio::io::BufReader::new(content);
+        let mut stream = parse_db_stream(SigType::FileHash, reader);
+        assert!(stream.next().await.unwrap().is_ok());
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(LoadError::ParseError(2, _)))
+        ));
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn load_from_reader() {
+        let sigs = load_from_async_reader(SigType::FileHash, FIXTURE)
+            .await
+            .unwrap();
+        assert_eq!(sigs.len(), 2);
+        assert_eq!(sigs.iter().next().unwrap().name(), "Eicar-Test-Signature");
+    }
+}
@@ -21,7 +21,7 @@ use crate::{
     sigbytes::{AppendSigBytes, FromSigBytes, SigBytes},
     signature::{hash::ParseError, FromSigBytesParseError, SigMeta},
     util::parse_number_dec,
-    Signature,
+    SigType, Signature,
 };
 use std::{fmt::Write as FmtWrite, io::Write, str};
 
@@ -71,6 +71,10 @@ impl Signature for DigitalSig {
     fn name(&self) -> &str {
         "Digital Signature"
     }
+
+    fn sig_type(&self) -> SigType {
+        SigType::DigitalSignature
+    }
 }
 
 impl AppendSigBytes for DigitalSig {
@@ -23,15 +23,19 @@ use crate::{
     signature::{
         bodysig::BodySig,
         logical_sig::{
+            expression,
             subsig::{SubSig, SubSigModifier},
-            targetdesc::TargetDescParseError,
+            targetdesc::{TargetDesc, TargetDescAttr, TargetDescParseError},
+            LogicalSig,
         },
         targettype::{TargetType, TargetTypeParseError},
         FromSigBytesParseError, SigMeta, Signature,
     },
     util::{parse_number_dec, ParseNumberError},
+    SigType,
 };
-use std::{fmt::Write, str};
+use enumflags2::{bitflags, BitFlags};
+use std::{fmt::Write, ops::RangeInclusive, str};
 use thiserror::Error;
 
 #[derive(Debug)]
@@ -47,34 +51,99 @@ pub struct ExtendedSig {
     pub(crate) modifier: Option<SubSigModifier>,
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error, PartialEq, Eq)]
 pub enum ExtendedSigParseError {
-    #[error("missing TargetType field")]
+    #[error("missing TargetType field (field 2)")]
     MissingTargetType,
 
-    #[error("missing Offset field")]
+    #[error("missing Offset field (field 3)")]
     MissingOffset,
 
-    #[error("missing HexSignature field")]
+    #[error("missing HexSignature field (field 4)")]
     MissingHexSignature,
 
-    #[error("invalid body signature: {0}")]
+    #[error("invalid body signature (field 4): {0}")]
     BodySig(#[from] BodySigParseError),
 
     #[error("parsing TargetDesc: {0}")]
     TargetDescParse(#[from] TargetDescParseError),
 
-    #[error("parsing TargetType: {0}")]
+    #[error("parsing TargetType (field 2): {0}")]
     TargetTypeParse(#[from] TargetTypeParseError),
 
-    #[error("Parsing offset: {0}")]
+    #[error("Parsing offset (field 3): {0}")]
     ParseOffset(#[from] OffsetParseError),
 
-    #[error("Parsing min_flevel: {0}")]
-    ParseMinFlevel(ParseNumberError<u32>),
+    #[error("Parsing min_flevel (field 5): {0}")]
+    ParseMinFlevel(#[source] ParseNumberError<u32>),
 
-    #[error("Parsing max_flevel: {0}")]
-    ParseMaxFlevel(ParseNumberError<u32>),
+    #[error("Parsing max_flevel (field 6): {0}")]
+    ParseMaxFlevel(#[source] ParseNumberError<u32>),
+}
+
+impl ExtendedSigParseError {
+    /// The 1-based position of the `:`-separated field in which this error
+    /// arose, with the signature name as field 1.  Returns `None` for errors
+    /// not attributable to a single field of an extended signature line
+    /// (e.g., `TargetDesc` errors, which occur only in subsig context).
+    #[must_use]
+    pub fn field_index(&self) -> Option<usize> {
+        match self {
+            ExtendedSigParseError::MissingTargetType
+            | ExtendedSigParseError::TargetTypeParse(_) => Some(2),
+            ExtendedSigParseError::MissingOffset | ExtendedSigParseError::ParseOffset(_) => Some(3),
+            ExtendedSigParseError::MissingHexSignature | ExtendedSigParseError::BodySig(_) => {
+                Some(4)
+            }
+            ExtendedSigParseError::ParseMinFlevel(_) => Some(5),
+            ExtendedSigParseError::ParseMaxFlevel(_) => Some(6),
+            ExtendedSigParseError::TargetDescParse(_) => None,
+        }
+    }
+}
+
+impl ExtendedSig {
+    /// The target type this signature applies to
+    #[must_use]
+    pub fn target_type(&self) -> TargetType {
+        self.target_type
+    }
+
+    /// Promote this signature to a single-subsig logical signature matched by
+    /// the bare expression `0` (e.g., in preparation for adding a second
+    /// condition).  The target type moves into the logical signature's
+    /// `TargetDesc`, along with the given engine feature-level range; the
+    /// offset and body carry over onto the subsig.
+    #[must_use]
+    pub fn into_logical(self, engine: RangeInclusive<u32>) -> LogicalSig {
+        let target_desc = TargetDesc {
+            attrs: vec![
+                TargetDescAttr::Engine(engine.into()),
+                TargetDescAttr::TargetType(self.target_type),
+            ],
+        };
+        let expression: Box<dyn expression::Element> = b"0"
+            .as_slice()
+            .try_into()
+            .expect("bare `0` expression parses");
+        let sub_sig = ExtendedSig {
+            name: None,
+            target_type: TargetType::Any,
+            // A subsig matching anywhere leaves the `*` offset implicit
+            offset: match self.offset {
+                Some(Offset::Normal(OffsetPos::Any)) => None,
+                offset => offset,
+            },
+            body_sig: self.body_sig,
+            modifier: None,
+        };
+        LogicalSig::new(
+            self.name.unwrap_or_default(),
+            target_desc,
+            expression,
+            vec![Box::new(sub_sig)],
+        )
+    }
 }
 
 impl FromSigBytes for ExtendedSig {
@@ -85,9 +154,9 @@ impl FromSigBytes for ExtendedSig {
         let data = sb.into().as_bytes();
         let mut fields = data.split(|b| *b == b':');
 
-        let name = str::from_utf8(fields.next().ok_or(FromSigBytesParseError::MissingName)?)
-            .map_err(FromSigBytesParseError::NameNotUnicode)?
-            .to_owned();
+        let name =
+            super::parse_sig_name(fields.next().ok_or(FromSigBytesParseError::MissingName)?)?
+                .to_owned();
         let target_type = fields
             .next()
             .ok_or(ExtendedSigParseError::MissingTargetType)?
@@ -136,13 +205,13 @@ impl FromSigBytes for ExtendedSig {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Offset {
     Normal(OffsetPos),
     Floating(OffsetPos, usize),
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
 pub enum OffsetParseError {
     #[error("offset missing")]
     Missing,
@@ -151,7 +220,10 @@ pub enum OffsetParseError {
     OffsetPosParse(#[from] OffsetPosParseError),
 
     #[error("parsing MaxShift: {0}")]
-    ParseMaxShift(ParseNumberError<usize>),
+    ParseMaxShift(#[source] ParseNumberError<usize>),
+
+    #[error("offset kind {0:?} is not supported in this context")]
+    KindNotSupported(OffsetKind),
 }
 
 impl Offset {
@@ -166,6 +238,33 @@ impl Offset {
             None
         }
     }
+
+    /// Parse an offset as with [`Offset::try_from`], but additionally require
+    /// that its position be one of the kinds named in `mask`.  Contexts that
+    /// support only a subset of offset kinds (e.g., file type magic entries,
+    /// which have no notion of PE sections) use this to reject unsupported
+    /// kinds at parse time rather than silently accepting them.
+    pub fn try_from_with_mask(
+        value: &[u8],
+        mask: OffsetKindMask,
+    ) -> Result<Self, OffsetParseError> {
+        let offset: Offset = value.try_into()?;
+        let kind = offset.kind();
+        if mask.contains(kind) {
+            Ok(offset)
+        } else {
+            Err(OffsetParseError::KindNotSupported(kind))
+        }
+    }
+
+    /// The [`OffsetKind`] describing this offset's position, without its
+    /// associated values
+    #[must_use]
+    pub fn kind(&self) -> OffsetKind {
+        match self {
+            Offset::Normal(pos) | Offset::Floating(pos, _) => pos.kind(),
+        }
+    }
 }
 
 impl AppendSigBytes for Offset {
@@ -198,7 +297,7 @@ impl AppendSigBytes for Offset {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum OffsetPos {
     Any,
     Absolute(usize),
@@ -210,34 +309,72 @@ pub enum OffsetPos {
     PEVersionInfo,
 }
 
-#[derive(Debug, Error, PartialEq)]
+/// The kind of an [`OffsetPos`], without its associated values.  These may be
+/// combined into an [`OffsetKindMask`] to describe which kinds a particular
+/// signature context accepts.
+#[bitflags]
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum OffsetKind {
+    Any = 0x01,
+    Absolute = 0x02,
+    FromEOF = 0x04,
+    EP = 0x08,
+    StartOfSection = 0x10,
+    EntireSection = 0x20,
+    StartOfLastSection = 0x40,
+    PEVersionInfo = 0x80,
+}
+
+/// A set of [`OffsetKind`]s accepted by a particular parsing context
+pub type OffsetKindMask = BitFlags<OffsetKind>;
+
+impl OffsetPos {
+    /// The [`OffsetKind`] describing this position, without its associated
+    /// values
+    #[must_use]
+    pub fn kind(&self) -> OffsetKind {
+        match self {
+            OffsetPos::Any => OffsetKind::Any,
+            OffsetPos::Absolute(_) => OffsetKind::Absolute,
+            OffsetPos::FromEOF(_) => OffsetKind::FromEOF,
+            OffsetPos::EP(_) => OffsetKind::EP,
+            OffsetPos::StartOfSection { .. } => OffsetKind::StartOfSection,
+            OffsetPos::EntireSection(_) => OffsetKind::EntireSection,
+            OffsetPos::StartOfLastSection(_) => OffsetKind::StartOfLastSection,
+            OffsetPos::PEVersionInfo => OffsetKind::PEVersionInfo,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
 pub enum OffsetPosParseError {
     #[error("Parsing EOF offset: {0}")]
-    ParseEOFOffset(ParseNumberError<usize>),
+    ParseEOFOffset(#[source] ParseNumberError<usize>),
 
     #[error("Parsing EP offset: {0}")]
-    ParseEPOffset(ParseNumberError<isize>),
+    ParseEPOffset(#[source] ParseNumberError<isize>),
 
     #[error("parsing EntireSection offset: {0}")]
-    ParseEntireSectionOffset(ParseNumberError<usize>),
+    ParseEntireSectionOffset(#[source] ParseNumberError<usize>),
 
     #[error("parsing StartOfLastSection offset: {0}")]
-    ParseStartOfLastSectionOffset(ParseNumberError<usize>),
+    ParseStartOfLastSectionOffset(#[source] ParseNumberError<usize>),
 
     #[error("missing section number in offset(SE#+n) format")]
     MissingOffsetSectionNo,
 
     #[error("parsing SectionNo: {0}")]
-    ParseSectionNo(ParseNumberError<usize>),
+    ParseSectionNo(#[source] ParseNumberError<usize>),
 
     #[error("missing offset from section in offset(SE#+n) format")]
     MissingOffsetSectionOffset,
 
     #[error("parsing SectionOffset: {0}")]
-    ParseSectionOffset(ParseNumberError<usize>),
+    ParseSectionOffset(#[source] ParseNumberError<usize>),
 
     #[error("parsing AbsoluteOffset: {0}")]
-    ParseAbsoluteOffset(ParseNumberError<usize>),
+    ParseAbsoluteOffset(#[source] ParseNumberError<usize>),
 }
 
 impl TryFrom<&[u8]> for Offset {
@@ -322,51 +459,86 @@ impl Signature for ExtendedSig {
         }
     }
 
-    fn validate(&self, sigmeta: &SigMeta) -> Result<(), super::SigValidationError> {
-        self.validate_subelements(sigmeta)?;
-        self.validate_flevel(sigmeta)?;
-        Ok(())
+    fn sig_type(&self) -> SigType {
+        SigType::Extended
     }
 
-    fn validate_flevel(&self, sigmeta: &SigMeta) -> Result<(), super::SigValidationError> {
-        // Check the specified vs. the computed feature level
-        if let Some(computed_flevel) = self.computed_feature_level() {
-            if let Some(computed_min_flevel) = computed_flevel.start() {
-                // Some features within this signature have a minimum feature level.
-                // Confirm that the signature specifies it (or a higher level)
-                match &sigmeta.f_level {
-                    Some(f_level) => match f_level.start() {
-                        Some(spec_min_flevel) => {
-                            if spec_min_flevel < computed_min_flevel {
-                                return Err(super::SigValidationError::SpecifiedMinFLevelTooLow {
-                                    spec_min_flevel,
-                                    computed_min_flevel,
-                                    feature_set: self.features().into(),
-                                });
-                            }
-                        }
-                        None => {
-                            // This is the [unlikely] case where a *maximum* FLevel
-                            // was specified without a minimum, but a minimum is required.
-                            return Err(super::SigValidationError::MinFLevelNotSpecified {
-                                computed_min_flevel,
-                                feature_set: self.features().into(),
-                            });
-                        }
-                    },
-                    None => {
-                        return Err(super::SigValidationError::MinFLevelNotSpecified {
-                            computed_min_flevel,
-                            feature_set: self.features().into(),
-                        });
-                    }
-                }
-            }
-            // TODO: check maximum, as well (but maximums are not presently computed)
+    fn literal_strings(&self) -> Vec<Vec<u8>> {
+        self.body_sig
+            .as_ref()
+            .map(BodySig::literal_strings)
+            .unwrap_or_default()
+    }
+
+    fn body_sigs(&self) -> Vec<&BodySig> {
+        self.body_sig.iter().collect()
+    }
+
+    fn complexity(&self) -> super::Complexity {
+        self.body_sig
+            .as_ref()
+            .map(BodySig::complexity)
+            .unwrap_or_default()
+    }
+
+    fn walk(&self, visitor: &mut dyn super::visitor::SigVisitor) {
+        if let Some(offset) = &self.offset {
+            visitor.visit_offset(offset);
+        }
+        if let Some(body_sig) = &self.body_sig {
+            visitor.visit_body_sig(body_sig);
+        }
+    }
+
+    fn walk_mut(&mut self, visitor: &mut dyn super::visitor::SigVisitorMut) {
+        if let Some(offset) = &mut self.offset {
+            visitor.visit_offset(offset);
         }
+        if let Some(body_sig) = &mut self.body_sig {
+            visitor.visit_body_sig(body_sig);
+        }
+    }
 
-        Ok(())
+    /// Derived from the body signature's [`Complexity`](super::Complexity): an
+    /// unspecificity penalty of `100 / (total_static_bytes + 1)`, plus 50 per
+    /// unbounded skip, 5 per alternative branch, and 1 per pattern.  A
+    /// floating offset adds a further 25, since every candidate position
+    /// within the shift range must be considered.
+    fn estimated_scan_cost(&self) -> u64 {
+        let cx = self.complexity();
+        let cost = 100 / (cx.total_static_bytes + 1)
+            + 50 * (cx.wildcard_count + cx.unbounded_range_count)
+            + 5 * cx.alternative_branch_count
+            + cx.pattern_count
+            + if matches!(self.offset, Some(Offset::Floating(..))) {
+                25
+            } else {
+                0
+            };
+        u64::try_from(cost).unwrap_or(u64::MAX)
+    }
+
+    fn warnings(&self) -> Vec<super::SigWarning> {
+        let mut warnings = vec![];
+        if let Some(body_sig) = &self.body_sig {
+            if body_sig.contains_multiple_wildcards() {
+                warnings.push(super::SigWarning::MultipleWildcards {
+                    count: body_sig.wildcard_count(),
+                });
+            }
+            if body_sig.is_trivially_matching() {
+                warnings.push(super::SigWarning::TriviallyMatching);
+            }
+            let distinct = body_sig.count_unique_bytes();
+            if distinct <= super::bodysig::LOW_BYTE_DIVERSITY_MAX {
+                warnings.push(super::SigWarning::LowByteDiversity { distinct });
+            }
+        }
+        warnings
     }
+
+    // `validate` and `validate_flevel` use the trait defaults; the feature
+    // set computed from the body signature drives the flevel comparison
 }
 
 impl EngineReq for ExtendedSig {
@@ -394,6 +566,11 @@ impl AppendSigBytes for ExtendedSig {
         if let Some(body_sig) = &self.body_sig {
             sb.write_char(':')?;
             body_sig.append_sigbytes(sb)?;
+        } else if self.name.is_some() {
+            // A standalone extended signature must carry all four fields; a
+            // missing body is spelled `*`.  Subsigs (which have no name)
+            // simply omit the body.
+            sb.write_str(":*")?;
         }
 
         Ok(())
@@ -404,6 +581,10 @@ impl SubSig for ExtendedSig {
     fn subsig_type(&self) -> super::logical_sig::subsig::SubSigType {
         super::logical_sig::subsig::SubSigType::Extended
     }
+
+    fn offset(&self) -> Option<Offset> {
+        self.offset
+    }
 }
 
 #[cfg(test)]
@@ -411,9 +592,9 @@ mod tests {
     use super::*;
 
     const SAMPLE_SIG: &str =
-        "AllTheStuff-1:1:EP+78,45:de1e7e*facade??(c0|ff|ee)decafe[5-9]00{3-4}d1d2{9-}7e8e{-5}!(0f|f1|ce)(B)(L)a??bccdd";
+        "AllTheStuff-1:1:EP+78,45:de1e7e*facade??(c0|ff|ee)decafe[5-9]00{3-4}d1d2{9-}7e8e{-5}!(0f|f1|ce)(B)a??bccdd";
     const SAMPLE_SIG_WITH_FLEVEL: &str =
-        "AllTheStuff-1:1:EP+78,45:de1e7e*facade??(c0|ff|ee)decafe[5-9]00{3-4}d1d2{9-}7e8e{-5}!(0f|f1|ce)(B)(L)a??bccdd:99:101";
+        "AllTheStuff-1:1:EP+78,45:de1e7e*facade??(c0|ff|ee)decafe[5-9]00{3-4}d1d2{9-}7e8e{-5}!(0f|f1|ce)(B)a??bccdd:99:101";
 
     #[test]
     fn export() {
@@ -423,6 +604,127 @@ mod tests {
         assert_eq!(sigmeta, SigMeta::default());
     }
 
+    #[test]
+    fn export_catch_all_body() {
+        // A `*` body parses to no body signature, but must still round-trip
+        // as a valid 4-field record
+        const CATCH_ALL: &str = "CatchAll-Test:0:*:*";
+        let (sig, sigmeta) = ExtendedSig::from_sigbytes(&CATCH_ALL.into()).unwrap();
+        assert!(sig
+            .downcast_ref::<ExtendedSig>()
+            .unwrap()
+            .body_sig
+            .is_none());
+        let exported = sig.to_sigbytes().unwrap().to_string();
+        assert_eq!(CATCH_ALL, &exported);
+        assert_eq!(sigmeta, SigMeta::default());
+    }
+
+    #[test]
+    fn warns_on_multiple_wildcards() {
+        // SAMPLE_SIG contains a `*` wildcard and an open-ended `{9-}` range
+        let (sig, _) = ExtendedSig::from_sigbytes(&SAMPLE_SIG.into()).unwrap();
+        assert_eq!(
+            sig.warnings(),
+            vec![crate::signature::SigWarning::MultipleWildcards { count: 2 }]
+        );
+    }
+
+    #[test]
+    fn warns_on_trivially_matching_body() {
+        use crate::signature::bodysig::{pattern::MatchByte, Pattern, PatternModifier};
+        use enumflags2::BitFlag;
+        let sig = ExtendedSig {
+            name: Some("Trivial-1".to_owned()),
+            target_type: TargetType::Any,
+            offset: None,
+            body_sig: Some(BodySig {
+                patterns: vec![Pattern::String(
+                    vec![MatchByte::Any; 4].into(),
+                    PatternModifier::empty(),
+                )],
+            }),
+            modifier: None,
+        };
+        assert!(sig
+            .warnings()
+            .contains(&crate::signature::SigWarning::TriviallyMatching));
+    }
+
+    #[test]
+    fn warns_on_low_byte_diversity() {
+        let (sig, _) = ExtendedSig::from_sigbytes(&"Padding-1:0:*:00000000ff".into()).unwrap();
+        assert_eq!(
+            sig.warnings(),
+            vec![crate::signature::SigWarning::LowByteDiversity { distinct: 2 }]
+        );
+        let (sig, _) = ExtendedSig::from_sigbytes(&"Diverse-1:0:*:de1e7efacade".into()).unwrap();
+        assert!(sig.warnings().is_empty());
+    }
+
+    #[test]
+    fn parse_errors_carry_field_index() {
+        fn field_index_of(sig: &str) -> Option<usize> {
+            match ExtendedSig::from_sigbytes(&sig.into()) {
+                Err(e) => e.field_index(),
+                Ok(_) => panic!("expected {sig:?} to fail"),
+            }
+        }
+        assert_eq!(field_index_of("Name:zz:*:aabb"), Some(2));
+        assert_eq!(field_index_of("Name:1:bogus:aabb"), Some(3));
+        assert_eq!(field_index_of("Name:1:*:xyz"), Some(4));
+        assert_eq!(field_index_of("Name:1:*:aabb:zz"), Some(5));
+        assert_eq!(field_index_of("Name:1:*:aabb:99:zz"), Some(6));
+        // A missing field still reports its expected position
+        assert_eq!(field_index_of("Name:1"), Some(3));
+    }
+
+    #[test]
+    fn scan_cost_orders_by_specificity() {
+        fn cost_of(sig: &str) -> u64 {
+            let (sig, _) = ExtendedSig::from_sigbytes(&sig.into()).unwrap();
+            sig.estimated_scan_cost()
+        }
+        // A wildcard-heavy signature costs more than an all-literal one
+        assert!(cost_of("Wild:0:*:aabb*ccdd*eeff") > cost_of("Literal:0:*:aabbccddeeff"));
+        // A floating offset costs more than the same body anchored
+        assert!(cost_of("Float:1:EP+78,45:aabbccdd") > cost_of("Anchored:1:EP+78:aabbccdd"));
+    }
+
+    #[test]
+    fn promote_to_logical() {
+        const NDB: &str = "Test.Sig:1:EP+0:aabbccdd";
+        const LDB: &str = "Test.Sig;Engine:51-255,Target:1;0;EP+0:aabbccdd";
+        let (sig, _) = ExtendedSig::from_sigbytes(&NDB.into()).unwrap();
+        let logical = sig
+            .downcast::<ExtendedSig>()
+            .unwrap()
+            .into_logical(51..=255);
+        assert_eq!(LDB, &logical.to_sigbytes().unwrap().to_string());
+        // The promoted signature must itself parse and validate
+        let (sig, sigmeta) = LogicalSig::from_sigbytes(&LDB.into()).unwrap();
+        sig.validate(&sigmeta).unwrap();
+        // ...and demoting it recovers the original
+        let ext = logical.try_into_extended().unwrap();
+        assert_eq!(NDB, &ext.to_sigbytes().unwrap().to_string());
+    }
+
+    #[test]
+    fn promote_any_offset_to_logical() {
+        // An `*` offset is implicit in a subsig, and must not be emitted
+        const NDB: &str = "Test.Sig:0:*:aabbccdd";
+        const LDB: &str = "Test.Sig;Engine:51-255,Target:0;0;aabbccdd";
+        let (sig, _) = ExtendedSig::from_sigbytes(&NDB.into()).unwrap();
+        let logical = sig
+            .downcast::<ExtendedSig>()
+            .unwrap()
+            .into_logical(51..=255);
+        assert_eq!(LDB, &logical.to_sigbytes().unwrap().to_string());
+        // Demotion restores the explicit `*` offset
+        let ext = logical.try_into_extended().unwrap();
+        assert_eq!(NDB, &ext.to_sigbytes().unwrap().to_string());
+    }
+
     #[test]
     fn parse_flevels() {
         let (sig, sigmeta) = match ExtendedSig::from_sigbytes(&SAMPLE_SIG_WITH_FLEVEL.into()) {
@@ -21,7 +21,7 @@ use crate::{
     sigbytes::{AppendSigBytes, FromSigBytes, SigBytes},
     signature::{hash::ParseError, FromSigBytesParseError, SigMeta},
     util::{self, parse_field, parse_number_dec, Hash},
-    Signature,
+    SigType, Signature,
 };
 use std::{fmt::Write, str};
 
@@ -33,10 +33,49 @@ pub struct FileHashSig {
     file_size: Option<usize>,
 }
 
+impl FileHashSig {
+    /// The name of the hash algorithm this signature uses (`"MD5"`, `"SHA1"`,
+    /// or `"SHA2-256"`), as determined by the digest length
+    #[must_use]
+    pub fn hash_type(&self) -> &'static str {
+        match self.hash {
+            Hash::Md5(_) => "MD5",
+            Hash::Sha1(_) => "SHA1",
+            Hash::Sha2_256(_) => "SHA2-256",
+        }
+    }
+
+    /// The raw bytes of the hash digest
+    #[must_use]
+    pub fn hash_bytes(&self) -> &[u8] {
+        match &self.hash {
+            Hash::Md5(bytes) => bytes,
+            Hash::Sha1(bytes) => bytes,
+            Hash::Sha2_256(bytes) => bytes,
+        }
+    }
+
+    /// The expected file size, if the signature specifies one (i.e., wasn't
+    /// written with the `*` size wildcard)
+    #[must_use]
+    pub fn file_size(&self) -> Option<usize> {
+        self.file_size
+    }
+}
+
 impl Signature for FileHashSig {
     fn name(&self) -> &str {
         &self.name
     }
+
+    fn sig_type(&self) -> SigType {
+        SigType::FileHash
+    }
+
+    /// Hash lookups are effectively constant-time, regardless of file size
+    fn estimated_scan_cost(&self) -> u64 {
+        1
+    }
 }
 
 impl EngineReq for FileHashSig {
@@ -73,8 +112,12 @@ impl FromSigBytes for FileHashSig {
         let mut sigmeta = SigMeta::default();
         let mut fields = sb.into().as_bytes().split(|b| *b == b':');
 
-        let hash = util::parse_hash(fields.next().ok_or(ParseError::MissingField("hash_string".to_string()))?)
-            .map_err(ParseError::ParseHash)?;
+        let hash = util::parse_hash(
+            fields
+                .next()
+                .ok_or(ParseError::MissingField("hash_string".to_string()))?,
+        )
+        .map_err(ParseError::ParseHash)?;
         let file_size = parse_field!(
             OPTIONAL
             fields,
@@ -82,9 +125,9 @@ impl FromSigBytes for FileHashSig {
             ParseError::MissingFileSize,
             ParseError::ParseSize
         )?;
-        let name = str::from_utf8(fields.next().ok_or(FromSigBytesParseError::MissingName)?)
-            .map_err(FromSigBytesParseError::NameNotUnicode)?
-            .to_owned();
+        let name =
+            super::parse_sig_name(fields.next().ok_or(FromSigBytesParseError::MissingName)?)?
+                .to_owned();
 
         // Parse optional min/max flevel
         if let Some(min_flevel) = fields.next() {
@@ -132,6 +175,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn accessors() {
+        let bytes = b"44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature".into();
+        let (sig, _) = FileHashSig::from_sigbytes(&bytes).unwrap();
+        let sig = sig.downcast_ref::<FileHashSig>().unwrap();
+        assert_eq!(sig.hash_type(), "MD5");
+        assert_eq!(sig.hash_bytes(), hex!("44d88612fea8a8f36de82e1278abb02f"));
+        assert_eq!(sig.file_size(), Some(68));
+
+        let bytes =
+            b"275a021bbfb6489e54d471899f7db9d1663fc695ec2fe2a2c4538aabf651fd0f:*:Eicar-Sha2".into();
+        let (sig, _) = FileHashSig::from_sigbytes(&bytes).unwrap();
+        let sig = sig.downcast_ref::<FileHashSig>().unwrap();
+        assert_eq!(sig.hash_type(), "SHA2-256");
+        assert_eq!(sig.file_size(), None);
+    }
+
     #[test]
     fn export() {
         let bytes = b"44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature".into();
@@ -139,4 +199,20 @@ mod tests {
         let exported = sig.to_sigbytes().unwrap();
         assert_eq!(&bytes, &exported);
     }
+
+    #[test]
+    fn export_with_meta() {
+        let bytes = b"44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature:51:255".into();
+        let (sig, sigmeta) = FileHashSig::from_sigbytes(&bytes).unwrap();
+        let exported = sig.to_sigbytes_with_meta(&sigmeta).unwrap();
+        assert_eq!(&bytes, &exported);
+    }
+
+    #[test]
+    fn export_with_meta_min_only() {
+        let bytes = b"44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature:51".into();
+        let (sig, sigmeta) = FileHashSig::from_sigbytes(&bytes).unwrap();
+        let exported = sig.to_sigbytes_with_meta(&sigmeta).unwrap();
+        assert_eq!(&bytes, &exported);
+    }
 }
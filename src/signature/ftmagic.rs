@@ -18,7 +18,7 @@
 
 use super::{
     bodysig::parse::BodySigParseError,
-    ext_sig::{self, Offset},
+    ext_sig::{self, Offset, OffsetKind},
     FromSigBytesParseError, SigMeta,
 };
 use crate::{
@@ -27,7 +27,7 @@ use crate::{
     sigbytes::{AppendSigBytes, FromSigBytes},
     signature::bodysig::BodySig,
     util::{parse_field, parse_number_dec, ParseNumberError},
-    Signature,
+    SigType, Signature,
 };
 use std::{fmt::Write, str};
 use thiserror::Error;
@@ -65,10 +65,10 @@ pub enum FTMagicParseError {
     OffsetMissing,
 
     #[error("parsing exact offset: {0}")]
-    ExactOffsetParse(ParseNumberError<usize>),
+    ExactOffsetParse(#[source] ParseNumberError<usize>),
 
     #[error("parsing bodysig offset: {0}")]
-    OffsetParse(ext_sig::OffsetParseError),
+    OffsetParse(#[source] ext_sig::OffsetParseError),
 
     #[error("missing magicbytes")]
     MagicBytesMissing,
@@ -77,31 +77,31 @@ pub enum FTMagicParseError {
     RtypeMissing,
 
     #[error("parsing rtype: {0}")]
-    Rtype(FileTypeParseError),
+    Rtype(#[source] FileTypeParseError),
 
     #[error("missing type")]
     TypeMissing,
 
     #[error("parsing type: {0}")]
-    Type(FileTypeParseError),
+    Type(#[source] FileTypeParseError),
 
     #[error("Parsing min_flevel: {0}")]
-    ParseMinFlevel(ParseNumberError<u32>),
+    ParseMinFlevel(#[source] ParseNumberError<u32>),
 
     #[error("Parsing max_flevel: {0}")]
-    ParseMaxFlevel(ParseNumberError<u32>),
+    ParseMaxFlevel(#[source] ParseNumberError<u32>),
 
     #[error("Unkown magictype")]
     UnknownMagicType,
 
     #[error("decoding magicbytes for direct memory comparison: {0}")]
-    DirectMemoryDecode(hex::FromHexError),
+    DirectMemoryDecode(#[source] hex::FromHexError),
 
     #[error("decoding body signature from magicbytes: {0}")]
-    BodySig(BodySigParseError),
+    BodySig(#[source] BodySigParseError),
 
     #[error("decoding magicbytes for direct memory (partition) comparison: {0}")]
-    DMPartitionDecode(hex::FromHexError),
+    DMPartitionDecode(#[source] hex::FromHexError),
 
     /// Offset specified for DirectMemory or DMPartition file type is not an
     /// exact value (floating, and computed offsets are supported only for
@@ -114,6 +114,122 @@ impl Signature for FTMagicSig {
     fn name(&self) -> &str {
         &self.name
     }
+
+    fn sig_type(&self) -> SigType {
+        SigType::FTMagic
+    }
+}
+
+impl FTMagicSig {
+    /// The file type this magic entry detects, as resolved through the
+    /// filetype module.  Audit tooling uses this to cross-check FTM entries
+    /// against the Container values used in `.cdb`/`.ldb` signatures.
+    #[must_use]
+    pub fn detected_type(&self) -> FileType {
+        self.file_type.clone()
+    }
+}
+
+/// Errors that can be encountered when building an [`FTMagicSig`]
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FTMagicBuildError {
+    #[error("missing name")]
+    MissingName,
+
+    #[error("missing magic bytes")]
+    MissingMagicBytes,
+
+    #[error("unknown file type {0:?}: {1}")]
+    UnknownFileType(String, #[source] FileTypeParseError),
+}
+
+/// A builder for direct-memory [`FTMagicSig`] entries, validating the declared
+/// file types against the known `CL_TYPE_*` names
+#[derive(Debug, Default)]
+pub struct FTMagicSigBuilder {
+    name: Option<String>,
+    rtype: Option<FileType>,
+    file_type: Option<FileType>,
+    /// A file type declared by name, resolved (and validated) at build time
+    file_type_name: Option<String>,
+    offset: usize,
+    magic: Vec<u8>,
+}
+
+impl FTMagicSigBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The name of the file type magic entry
+    #[must_use]
+    pub fn name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// The container file type within which this magic applies (defaults to
+    /// `CL_TYPE_ANY`)
+    #[must_use]
+    pub fn rtype(mut self, rtype: FileType) -> Self {
+        self.rtype = Some(rtype);
+        self
+    }
+
+    /// The file type detected by this magic entry
+    #[must_use]
+    pub fn file_type(mut self, file_type: FileType) -> Self {
+        self.file_type = Some(file_type);
+        self
+    }
+
+    /// The file type detected by this magic entry, declared by its `CL_TYPE_*`
+    /// name.  The name is validated when the signature is built.
+    #[must_use]
+    pub fn file_type_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.file_type_name = Some(name.into());
+        self
+    }
+
+    /// The absolute offset at which the magic bytes must appear (defaults to 0)
+    #[must_use]
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// The literal bytes to match
+    #[must_use]
+    pub fn magic_bytes<B: Into<Vec<u8>>>(mut self, magic: B) -> Self {
+        self.magic = magic.into();
+        self
+    }
+
+    /// Build the direct-memory magic signature, validating that all required
+    /// elements were provided and that any file type declared by name resolves
+    /// to a known `CL_TYPE_*` value
+    pub fn build(self) -> Result<FTMagicSig, FTMagicBuildError> {
+        let name = self.name.ok_or(FTMagicBuildError::MissingName)?;
+        if self.magic.is_empty() {
+            return Err(FTMagicBuildError::MissingMagicBytes);
+        }
+        let file_type = match (self.file_type, self.file_type_name) {
+            (Some(file_type), _) => file_type,
+            (None, Some(type_name)) => FileType::try_from(type_name.as_bytes())
+                .map_err(|e| FTMagicBuildError::UnknownFileType(type_name, e))?,
+            (None, None) => FileType::CL_TYPE_ANY,
+        };
+        Ok(FTMagicSig {
+            name,
+            rtype: self.rtype.unwrap_or(FileType::CL_TYPE_ANY),
+            file_type,
+            magic_bytes: MagicBytes::DirectMemory {
+                offset: self.offset,
+                literal: self.magic,
+            },
+        })
+    }
 }
 
 impl FromSigBytes for FTMagicSig {
@@ -128,16 +244,27 @@ impl FromSigBytes for FTMagicSig {
         // Field 1
         let magic_type = fields.next().ok_or(FTMagicParseError::MagicTypeMissing)?;
 
-        // Field 2
-        let offset = parse_field!(OPTIONAL fields, Offset::try_from, FTMagicParseError::OffsetMissing, FTMagicParseError::OffsetParse)?;
+        // Field 2.  Direct-memory entries support only absolute offsets (or
+        // `*`); entry-point and section-relative kinds only make sense against
+        // executable layouts, which FTM entries never describe.
+        let offset_mask = match magic_type {
+            b"0" | b"4" => OffsetKind::Absolute | OffsetKind::Any,
+            _ => OffsetKind::Absolute | OffsetKind::Any | OffsetKind::FromEOF,
+        };
+        let offset = parse_field!(
+            OPTIONAL fields,
+            |bytes| Offset::try_from_with_mask(bytes, offset_mask),
+            FTMagicParseError::OffsetMissing,
+            FTMagicParseError::OffsetParse
+        )?;
 
         // Field 3
         let magic_bytes_content = fields.next().ok_or(FTMagicParseError::MagicBytesMissing)?;
 
         // Field 4
-        let name = str::from_utf8(fields.next().ok_or(FromSigBytesParseError::MissingName)?)
-            .map_err(FromSigBytesParseError::NameNotUnicode)?
-            .to_owned();
+        let name =
+            super::parse_sig_name(fields.next().ok_or(FromSigBytesParseError::MissingName)?)?
+                .to_owned();
 
         // Field 5
         let rtype = parse_field!(
@@ -306,6 +433,89 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn build_zip_magic() {
+        let sig = FTMagicSigBuilder::new()
+            .name("ZIP")
+            .magic_bytes(b"PK\x03\x04".to_vec())
+            .file_type(FileType::CL_TYPE_ZIP)
+            .build()
+            .unwrap();
+        assert_eq!(sig.detected_type(), FileType::CL_TYPE_ZIP);
+        let exported = sig.to_sigbytes().unwrap().to_string();
+        assert_eq!(exported, "1:0:504b0304:ZIP:CL_TYPE_ANY:CL_TYPE_ZIP");
+    }
+
+    #[test]
+    fn build_resolves_type_names() {
+        let sig = FTMagicSigBuilder::new()
+            .name("ZIP")
+            .magic_bytes(b"PK\x03\x04".to_vec())
+            .file_type_name("CL_TYPE_ZIP")
+            .build()
+            .unwrap();
+        assert_eq!(sig.detected_type(), FileType::CL_TYPE_ZIP);
+    }
+
+    #[test]
+    fn build_rejects_dangling_type_name() {
+        let result = FTMagicSigBuilder::new()
+            .name("bogus")
+            .magic_bytes(b"PK\x03\x04".to_vec())
+            .file_type_name("CL_TYPE_NOT_A_REAL_TYPE")
+            .build();
+        assert!(matches!(
+            result,
+            Err(FTMagicBuildError::UnknownFileType(..))
+        ));
+    }
+
+    #[test]
+    fn dm_sig_rejects_ep_offset() {
+        let input = SigBytes::from("0:EP+4:ffd8ff:JPEG:CL_TYPE_ANY:CL_TYPE_GRAPHICS");
+        let result = FTMagicSig::from_sigbytes(&input);
+        assert!(matches!(
+            result,
+            Err(FromSigBytesParseError::FTMagicSig(
+                FTMagicParseError::OffsetParse(ext_sig::OffsetParseError::KindNotSupported(
+                    ext_sig::OffsetKind::EP
+                ))
+            ))
+        ));
+    }
+
+    #[test]
+    fn bs_sig_rejects_section_offset() {
+        let input = SigBytes::from(
+            "1:S0+4:cafebabe0000000?:Universal Binary:CL_TYPE_ANY:CL_TYPE_MACHO_UNIBIN",
+        );
+        let result = FTMagicSig::from_sigbytes(&input);
+        assert!(matches!(
+            result,
+            Err(FromSigBytesParseError::FTMagicSig(
+                FTMagicParseError::OffsetParse(ext_sig::OffsetParseError::KindNotSupported(
+                    ext_sig::OffsetKind::StartOfSection
+                ))
+            ))
+        ));
+    }
+
+    #[test]
+    fn bs_sig_accepts_eof_offset() {
+        let input = SigBytes::from(
+            "1:EOF-4:cafebabe0000000?:Universal Binary:CL_TYPE_ANY:CL_TYPE_MACHO_UNIBIN",
+        );
+        let (sig, _) = FTMagicSig::from_sigbytes(&input).unwrap();
+        let sig = sig.downcast_ref::<FTMagicSig>().unwrap();
+        assert!(matches!(
+            sig.magic_bytes,
+            MagicBytes::BodySig {
+                offset: Some(Offset::Normal(OffsetPos::FromEOF(4))),
+                bodysig: _
+            }
+        ));
+    }
+
     #[test]
     fn good_ftm_dmpart_sig() {
         let input = SigBytes::from(
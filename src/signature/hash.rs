@@ -31,17 +31,17 @@ pub enum ParseError {
     MissingField(String),
 
     #[error("parsing size: {0}")]
-    ParseSize(ParseNumberError<usize>),
+    ParseSize(#[source] ParseNumberError<usize>),
 
     #[error("Parsing min_flevel: {0}")]
-    ParseMinFlevel(ParseNumberError<u32>),
+    ParseMinFlevel(#[source] ParseNumberError<u32>),
 
     #[error("Parsing max_flevel: {0}")]
-    ParseMaxFlevel(ParseNumberError<u32>),
+    ParseMaxFlevel(#[source] ParseNumberError<u32>),
 
     #[error("Parsing hash signature: {0}")]
     ParseHash(#[from] crate::util::ParseHashError),
 }
 
-#[derive(Debug, thiserror::Error, PartialEq)]
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Hash)]
 pub enum ValidationError {}
@@ -0,0 +1,328 @@
+/*
+ *  Copyright (C) 2024 Cisco Systems, Inc. and/or its affiliates. All rights reserved.
+ *
+ *  This program is free software; you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License version 2 as
+ *  published by the Free Software Foundation.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program; if not, write to the Free Software
+ *  Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston,
+ *  MA 02110-1301, USA.
+ */
+
+use crate::{
+    feature::EngineReq,
+    sigbytes::{AppendSigBytes, FromSigBytes, SigBytes},
+    signature::{FromSigBytesParseError, SigMeta},
+    util::parse_number_dec,
+    SigType, Signature,
+};
+use std::{fmt::Write, str};
+use thiserror::Error;
+
+/// The length (in bytes) of each of the three hash blocks carried by an icon
+/// signature
+pub const ICON_HASH_BLOCK_SIZE: usize = 16;
+
+/// The icon size bucket an icon signature's hash blocks were computed over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IconSize {
+    /// 16x16 pixels
+    Size16,
+    /// 24x24 pixels
+    Size24,
+    /// 32x32 pixels
+    Size32,
+}
+
+impl IconSize {
+    /// The edge length of this size bucket, in pixels
+    #[must_use]
+    pub fn pixels(self) -> usize {
+        match self {
+            IconSize::Size16 => 16,
+            IconSize::Size24 => 24,
+            IconSize::Size32 => 32,
+        }
+    }
+}
+
+/// A signature from the icon-matching (`.idb`) database: a named icon hash,
+/// assigned to up to two groups that logical signatures may reference via
+/// their `IconGroup1`/`IconGroup2` attributes
+#[derive(Debug, PartialEq, Eq)]
+pub struct IconSig {
+    name: String,
+    group1: Option<String>,
+    group2: Option<String>,
+    size: IconSize,
+    color: [u8; ICON_HASH_BLOCK_SIZE],
+    edge: [u8; ICON_HASH_BLOCK_SIZE],
+    gray: [u8; ICON_HASH_BLOCK_SIZE],
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ParseError {
+    #[error("missing field: {0}")]
+    MissingField(&'static str),
+
+    #[error("{group} group name not unicode")]
+    GroupNotUnicode {
+        group: &'static str,
+        #[source]
+        source: std::str::Utf8Error,
+    },
+
+    #[error("parsing icon size: {0}")]
+    ParseSize(#[source] crate::util::ParseNumberError<usize>),
+
+    #[error("unsupported icon size {found} (expected 16, 24 or 32)")]
+    UnsupportedIconSize { found: usize },
+
+    #[error("{block} hash block has {got} hex characters, expected {expected}")]
+    HashBlockLength {
+        block: &'static str,
+        got: usize,
+        expected: usize,
+    },
+
+    #[error("decoding {block} hash block: {source}")]
+    HashBlockNotHex {
+        block: &'static str,
+        #[source]
+        source: hex::FromHexError,
+    },
+}
+
+/// Decode one fixed-length hash block, verifying its length before decoding
+/// so that a truncated block is reported as such rather than as a hex error
+fn parse_hash_block(
+    block: &'static str,
+    raw: &[u8],
+) -> Result<[u8; ICON_HASH_BLOCK_SIZE], ParseError> {
+    if raw.len() != ICON_HASH_BLOCK_SIZE * 2 {
+        return Err(ParseError::HashBlockLength {
+            block,
+            got: raw.len(),
+            expected: ICON_HASH_BLOCK_SIZE * 2,
+        });
+    }
+    let mut bytes = [0u8; ICON_HASH_BLOCK_SIZE];
+    hex::decode_to_slice(raw, &mut bytes)
+        .map_err(|source| ParseError::HashBlockNotHex { block, source })?;
+    Ok(bytes)
+}
+
+/// Decode an optional group-name field; an empty field means the icon isn't
+/// assigned to that group
+fn parse_group(group: &'static str, raw: &[u8]) -> Result<Option<String>, ParseError> {
+    if raw.is_empty() {
+        Ok(None)
+    } else {
+        str::from_utf8(raw)
+            .map(|s| Some(s.to_owned()))
+            .map_err(|source| ParseError::GroupNotUnicode { group, source })
+    }
+}
+
+impl IconSig {
+    /// The group names this icon is assigned to (omitting unassigned slots),
+    /// as referenced by logical signatures' `IconGroup1`/`IconGroup2`
+    /// attributes
+    pub fn group_names(&self) -> impl Iterator<Item = &str> {
+        self.group1
+            .as_deref()
+            .into_iter()
+            .chain(self.group2.as_deref())
+    }
+
+    /// The size bucket the hash blocks were computed over
+    #[must_use]
+    pub fn size(&self) -> IconSize {
+        self.size
+    }
+
+    /// The color-distribution hash block
+    #[must_use]
+    pub fn color_block(&self) -> &[u8; ICON_HASH_BLOCK_SIZE] {
+        &self.color
+    }
+
+    /// The edge-detection hash block
+    #[must_use]
+    pub fn edge_block(&self) -> &[u8; ICON_HASH_BLOCK_SIZE] {
+        &self.edge
+    }
+
+    /// The grayscale-intensity hash block
+    #[must_use]
+    pub fn gray_block(&self) -> &[u8; ICON_HASH_BLOCK_SIZE] {
+        &self.gray
+    }
+}
+
+impl Signature for IconSig {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn sig_type(&self) -> SigType {
+        SigType::Icon
+    }
+}
+
+impl EngineReq for IconSig {}
+
+impl AppendSigBytes for IconSig {
+    fn append_sigbytes(&self, sb: &mut SigBytes) -> Result<(), crate::signature::ToSigBytesError> {
+        let size_hint = self.name.len()
+            + self.group1.as_ref().map_or(0, String::len)
+            + self.group2.as_ref().map_or(0, String::len)
+            + 3 * ICON_HASH_BLOCK_SIZE * 2
+            + 8;
+        sb.try_reserve_exact(size_hint)?;
+        write!(
+            sb,
+            "{}:{}:{}:{}:",
+            self.name,
+            self.group1.as_deref().unwrap_or(""),
+            self.group2.as_deref().unwrap_or(""),
+            self.size.pixels()
+        )?;
+        for (i, block) in [&self.color, &self.edge, &self.gray]
+            .into_iter()
+            .enumerate()
+        {
+            if i > 0 {
+                sb.write_char(':')?;
+            }
+            write!(sb, "{}", hex::encode(block))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromSigBytes for IconSig {
+    fn from_sigbytes<'a, SB: Into<&'a SigBytes>>(
+        sb: SB,
+    ) -> Result<(Box<dyn crate::Signature>, super::SigMeta), FromSigBytesParseError> {
+        let mut fields = sb.into().as_bytes().split(|b| *b == b':');
+
+        let name =
+            super::parse_sig_name(fields.next().ok_or(FromSigBytesParseError::MissingName)?)?
+                .to_owned();
+        let group1 = parse_group(
+            "IconGroup1",
+            fields.next().ok_or(ParseError::MissingField("group1"))?,
+        )?;
+        let group2 = parse_group(
+            "IconGroup2",
+            fields.next().ok_or(ParseError::MissingField("group2"))?,
+        )?;
+        let size = parse_number_dec(fields.next().ok_or(ParseError::MissingField("size"))?)
+            .map_err(ParseError::ParseSize)?;
+        let size = match size {
+            16 => IconSize::Size16,
+            24 => IconSize::Size24,
+            32 => IconSize::Size32,
+            found => return Err(ParseError::UnsupportedIconSize { found }.into()),
+        };
+        let color = parse_hash_block(
+            "color",
+            fields.next().ok_or(ParseError::MissingField("color"))?,
+        )?;
+        let edge = parse_hash_block(
+            "edge",
+            fields.next().ok_or(ParseError::MissingField("edge"))?,
+        )?;
+        let gray = parse_hash_block(
+            "gray",
+            fields.next().ok_or(ParseError::MissingField("gray"))?,
+        )?;
+
+        Ok((
+            Box::new(Self {
+                name,
+                group1,
+                group2,
+                size,
+                color,
+                edge,
+                gray,
+            }),
+            SigMeta::default(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Synthetic, but format-correct: three 32-hex-character blocks over the
+    // 24-pixel bucket
+    const SAMPLE_SIG: &[u8] = b"Win.Trojan.FakeAV:installers:setup_icons:24:\
+        00112233445566778899aabbccddeeff:\
+        ffeeddccbbaa99887766554433221100:\
+        0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn parse_sample() {
+        let bytes = SAMPLE_SIG.into();
+        let (sig, sigmeta) = IconSig::from_sigbytes(&bytes).unwrap();
+        assert_eq!(sigmeta, SigMeta::default());
+        let sig = sig.downcast_ref::<IconSig>().unwrap();
+        assert_eq!(sig.name(), "Win.Trojan.FakeAV");
+        assert_eq!(
+            sig.group_names().collect::<Vec<_>>(),
+            ["installers", "setup_icons"]
+        );
+        assert_eq!(sig.size(), IconSize::Size24);
+        assert_eq!(sig.color_block()[0], 0x00);
+        assert_eq!(sig.edge_block()[0], 0xff);
+        assert_eq!(sig.gray_block()[0], 0x01);
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let bytes = SAMPLE_SIG.into();
+        let (sig, _) = IconSig::from_sigbytes(&bytes).unwrap();
+        let exported = sig.to_sigbytes().unwrap();
+        assert_eq!(exported.as_bytes(), SAMPLE_SIG);
+    }
+
+    #[test]
+    fn empty_groups_allowed() {
+        let bytes = b"Test.Icon:::16:00112233445566778899aabbccddeeff:00112233445566778899aabbccddeeff:00112233445566778899aabbccddeeff".into();
+        let (sig, _) = IconSig::from_sigbytes(&bytes).unwrap();
+        let sig = sig.downcast_ref::<IconSig>().unwrap();
+        assert_eq!(sig.group_names().count(), 0);
+    }
+
+    #[test]
+    fn wrong_length_hash_block() {
+        // The edge block is two characters short
+        let bytes = b"Test.Icon:grp::32:00112233445566778899aabbccddeeff:ffeeddccbbaa998877665544332211:0123456789abcdef0123456789abcdef".into();
+        let err = IconSig::from_sigbytes(&bytes).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "parsing icon signature: edge hash block has 30 hex characters, expected 32"
+        );
+    }
+
+    #[test]
+    fn unsupported_size() {
+        let bytes = b"Test.Icon:grp::48:00112233445566778899aabbccddeeff:00112233445566778899aabbccddeeff:00112233445566778899aabbccddeeff".into();
+        let err = IconSig::from_sigbytes(&bytes).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "parsing icon signature: unsupported icon size 48 (expected 16, 24 or 32)"
+        );
+    }
+}
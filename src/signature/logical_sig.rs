@@ -27,17 +27,25 @@ use self::{
 use super::bodysig::parse::BodySigParseError;
 use crate::{
     feature::EngineReq,
-    sigbytes::{AppendSigBytes, FromSigBytes},
+    sigbytes::{AppendSigBytes, FromSigBytes, SigBytes},
     signature::{
-        ext_sig::ExtendedSig, FromSigBytesParseError, SigMeta, SigValidationError, Signature,
+        ext_sig::{ExtendedSig, Offset, OffsetKind, OffsetPos},
+        targettype::TargetType,
+        FromSigBytesParseError, SigMeta, SigValidationError, Signature,
     },
     util::Range,
+    SigType,
 };
 use std::{fmt::Write, str};
 use subsig::SubSig;
 use targetdesc::TargetDesc;
 use thiserror::Error;
 
+/// A logical signature.
+///
+/// The engine caps the number of sub-signatures a logical signature may
+/// carry; validation rejects signatures with more than [`MAX_SUB_SIGS`]
+/// sub-signatures.
 #[derive(Debug)]
 pub struct LogicalSig {
     name: String,
@@ -47,33 +55,82 @@ pub struct LogicalSig {
     expression: Box<dyn expression::Element>,
     #[allow(dead_code)]
     sub_sigs: Vec<Box<dyn SubSig>>,
+    /// `#`-prefixed per-subsig annotations retained under
+    /// [`SubsigAnnotationPolicy::Preserve`], keyed by subsig index
+    annotations: Vec<(usize, String)>,
+    /// Ignorable trailing fields (historical artifacts the engine ignores)
+    /// accepted when parsing permissively, preserved for export
+    trailing_fields: Vec<SigBytes>,
+}
+
+/// How `#`-prefixed per-subsig annotations should be handled when parsing a
+/// logical signature
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SubsigAnnotationPolicy {
+    /// Don't interpret annotations.  An annotated subsig will fail to parse,
+    /// matching ClamAV behavior.
+    #[default]
+    Reject,
+    /// Remove annotations prior to parsing the subsig
+    Strip,
+    /// Remove annotations prior to parsing the subsig, retaining them for
+    /// re-emission on export
+    Preserve,
 }
 
+/// The 1-based position of the first subsig within a logical signature's
+/// `;`-separated fields (after the name, `TargetDesc` and expression)
+const FIRST_SUBSIG_FIELD: usize = 4;
+
+/// The maximum number of sub-signatures the engine permits in a logical
+/// signature
+pub const MAX_SUB_SIGS: usize = 64;
+
 #[derive(Debug, Error, PartialEq)]
 pub enum ParseError {
     #[error("parsing body signature index {0}: {1}")]
-    BodySigParse(usize, BodySigParseError),
+    BodySigParse(usize, #[source] BodySigParseError),
 
     #[error("empty")]
     Empty,
 
-    #[error("missing Expression field")]
+    #[error("missing Expression field (field 3)")]
     MissingExpression,
 
-    #[error("invalid logical expression: {0}")]
+    #[error("invalid logical expression (field 3): {0}")]
     LogExprParse(#[from] expression::LogExprParseError),
 
-    #[error("missing TargetDesc field")]
+    #[error("missing TargetDesc field (field 2)")]
     MissingTargetDesc,
 
-    #[error("parsing TargetDesc field: {0}")]
+    #[error("parsing TargetDesc (field 2): {0}")]
     TargetDesc(#[from] TargetDescParseError),
 
+    /// The subsig number is subsig-relative (zero-based, as referenced from
+    /// the logical expression); [`ParseError::field_index`] reports the
+    /// absolute field position.
     #[error("parsing subsig {0}: {1}")]
-    SubSigParse(usize, SubSigParseError),
+    SubSigParse(usize, #[source] SubSigParseError),
 }
 
-#[derive(Debug, Error, PartialEq)]
+impl ParseError {
+    /// The 1-based position of the `;`-separated field in which this error
+    /// arose, with the signature name as field 1.  Returns `None` for errors
+    /// not attributable to a single field.
+    #[must_use]
+    pub fn field_index(&self) -> Option<usize> {
+        match self {
+            ParseError::Empty => None,
+            ParseError::MissingTargetDesc | ParseError::TargetDesc(_) => Some(2),
+            ParseError::MissingExpression | ParseError::LogExprParse(_) => Some(3),
+            ParseError::BodySigParse(subsig_no, _) | ParseError::SubSigParse(subsig_no, _) => {
+                Some(FIRST_SUBSIG_FIELD + subsig_no)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
 pub enum ValidationError {
     #[error("validating TargetDesc: {0}")]
     TargetDesc(#[from] TargetDescValidationError),
@@ -83,6 +140,42 @@ pub enum ValidationError {
         idx: usize,
         err: Box<SigValidationError>,
     },
+
+    #[error("subsig {idx} has no body; logical subsigs may not be `*`")]
+    SubSigMissingBody { idx: usize },
+
+    #[error("{count} subsigs exceeds the engine maximum of {max}")]
+    TooManySubSigs { count: usize, max: usize },
+
+    #[error("subsig {from} references subsig {to}: {reason}")]
+    InvalidSubSigReference {
+        from: usize,
+        to: usize,
+        reason: &'static str,
+    },
+
+    #[error("subsig {idx} offset kind {kind:?} requires a native executable Target")]
+    SubSigOffsetRequiresExecTarget { idx: usize, kind: OffsetKind },
+}
+
+/// Errors arising when demoting a logical signature to an extended signature
+/// via [`LogicalSig::try_into_extended`]
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
+pub enum ConversionError {
+    #[error("expected exactly one subsig, found {0}")]
+    NotOneSubSig(usize),
+
+    #[error("subsig is not an extended (hex body) subsig")]
+    SubSigNotExtended,
+
+    #[error("subsig carries a modifier")]
+    SubSigHasModifier,
+
+    #[error("expression is not the bare subsig index `0`")]
+    ExpressionNotTrivial,
+
+    #[error("TargetDesc attribute has no extended-signature equivalent: {attr}")]
+    LogicalOnlyTargetDescAttr { attr: &'static str },
 }
 
 impl Signature for LogicalSig {
@@ -90,12 +183,163 @@ impl Signature for LogicalSig {
         &self.name
     }
 
+    fn sig_type(&self) -> SigType {
+        SigType::Logical
+    }
+
+    fn literal_strings(&self) -> Vec<Vec<u8>> {
+        self.sub_sigs
+            .iter()
+            .filter_map(|ss| ss.downcast_ref::<ExtendedSig>())
+            .flat_map(Signature::literal_strings)
+            .collect()
+    }
+
+    fn body_sigs(&self) -> Vec<&super::bodysig::BodySig> {
+        self.sub_sigs
+            .iter()
+            .filter_map(|ss| ss.downcast_ref::<ExtendedSig>())
+            .filter_map(|ext| ext.body_sig.as_ref())
+            .collect()
+    }
+
+    fn complexity(&self) -> crate::signature::Complexity {
+        let mut cx = crate::signature::Complexity::default();
+        for sub_sig in &self.sub_sigs {
+            if sub_sig.contains_pcre() {
+                cx.pcre_subsig_count += 1;
+            } else if let Some(ext) = sub_sig.downcast_ref::<ExtendedSig>() {
+                cx += ext.complexity();
+            }
+        }
+        cx
+    }
+
+    /// The sum of the subsig costs (extended subsigs per
+    /// [`ExtendedSig::estimated_scan_cost`]; PCRE subsigs are charged a flat
+    /// 500, and other subsig types the nominal 10), plus 2 per subsig for
+    /// evaluating the logical expression.
+    fn estimated_scan_cost(&self) -> u64 {
+        let mut cost = 0u64;
+        for sub_sig in &self.sub_sigs {
+            cost += if sub_sig.contains_pcre() {
+                500
+            } else if let Some(ext) = sub_sig.downcast_ref::<ExtendedSig>() {
+                ext.estimated_scan_cost()
+            } else {
+                10
+            };
+        }
+        cost + 2 * u64::try_from(self.sub_sigs.len()).unwrap_or(u64::MAX / 2)
+    }
+
+    fn walk(&self, visitor: &mut dyn crate::signature::visitor::SigVisitor) {
+        visitor.visit_target_desc(&self.target_desc);
+        for sub_sig in &self.sub_sigs {
+            visitor.visit_subsig(sub_sig.as_ref());
+            if let Some(ext) = sub_sig.downcast_ref::<ExtendedSig>() {
+                ext.walk(visitor);
+            }
+        }
+    }
+
+    fn walk_mut(&mut self, visitor: &mut dyn crate::signature::visitor::SigVisitorMut) {
+        visitor.visit_target_desc(&mut self.target_desc);
+        for sub_sig in &mut self.sub_sigs {
+            visitor.visit_subsig(sub_sig.as_mut());
+            if let Some(ext) = sub_sig.downcast_mut::<ExtendedSig>() {
+                ext.walk_mut(visitor);
+            }
+        }
+    }
+
+    fn warnings(&self) -> Vec<crate::signature::SigWarning> {
+        self.sub_sigs
+            .iter()
+            .filter_map(|ss| ss.downcast_ref::<ExtendedSig>())
+            .flat_map(Signature::warnings)
+            .chain(
+                expression::validate(self.expression.as_ref())
+                    .into_iter()
+                    .map(crate::signature::SigWarning::Expression),
+            )
+            .collect()
+    }
+
+    fn append_diagnostic_fields(&self, out: &mut String) {
+        use std::fmt::Write;
+        let mut td = SigBytes::new();
+        if self.target_desc.append_sigbytes(&mut td).is_ok() {
+            let _ = writeln!(out, "target desc: {td}");
+        }
+        let _ = writeln!(out, "expression: {}", self.expression);
+        for (idx, (sub_sig, body)) in self.sub_sigs.iter().zip(self.sub_sig_bodies()).enumerate() {
+            match body {
+                Some(body) => {
+                    let _ = writeln!(
+                        out,
+                        "subsig {idx}: type={:?} body={body}",
+                        sub_sig.subsig_type()
+                    );
+                }
+                None => {
+                    let _ = writeln!(out, "subsig {idx}: type={:?}", sub_sig.subsig_type());
+                }
+            }
+        }
+    }
+
+    fn to_sigbytes_with_meta(
+        &self,
+        sigmeta: &SigMeta,
+    ) -> Result<SigBytes, crate::signature::ToSigBytesError> {
+        // A stored `Engine` attribute is authoritative.  Otherwise,
+        // synthesize one from the metadata (with an open upper bound
+        // rendered as 255) so that programmatically-built signatures with
+        // only a computed feature level still serialize a valid `Engine`
+        // attribute.
+        if self
+            .target_desc
+            .attrs
+            .iter()
+            .any(|attr| matches!(attr, TargetDescAttr::Engine(_)))
+        {
+            return self.to_sigbytes();
+        }
+        let Some(engine) = sigmeta.engine_attr() else {
+            return self.to_sigbytes();
+        };
+        let mut attrs = Vec::with_capacity(self.target_desc.attrs.len() + 1);
+        attrs.push(engine);
+        attrs.extend(self.target_desc.attrs.iter().cloned());
+        let target_desc = TargetDesc { attrs };
+
+        let mut sb = SigBytes::new();
+        write!(sb, "{};", self.name)?;
+        target_desc.append_sigbytes(&mut sb)?;
+        self.append_sigbytes_tail(&mut sb)?;
+        Ok(sb)
+    }
+
     fn validate_subelements(&self, sigmeta: &SigMeta) -> Result<(), SigValidationError> {
         self.target_desc
             .validate()
             .map_err(ValidationError::TargetDesc)?;
+        if self.sub_sigs.len() > MAX_SUB_SIGS {
+            return Err(ValidationError::TooManySubSigs {
+                count: self.sub_sigs.len(),
+                max: MAX_SUB_SIGS,
+            }
+            .into());
+        }
         for (idx, sub_sig) in self.sub_sigs.iter().enumerate() {
             if let Some(extsig) = sub_sig.downcast_ref::<ExtendedSig>() {
+                // A `*` (absent) body is permitted only in standalone
+                // extended signatures; every logical subsig must match
+                // something
+                if extsig.body_sig.is_none() {
+                    return Err(ValidationError::SubSigMissingBody { idx }.into());
+                }
                 extsig
                     .validate(sigmeta)
                     .map_err(|err| ValidationError::SubSig {
@@ -103,22 +347,427 @@ impl Signature for LogicalSig {
                         err: Box::new(err),
                     })?;
             }
+
+            // Executable-relative offsets are meaningful only when they can
+            // be resolved against a native executable image; `VI`
+            // specifically requires PE.  With a `Container` attribute
+            // present, offsets resolve within the extracted inner object, so
+            // the innermost container type is consulted instead of the outer
+            // target (and `Absolute`/EOF-relative offsets refer to positions
+            // within the inner object, which remains valid)
+            if let Some(offset) = sub_sig.offset() {
+                let context = self.target_desc.resolution_context();
+                let kind = offset.kind();
+                let allowed = match kind {
+                    OffsetKind::EP
+                    | OffsetKind::StartOfSection
+                    | OffsetKind::EntireSection
+                    | OffsetKind::StartOfLastSection => context.supports_exec_offsets(),
+                    OffsetKind::PEVersionInfo => context.supports_pe_version_info(),
+                    _ => true,
+                };
+                if !allowed {
+                    return Err(
+                        ValidationError::SubSigOffsetRequiresExecTarget { idx, kind }.into(),
+                    );
+                }
+            }
+        }
+
+        // PCRE trigger expressions and byte-compare triggers may reference
+        // only earlier, non-PCRE sub-signatures.  Since references must point
+        // backwards, this also rules out cycles.
+        for (from, to) in self.subsig_dependency_graph() {
+            if to >= self.sub_sigs.len() {
+                return Err(ValidationError::InvalidSubSigReference {
+                    from,
+                    to,
+                    reason: "no such sub-signature",
+                }
+                .into());
+            }
+            if to >= from {
+                return Err(ValidationError::InvalidSubSigReference {
+                    from,
+                    to,
+                    reason: "may only reference an earlier sub-signature",
+                }
+                .into());
+            }
+            if self.sub_sigs[to]
+                .downcast_ref::<subsig::PCRESubSig>()
+                .is_some()
+            {
+                return Err(ValidationError::InvalidSubSigReference {
+                    from,
+                    to,
+                    reason: "PCRE sub-signatures may not trigger other PCRE sub-signatures",
+                }
+                .into());
+            }
         }
 
         Ok(())
     }
 }
 
-impl FromSigBytes for LogicalSig {
-    fn from_sigbytes<'a, SB: Into<&'a crate::sigbytes::SigBytes>>(
+impl LogicalSig {
+    /// The type of each sub-signature, in order.  This saves callers from
+    /// iterating and downcasting themselves.
+    #[must_use]
+    pub fn sub_sig_types(&self) -> Vec<subsig::SubSigType> {
+        self.sub_sigs
+            .iter()
+            .map(|sub_sig| sub_sig.subsig_type())
+            .collect()
+    }
+
+    /// Whether any sub-signature is a PCRE pattern
+    #[must_use]
+    pub fn has_pcre_subsig(&self) -> bool {
+        self.sub_sigs.iter().any(|sub_sig| sub_sig.contains_pcre())
+    }
+
+    /// Assemble a logical signature from its parts
+    pub(crate) fn new(
+        name: String,
+        target_desc: TargetDesc,
+        expression: Box<dyn expression::Element>,
+        sub_sigs: Vec<Box<dyn SubSig>>,
+    ) -> Self {
+        Self {
+            name,
+            target_desc,
+            expression,
+            sub_sigs,
+            annotations: vec![],
+            trailing_fields: vec![],
+        }
+    }
+
+    /// Demote this signature to an extended signature.  This succeeds only
+    /// when the signature is trivially logical: a single extended subsig
+    /// without a modifier, matched by the bare expression `0`, and a
+    /// `TargetDesc` containing nothing an extended signature can't express.
+    /// An `Engine` attribute is accepted, but dropped: an extended
+    /// signature's feature level range is metadata (carried in
+    /// [`SigMeta::f_level`]) rather than part of the signature itself.
+    pub fn try_into_extended(self) -> Result<ExtendedSig, ConversionError> {
+        // A bare index parses as a chain of transparent single-child
+        // expression wrappers around the index itself
+        fn bare_index(element: &dyn expression::Element) -> Option<u8> {
+            if element.modifier().is_some() {
+                return None;
+            }
+            match element.children() {
+                [] => element.sig_index(),
+                [only] => bare_index(only.as_ref()),
+                _ => None,
+            }
+        }
+
+        let Self {
+            name,
+            target_desc,
+            expression,
+            sub_sigs,
+            ..
+        } = self;
+
+        if sub_sigs.len() != 1 {
+            return Err(ConversionError::NotOneSubSig(sub_sigs.len()));
+        }
+        let sub_sig = sub_sigs
+            .into_iter()
+            .next()
+            .expect("length confirmed above")
+            .downcast::<ExtendedSig>()
+            .map_err(|_| ConversionError::SubSigNotExtended)?;
+        if sub_sig.modifier.is_some() {
+            return Err(ConversionError::SubSigHasModifier);
+        }
+        if bare_index(expression.as_ref()) != Some(0) {
+            return Err(ConversionError::ExpressionNotTrivial);
+        }
+
+        let mut target_type = TargetType::Any;
+        for attr in target_desc.attrs {
+            match attr {
+                TargetDescAttr::Engine(_) => (),
+                TargetDescAttr::TargetType(tt) => target_type = tt,
+                logical_only => {
+                    return Err(ConversionError::LogicalOnlyTargetDescAttr {
+                        attr: logical_only.keyword(),
+                    })
+                }
+            }
+        }
+
+        Ok(ExtendedSig {
+            name: Some(name),
+            target_type,
+            // Standalone extended sigs require an offset; `*` is the
+            // "anywhere" spelling a subsig leaves implicit
+            offset: sub_sig.offset.or(Some(Offset::Normal(OffsetPos::Any))),
+            body_sig: sub_sig.body_sig,
+            modifier: None,
+        })
+    }
+
+    /// Whether any of this signature's sub-signatures is a PCRE pattern.
+    /// PCRE sub-signatures force a minimum engine feature level of 81.
+    #[must_use]
+    pub fn any_subsig_is_pcre(&self) -> bool {
+        self.sub_sigs.iter().any(|ss| ss.contains_pcre())
+    }
+
+    /// The dependencies between this signature's sub-signatures, as
+    /// `(dependent, dependency)` index pairs in sub-signature order.  PCRE
+    /// sub-signatures depend on every index in their trigger expression;
+    /// byte-compare sub-signatures depend on their trigger sub-signature.
+    /// Validation requires each dependency to name an earlier, non-PCRE
+    /// sub-signature.
+    #[must_use]
+    pub fn subsig_dependency_graph(&self) -> Vec<(usize, usize)> {
+        let mut edges = vec![];
+        for (idx, sub_sig) in self.sub_sigs.iter().enumerate() {
+            if let Some(pcre) = sub_sig.downcast_ref::<subsig::PCRESubSig>() {
+                edges.extend(
+                    pcre.trigger_sig_indices()
+                        .into_iter()
+                        .map(|to| (idx, usize::from(to))),
+                );
+            } else if let Some(bytecmp) = sub_sig.downcast_ref::<subsig::ByteCmpSubSig>() {
+                edges.push((idx, usize::from(bytecmp.subsigid_trigger())));
+            }
+        }
+        edges
+    }
+
+    /// The serialized body pattern of each sub-signature, in subsignature
+    /// order (so indices match those used in the logical expression).
+    /// Extended subsigs report their hex-encoded body; PCRE subsigs report
+    /// the raw regular expression.  Subsigs of other types, subsigs without a
+    /// body, and bodies that fail to serialize all report `None`.
+    #[must_use]
+    pub fn sub_sig_bodies(&self) -> Vec<Option<SigBytes>> {
+        self.sub_sigs
+            .iter()
+            .map(|sub_sig| {
+                if let Some(ext) = sub_sig.downcast_ref::<ExtendedSig>() {
+                    ext.body_sig.as_ref().and_then(|body_sig| {
+                        let mut sb = SigBytes::new();
+                        body_sig.append_sigbytes(&mut sb).ok().map(|()| sb)
+                    })
+                } else {
+                    sub_sig
+                        .downcast_ref::<subsig::PCRESubSig>()
+                        .map(|pcre| pcre.regexp().raw.as_slice().into())
+                }
+            })
+            .collect()
+    }
+
+    /// Ignorable trailing fields found after the subsignatures, if the
+    /// signature was parsed permissively
+    #[must_use]
+    pub fn trailing_fields(&self) -> &[SigBytes] {
+        &self.trailing_fields
+    }
+
+    /// Consume this signature, returning its sub-signatures.  Together with
+    /// [`into_expression`](LogicalSig::into_expression) and
+    /// [`into_target_desc`](LogicalSig::into_target_desc), this allows a
+    /// logical signature to be disassembled into its parts (e.g., to split a
+    /// multi-subsig logical signature into individual extended signatures).
+    #[must_use]
+    pub fn into_sub_sigs(self) -> Vec<Box<dyn SubSig>> {
+        self.sub_sigs
+    }
+
+    /// Consume this signature, returning its logical expression
+    #[must_use]
+    pub fn into_expression(self) -> Box<dyn expression::Element> {
+        self.expression
+    }
+
+    /// Consume this signature, returning its target description
+    #[must_use]
+    pub fn into_target_desc(self) -> TargetDesc {
+        self.target_desc
+    }
+
+    /// This signature's target description
+    #[must_use]
+    pub fn target_desc(&self) -> &TargetDesc {
+        &self.target_desc
+    }
+
+    /// Each sub-signature's parsed hex body, where it has one, keyed by
+    /// subsig index.  Non-body sub-signatures (PCRE, byte-compare, etc.) are
+    /// skipped, so indices may be sparse.  See [`Self::sub_sig_bodies`] for
+    /// the serialized view covering PCRE subsigs as well.
+    #[must_use]
+    pub fn sub_sig_body_sigs(&self) -> Vec<(usize, &crate::signature::bodysig::BodySig)> {
+        self.sub_sigs
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, sub_sig)| {
+                sub_sig
+                    .downcast_ref::<ExtendedSig>()
+                    .and_then(|extsig| extsig.body_sig.as_ref())
+                    .map(|body| (idx, body))
+            })
+            .collect()
+    }
+
+    /// Render this signature's structure as a Graphviz digraph for
+    /// documentation and review: the `TargetDesc` constraints in a header
+    /// node, the logical expression as a tree of operator nodes, and each
+    /// sub-signature as a leaf labeled with its decoded body (truncated, per
+    /// [`BodySig::decode_ascii`](crate::signature::bodysig::BodySig::decode_ascii))
+    /// or, where it has no hex body, its sub-signature type.  The output is
+    /// deterministic, so it's suitable for snapshot comparison.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        /// Decoded bodies longer than this are truncated in leaf labels
+        const MAX_LABEL_BODY: usize = 48;
+
+        // Escape a label for inclusion in a double-quoted DOT string.  The
+        // decoded-ASCII rendering has already reduced arbitrary bytes to
+        // printable ASCII; only the DOT metacharacters remain.
+        fn dot_escape(s: &str) -> String {
+            let mut out = String::with_capacity(s.len());
+            for c in s.chars() {
+                if matches!(c, '"' | '\\') {
+                    out.push('\\');
+                }
+                out.push(c);
+            }
+            out
+        }
+
+        // Emit the node for `element` and the edges to its children,
+        // returning the node name.  Bare subsig indices map to the shared
+        // `subsig_N` leaves; grouping elements become `expr_N` nodes labeled
+        // with the operators joining their children (`()` for a transparent
+        // group) and any match-count modifier.
+        fn walk(
+            element: &dyn expression::Element,
+            next_id: &mut usize,
+            out: &mut String,
+        ) -> String {
+            if let Some(idx) = element.sig_index() {
+                return format!("subsig_{idx}");
+            }
+            let id = *next_id;
+            *next_id += 1;
+            let mut label = String::new();
+            for child in element.children() {
+                if let Some(op) = child.operation() {
+                    let op = op.to_string();
+                    if !label.contains(&op) {
+                        label.push_str(&op);
+                    }
+                }
+            }
+            if label.is_empty() {
+                label.push_str("()");
+            }
+            if let Some(modifier) = element.modifier() {
+                let _ = write!(label, " {modifier}");
+            }
+            let _ = writeln!(out, "  expr_{id} [label=\"{}\"];", dot_escape(&label));
+            for child in element.children() {
+                let child_name = walk(child.as_ref(), next_id, out);
+                // A modifier on a bare index would otherwise be lost, since
+                // the shared leaf node can't carry it
+                match child.modifier().filter(|_| child.sig_index().is_some()) {
+                    Some(modifier) => {
+                        let _ = writeln!(
+                            out,
+                            "  expr_{id} -> {child_name} [label=\"{}\"];",
+                            dot_escape(&modifier.to_string())
+                        );
+                    }
+                    None => {
+                        let _ = writeln!(out, "  expr_{id} -> {child_name};");
+                    }
+                }
+            }
+            format!("expr_{id}")
+        }
+
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph \"{}\" {{", dot_escape(&self.name));
+        out.push_str("  node [shape=box];\n");
+        let mut td = SigBytes::new();
+        if self.target_desc.append_sigbytes(&mut td).is_ok() {
+            let _ = writeln!(
+                out,
+                "  target_desc [shape=note, label=\"{}\"];",
+                dot_escape(&td.to_string())
+            );
+        }
+        let mut next_id = 0;
+        walk(self.expression.as_ref(), &mut next_id, &mut out);
+        for (idx, sub_sig) in self.sub_sigs.iter().enumerate() {
+            let label = match sub_sig
+                .downcast_ref::<ExtendedSig>()
+                .and_then(|ext| ext.body_sig.as_ref())
+            {
+                Some(body_sig) => {
+                    let mut body = body_sig.decode_ascii().to_string();
+                    if body.len() > MAX_LABEL_BODY {
+                        body = body.chars().take(MAX_LABEL_BODY).collect();
+                        body.push_str("...");
+                    }
+                    format!("{idx}: {body}")
+                }
+                None => format!("{idx}: {:?}", sub_sig.subsig_type()),
+            };
+            let _ = writeln!(
+                out,
+                "  subsig_{idx} [shape=ellipse, label=\"{}\"];",
+                dot_escape(&label)
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Parse a logical signature, handling `#`-prefixed per-subsig annotations
+    /// per the specified policy.  [`LogicalSig::from_sigbytes`] is equivalent
+    /// to calling this with [`SubsigAnnotationPolicy::Reject`].
+    pub fn from_sigbytes_with_annotation_policy<'a, SB: Into<&'a crate::sigbytes::SigBytes>>(
         sb: SB,
-    ) -> Result<(Box<dyn Signature>, super::SigMeta), FromSigBytesParseError> {
+        policy: SubsigAnnotationPolicy,
+    ) -> Result<(Box<dyn Signature>, SigMeta), FromSigBytesParseError> {
+        Self::parse(sb.into(), policy, false)
+    }
+
+    /// Parse a logical signature permissively: trailing fields that fail to
+    /// parse as subsignatures, but match known-ignorable shapes (an empty
+    /// field, or a pure-digit legacy field), are recorded in
+    /// [`trailing_fields`](LogicalSig::trailing_fields) rather than treated as
+    /// errors.  Anything else still errors.
+    pub fn from_sigbytes_permissive<'a, SB: Into<&'a crate::sigbytes::SigBytes>>(
+        sb: SB,
+    ) -> Result<(Box<dyn Signature>, SigMeta), FromSigBytesParseError> {
+        Self::parse(sb.into(), SubsigAnnotationPolicy::default(), true)
+    }
+
+    fn parse(
+        sb: &crate::sigbytes::SigBytes,
+        policy: SubsigAnnotationPolicy,
+        permissive: bool,
+    ) -> Result<(Box<dyn Signature>, SigMeta), FromSigBytesParseError> {
         let mut sigmeta = SigMeta::default();
-        let mut fields = sb.into().as_bytes().split(|b| *b == b';');
+        let mut fields = sb.as_bytes().split(|b| *b == b';');
 
-        let name = str::from_utf8(fields.next().ok_or(FromSigBytesParseError::MissingName)?)
-            .map_err(FromSigBytesParseError::NameNotUnicode)?
-            .into();
+        let name =
+            super::parse_sig_name(fields.next().ok_or(FromSigBytesParseError::MissingName)?)?
+                .into();
         let target_desc: TargetDesc = fields
             .next()
             .ok_or(ParseError::MissingTargetDesc)?
@@ -130,12 +779,41 @@ impl FromSigBytes for LogicalSig {
             .try_into()
             .map_err(ParseError::LogExprParse)?;
         let mut sub_sigs = vec![];
-        for (subsig_no, subsig_bytes) in fields.enumerate() {
+        let mut annotations = vec![];
+        let mut trailing_fields: Vec<SigBytes> = vec![];
+        for (subsig_no, field_bytes) in fields.enumerate() {
+            let subsig_bytes = match policy {
+                SubsigAnnotationPolicy::Reject => field_bytes,
+                SubsigAnnotationPolicy::Strip | SubsigAnnotationPolicy::Preserve => {
+                    let (subsig_bytes, annotation) = split_annotation(field_bytes);
+                    if policy == SubsigAnnotationPolicy::Preserve {
+                        if let Some(annotation) = annotation {
+                            annotations.push((
+                                subsig_no,
+                                str::from_utf8(annotation)
+                                    .map_err(|_| {
+                                        FromSigBytesParseError::InvalidValueFor(
+                                            "subsig annotation".to_owned(),
+                                        )
+                                    })?
+                                    .to_owned(),
+                            ));
+                        }
+                    }
+                    subsig_bytes
+                }
+            };
             let (modifier, subsig_bytes) = find_modifier(subsig_bytes);
-            sub_sigs.push(
-                subsig::parse_bytes(subsig_bytes, modifier)
-                    .map_err(|e| ParseError::SubSigParse(subsig_no, e))?,
-            );
+            match subsig::parse_bytes(subsig_bytes, modifier) {
+                Ok(sub_sig) => sub_sigs.push(sub_sig),
+                Err(e) => {
+                    if permissive && is_ignorable_trailing_field(field_bytes) {
+                        trailing_fields.push(field_bytes.into());
+                    } else {
+                        return Err(ParseError::SubSigParse(subsig_no, e).into());
+                    }
+                }
+            }
         }
 
         if let Some(range) = target_desc.attrs.iter().find_map(|attr| match attr {
@@ -150,12 +828,22 @@ impl FromSigBytes for LogicalSig {
             target_desc,
             expression,
             sub_sigs,
+            annotations,
+            trailing_fields,
         };
 
         Ok((Box::new(sig), sigmeta))
     }
 }
 
+impl FromSigBytes for LogicalSig {
+    fn from_sigbytes<'a, SB: Into<&'a crate::sigbytes::SigBytes>>(
+        sb: SB,
+    ) -> Result<(Box<dyn Signature>, super::SigMeta), FromSigBytesParseError> {
+        Self::from_sigbytes_with_annotation_policy(sb, SubsigAnnotationPolicy::default())
+    }
+}
+
 impl EngineReq for LogicalSig {
     fn features(&self) -> crate::feature::Set {
         // Collect all the features required by the various subsigs
@@ -174,6 +862,17 @@ impl AppendSigBytes for LogicalSig {
     ) -> Result<(), crate::signature::ToSigBytesError> {
         write!(sb, "{};", self.name)?;
         self.target_desc.append_sigbytes(sb)?;
+        self.append_sigbytes_tail(sb)
+    }
+}
+
+impl LogicalSig {
+    /// Serialize everything following the `TargetDesc`: the expression,
+    /// subsignatures (with annotations), and any trailing fields
+    fn append_sigbytes_tail(
+        &self,
+        sb: &mut crate::sigbytes::SigBytes,
+    ) -> Result<(), crate::signature::ToSigBytesError> {
         write!(sb, ";{};", self.expression)?;
         for (i, sub_sig) in self.sub_sigs.iter().enumerate() {
             if i > 0 {
@@ -199,6 +898,13 @@ impl AppendSigBytes for LogicalSig {
             } else {
                 sub_sig.append_sigbytes(sb)?;
             }
+            if let Some((_, annotation)) = self.annotations.iter().find(|(idx, _)| *idx == i) {
+                write!(sb, "#{annotation}")?;
+            }
+        }
+        for field in &self.trailing_fields {
+            sb.write_char(';')?;
+            std::io::Write::write_all(sb, field.as_bytes())?;
         }
         Ok(())
     }
@@ -241,6 +947,22 @@ fn find_modifier(haystack: &[u8]) -> (Option<SubSigModifier>, &[u8]) {
     (None, haystack)
 }
 
+/// Whether a field that failed subsig parsing matches a known-ignorable shape:
+/// an empty field, or a pure-digit legacy field.  These occur as historical
+/// artifacts on some published .ldb lines, and the engine ignores them.
+fn is_ignorable_trailing_field(field: &[u8]) -> bool {
+    field.iter().all(u8::is_ascii_digit)
+}
+
+/// Split a trailing `#`-prefixed annotation from a subsignature, if one is
+/// present.  The annotation begins at the last `#` in the subsignature.
+fn split_annotation(haystack: &[u8]) -> (&[u8], Option<&[u8]>) {
+    match haystack.iter().rposition(|&b| b == b'#') {
+        Some(pos) => (&haystack[..pos], Some(&haystack[pos + 1..])),
+        None => (haystack, None),
+    }
+}
+
 /*
 impl TryFrom<&[u8]> for LogicalSig {
     type Error = FromSigBytesParseError;
@@ -248,8 +970,7 @@ impl TryFrom<&[u8]> for LogicalSig {
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
         let mut fields = data.split(|b| *b == b';');
 
-        let name = str::from_utf8(fields.next().ok_or(FromSigBytesParseError::MissingName)?)
-            .map_err(FromSigBytesParseError::NameNotUnicode)?
+        let name = super::parse_sig_name(fields.next().ok_or(FromSigBytesParseError::MissingName)?)?
             .into();
         let target_desc = fields
             .next()
@@ -310,6 +1031,38 @@ mod tests {
         dbg!(sig);
     }
 
+    #[test]
+    fn diagnostic_string_covers_parsed_fields() {
+        let input = SAMPLE_SIG.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let diag = sig.to_diagnostic_string();
+        assert!(diag.contains("name: PUA.Email.Phishing.FedEx-1\n"));
+        assert!(diag.contains("type: Logical\n"));
+        assert!(diag.contains("target desc: Engine:51-255,Target:4\n"));
+        assert!(diag.contains("expression: (0&1)&(2|3)\n"));
+        assert!(diag.contains(
+            "subsig 0: type=Extended body=697320656e636c6f73656420746f20746865206c6574746572\n"
+        ));
+        assert!(diag.contains("validation: ok\n"));
+        assert!(diag.contains(&format!("cvd form: {SAMPLE_SIG}\n")));
+    }
+
+    #[test]
+    fn decoded_renders_subsig_bodies_as_ascii() {
+        let input = SAMPLE_SIG.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let decoded: Vec<String> = sig.decoded().iter().map(ToString::to_string).collect();
+        assert_eq!(
+            decoded,
+            vec![
+                "is enclosed to the letter",
+                "compensation from you for it's keeping",
+                "octet-stream;name=\"FedEx_Label_ID_Order",
+                "octet-stream;name=\"Label_Parcel_FedEx_",
+            ]
+        );
+    }
+
     #[test]
     fn test_find_modifier() {
         assert_eq!(
@@ -359,6 +1112,98 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_errors_carry_field_index() {
+        fn field_index_of(sig: &str) -> Option<usize> {
+            let input = sig.into();
+            match LogicalSig::from_sigbytes(&input) {
+                Err(e) => e.field_index(),
+                Ok(_) => panic!("expected {sig:?} to fail"),
+            }
+        }
+        // Field 2: TargetDesc
+        assert_eq!(
+            field_index_of("Name;Engine:zz-255,Target:0;(0&1);414141;424242"),
+            Some(2)
+        );
+        // Field 3: expression
+        assert_eq!(
+            field_index_of("Name;Engine:51-255,Target:0;0x1;414141;424242"),
+            Some(3)
+        );
+        // Fields 4 and up: subsigs.  The reported index is absolute, not
+        // subsig-relative.
+        assert_eq!(
+            field_index_of("Name;Engine:51-255,Target:0;(0&1);zzz;424242"),
+            Some(4)
+        );
+        assert_eq!(
+            field_index_of("Name;Engine:51-255,Target:0;(0&1);414141;zzz"),
+            Some(5)
+        );
+        // A missing field still reports its expected position
+        assert_eq!(field_index_of("Name"), Some(2));
+    }
+
+    #[test]
+    fn expression_diagnostics_surface_as_warnings() {
+        let raw_sig = b"Test.Sig;Engine:51-255,Target:0;(0&1)>2;414141;424242".into();
+        let (sig, _) = LogicalSig::from_sigbytes(&raw_sig).unwrap();
+        assert!(sig.warnings().iter().any(|w| matches!(
+            w,
+            crate::signature::SigWarning::Expression(
+                expression::ExprDiagnostic::MatchReqExceedsAndGroup { .. }
+            )
+        )));
+
+        let raw_sig = SAMPLE_SIG.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&raw_sig).unwrap();
+        assert!(sig.warnings().is_empty());
+    }
+
+    #[test]
+    fn gandcrab_complexity() {
+        let input = SAMPLE_SIG_WITH_PCRE_OFFSET.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let cx = sig.complexity();
+        assert_eq!(
+            cx,
+            crate::signature::Complexity {
+                pattern_count: 5,
+                total_static_bytes: 41,
+                wildcard_count: 0,
+                unbounded_range_count: 0,
+                alternative_branch_count: 2,
+                pcre_subsig_count: 1,
+            }
+        );
+        assert_eq!(cx.score(), 25);
+    }
+
+    #[test]
+    fn destructuring_accessors() {
+        let input = SAMPLE_SIG.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let sig = sig.downcast::<LogicalSig>().unwrap();
+        let sub_sigs = sig.into_sub_sigs();
+        assert_eq!(sub_sigs.len(), 4);
+        assert!(sub_sigs
+            .iter()
+            .all(|ss| ss.downcast_ref::<ExtendedSig>().is_some()));
+
+        let input = SAMPLE_SIG.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let expr = sig.downcast::<LogicalSig>().unwrap().into_expression();
+        assert_eq!(expr.to_string(), "(0&1)&(2|3)");
+
+        let input = SAMPLE_SIG.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let target_desc = sig.downcast::<LogicalSig>().unwrap().into_target_desc();
+        let mut exported = SigBytes::new();
+        target_desc.append_sigbytes(&mut exported).unwrap();
+        assert_eq!(exported.to_string(), "Engine:51-255,Target:4");
+    }
+
     #[test]
     fn export() {
         let input = SAMPLE_SIG.into();
@@ -375,6 +1220,16 @@ mod tests {
         assert_eq!(SAMPLE_SIG_WITH_PCRE_OFFSET, &exported);
     }
 
+    #[test]
+    fn export_with_meta_unchanged() {
+        // The Engine attribute already carries the flevel range, so exporting
+        // with metadata must not append anything
+        let input = SAMPLE_SIG.into();
+        let (sig, sigmeta) = LogicalSig::from_sigbytes(&input).unwrap();
+        let exported = sig.to_sigbytes_with_meta(&sigmeta).unwrap().to_string();
+        assert_eq!(SAMPLE_SIG, &exported);
+    }
+
     #[test]
     fn get_meta() {
         let input = SAMPLE_SIG.into();
@@ -399,6 +1254,207 @@ mod tests {
         assert_eq!(raw_sig, exported);
     }
 
+    #[test]
+    fn permissive_accepts_ignorable_trailing_fields() {
+        let raw_sig = format!("{SAMPLE_SIG};");
+        // The strict parser still rejects the empty trailing field
+        assert!(LogicalSig::from_sigbytes(&raw_sig.as_str().into()).is_err());
+
+        let (sig, _) = LogicalSig::from_sigbytes_permissive(&raw_sig.as_str().into()).unwrap();
+        {
+            let sig = sig.downcast_ref::<LogicalSig>().unwrap();
+            assert_eq!(sig.trailing_fields().len(), 1);
+        }
+        // Trailing fields are preserved on export
+        let exported = sig.to_sigbytes().unwrap().to_string();
+        assert_eq!(exported, raw_sig);
+    }
+
+    #[test]
+    fn permissive_still_rejects_broken_subsigs() {
+        let raw_sig = format!("{SAMPLE_SIG};zzz-not-a-subsig");
+        assert!(LogicalSig::from_sigbytes_permissive(&raw_sig.as_str().into()).is_err());
+    }
+
+    #[test]
+    fn literal_strings_indexable() {
+        let input = SAMPLE_SIG.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let literals = sig.literal_strings();
+        assert!(!literals.is_empty());
+        assert!(literals.iter().any(|s| s
+            .windows(b"octet-stream".len())
+            .any(|w| w == b"octet-stream")));
+    }
+
+    #[test]
+    fn sub_sig_bodies_in_expression_order() {
+        let input = SAMPLE_SIG.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let sig = sig.downcast_ref::<LogicalSig>().unwrap();
+        let bodies: Vec<String> = sig
+            .sub_sig_bodies()
+            .into_iter()
+            .map(|body| body.expect("body").to_string())
+            .collect();
+        // Each subsig's hex body, in the order referenced by the expression
+        assert_eq!(bodies.len(), 4);
+        assert_eq!(
+            bodies[0],
+            "697320656e636c6f73656420746f20746865206c6574746572"
+        );
+        assert!(SAMPLE_SIG.ends_with(&bodies[3]));
+
+        // PCRE subsigs report the raw regular expression
+        let input = SAMPLE_SIG_WITH_PCRE_OFFSET.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let sig = sig.downcast_ref::<LogicalSig>().unwrap();
+        let bodies = sig.sub_sig_bodies();
+        assert_eq!(
+            bodies[4].as_ref().map(ToString::to_string).as_deref(),
+            Some(r"\x00{24}[A-Za-z0-9+/=]{8}")
+        );
+    }
+
+    #[test]
+    fn subsig_count_limit() {
+        fn sig_with_subsigs(n: usize) -> (Box<dyn Signature>, SigMeta) {
+            let input: SigBytes = format!(
+                "Test.Max.Subsigs;Engine:51-255,Target:0;0&1;{}",
+                vec!["414141"; n].join(";")
+            )
+            .into();
+            LogicalSig::from_sigbytes(&input).unwrap()
+        }
+
+        let (sig, sigmeta) = sig_with_subsigs(MAX_SUB_SIGS);
+        assert!(sig.validate(&sigmeta).is_ok());
+
+        let (sig, sigmeta) = sig_with_subsigs(MAX_SUB_SIGS + 1);
+        assert_eq!(
+            sig.validate(&sigmeta),
+            Err(ValidationError::TooManySubSigs {
+                count: MAX_SUB_SIGS + 1,
+                max: MAX_SUB_SIGS
+            }
+            .into())
+        );
+    }
+
+    #[test]
+    fn bodyless_subsig_fails_validation() {
+        let input = SAMPLE_SIG.into();
+        let (sig, sigmeta) = LogicalSig::from_sigbytes(&input).unwrap();
+        let mut sig = sig.downcast::<LogicalSig>().unwrap();
+        assert!(sig.validate(&sigmeta).is_ok());
+
+        // A bodyless extended subsig can't arise from parsing, but can from
+        // programmatic assembly
+        sig.sub_sigs[0] = Box::new(ExtendedSig {
+            name: None,
+            target_type: crate::signature::targettype::TargetType::Any,
+            offset: None,
+            body_sig: None,
+            modifier: None,
+        });
+        assert_eq!(
+            sig.validate(&sigmeta),
+            Err(ValidationError::SubSigMissingBody { idx: 0 }.into())
+        );
+    }
+
+    #[test]
+    fn detect_pcre_subsigs() {
+        let input = SAMPLE_SIG_WITH_PCRE_OFFSET.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let sig = sig.downcast_ref::<LogicalSig>().unwrap();
+        assert!(sig.any_subsig_is_pcre());
+
+        let input = SAMPLE_SIG.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let sig = sig.downcast_ref::<LogicalSig>().unwrap();
+        assert!(!sig.any_subsig_is_pcre());
+    }
+
+    const SAMPLE_SIG_ANNOTATED: &str = concat!(
+        "Test.Annotated.Sig;Engine:51-255,Target:0;(0&1);",
+        "414141#first marker;",
+        "424242"
+    );
+
+    #[test]
+    fn annotations_rejected_by_default() {
+        let input = SAMPLE_SIG_ANNOTATED.into();
+        assert!(LogicalSig::from_sigbytes(&input).is_err());
+    }
+
+    #[test]
+    fn annotations_stripped() {
+        let input = SAMPLE_SIG_ANNOTATED.into();
+        let (sig, _) =
+            LogicalSig::from_sigbytes_with_annotation_policy(&input, SubsigAnnotationPolicy::Strip)
+                .unwrap();
+        let exported = sig.to_sigbytes().unwrap().to_string();
+        assert_eq!(
+            exported,
+            "Test.Annotated.Sig;Engine:51-255,Target:0;(0&1);414141;424242"
+        );
+    }
+
+    #[test]
+    fn annotations_preserved() {
+        let input = SAMPLE_SIG_ANNOTATED.into();
+        let (sig, _) = LogicalSig::from_sigbytes_with_annotation_policy(
+            &input,
+            SubsigAnnotationPolicy::Preserve,
+        )
+        .unwrap();
+        let exported = sig.to_sigbytes().unwrap().to_string();
+        assert_eq!(exported, SAMPLE_SIG_ANNOTATED);
+    }
+
+    #[test]
+    fn min_flevel_shorthand() {
+        let raw_sig = br"TestSig;Engine:81-255;0;/foobar/".into();
+        let (sig, _) = LogicalSig::from_sigbytes(&raw_sig).unwrap();
+        assert_eq!(
+            sig.min_flevel(),
+            sig.computed_feature_level().and_then(|r| r.start())
+        );
+        assert_eq!(sig.min_flevel(), Some(81));
+    }
+
+    #[test]
+    fn engine_attr_synthesized_on_export() {
+        // A signature without an `Engine` attribute picks one up from the
+        // metadata, with an open upper bound rendered as 255
+        let input = b"TestSig;Target:0;(0&1);414141;424242".into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let sigmeta = SigMeta {
+            f_level: Some((81..).into()),
+        };
+        assert_eq!(
+            sig.to_sigbytes_with_meta(&sigmeta).unwrap().to_string(),
+            "TestSig;Engine:81-255,Target:0;(0&1);414141;424242"
+        );
+
+        // Without any feature level, the signature is exported unchanged
+        assert_eq!(
+            sig.to_sigbytes_with_meta(&SigMeta::default())
+                .unwrap()
+                .to_string(),
+            "TestSig;Target:0;(0&1);414141;424242"
+        );
+
+        // A stored `Engine` attribute is authoritative
+        let input = b"TestSig;Engine:90-120,Target:0;(0&1);414141;424242".into();
+        let (sig, sigmeta) = LogicalSig::from_sigbytes(&input).unwrap();
+        assert_eq!(
+            sig.to_sigbytes_with_meta(&sigmeta).unwrap().to_string(),
+            "TestSig;Engine:90-120,Target:0;(0&1);414141;424242"
+        );
+    }
+
     #[test]
     fn validate_min_flevel() {
         // This signature contains a PCRE subsig, which should force a minimum
@@ -414,4 +1470,241 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn subsig_types_readback() {
+        let input = SAMPLE_SIG_WITH_PCRE_OFFSET.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let lsig = sig.downcast_ref::<LogicalSig>().unwrap();
+        assert_eq!(
+            lsig.sub_sig_types(),
+            vec![
+                subsig::SubSigType::Extended,
+                subsig::SubSigType::Extended,
+                subsig::SubSigType::Extended,
+                subsig::SubSigType::Extended,
+                subsig::SubSigType::Pcre,
+            ]
+        );
+        assert!(lsig.has_pcre_subsig());
+
+        let input = SAMPLE_SIG.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let lsig = sig.downcast_ref::<LogicalSig>().unwrap();
+        assert!(!lsig.has_pcre_subsig());
+    }
+
+    #[test]
+    fn subsig_offset_readback() {
+        let input = SAMPLE_SIG_WITH_PCRE_OFFSET.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let lsig = sig.downcast_ref::<LogicalSig>().unwrap();
+        // Only the PCRE subsig (index 4) carries an offset prefix
+        assert_eq!(
+            lsig.sub_sigs[4].offset(),
+            Some(Offset::Normal(OffsetPos::FromEOF(32)))
+        );
+        assert!(lsig.sub_sigs[..4].iter().all(|s| s.offset().is_none()));
+    }
+
+    #[test]
+    fn subsig_exec_offset_requires_exec_target() {
+        // An EP-relative subsig offset is meaningless against Target:0
+        let input = concat!(
+            "Test.ExecOffset.Target;Engine:51-255,Target:0;0&1;",
+            "aabbccdd;EP+0:eeff0011"
+        )
+        .into();
+        let (sig, sigmeta) = LogicalSig::from_sigbytes(&input).unwrap();
+        assert_eq!(
+            sig.validate(&sigmeta),
+            Err(ValidationError::SubSigOffsetRequiresExecTarget {
+                idx: 1,
+                kind: OffsetKind::EP,
+            }
+            .into())
+        );
+    }
+
+    #[test]
+    fn subsig_offsets_resolve_against_container() {
+        // With a Container attribute, offsets resolve within the extracted
+        // inner object.  EOF-relative offsets refer to the inner object's
+        // size and remain valid regardless of the container type...
+        let input = concat!(
+            "Test.Container.EOFOffset;Engine:51-255,Target:0,Container:CL_TYPE_ZIP;0&1;",
+            "aabbccdd;EOF-16:eeff0011"
+        )
+        .into();
+        let (sig, sigmeta) = LogicalSig::from_sigbytes(&input).unwrap();
+        assert!(sig.validate(&sigmeta).is_ok());
+
+        // ...but an EP-relative offset can't be resolved within an object
+        // extracted from a ZIP
+        let input = concat!(
+            "Test.Container.EPOffset;Engine:51-255,Target:0,Container:CL_TYPE_ZIP;0&1;",
+            "aabbccdd;EP+0:eeff0011"
+        )
+        .into();
+        let (sig, sigmeta) = LogicalSig::from_sigbytes(&input).unwrap();
+        assert_eq!(
+            sig.validate(&sigmeta),
+            Err(ValidationError::SubSigOffsetRequiresExecTarget {
+                idx: 1,
+                kind: OffsetKind::EP,
+            }
+            .into())
+        );
+
+        // An executable container, however, supports it even though the
+        // outer target alone would not
+        let input = concat!(
+            "Test.Container.ExeEPOffset;Engine:51-255,Target:0,Container:CL_TYPE_MSEXE;0&1;",
+            "aabbccdd;EP+0:eeff0011"
+        )
+        .into();
+        let (sig, sigmeta) = LogicalSig::from_sigbytes(&input).unwrap();
+        assert!(sig.validate(&sigmeta).is_ok());
+    }
+
+    #[test]
+    fn subsig_dependency_graph_valid_chain() {
+        let input = SAMPLE_SIG_WITH_PCRE_OFFSET.into();
+        let (sig, sigmeta) = LogicalSig::from_sigbytes(&input).unwrap();
+        let lsig = sig.downcast_ref::<LogicalSig>().unwrap();
+        // The PCRE subsig (index 4) triggers on each of the four earlier
+        // subsigs
+        assert_eq!(
+            lsig.subsig_dependency_graph(),
+            vec![(4, 0), (4, 1), (4, 2), (4, 3)]
+        );
+        assert!(sig.validate(&sigmeta).is_ok());
+    }
+
+    #[test]
+    fn subsig_forward_reference_fails_validation() {
+        let input = concat!(
+            "Test.Forward.Reference;Engine:81-255,Target:1;0&1&2;",
+            "aabbccdd;2/abc/;eeff0011"
+        )
+        .into();
+        let (sig, sigmeta) = LogicalSig::from_sigbytes(&input).unwrap();
+        assert_eq!(
+            sig.validate(&sigmeta),
+            Err(ValidationError::InvalidSubSigReference {
+                from: 1,
+                to: 2,
+                reason: "may only reference an earlier sub-signature",
+            }
+            .into())
+        );
+    }
+
+    #[test]
+    fn subsig_pcre_triggering_pcre_fails_validation() {
+        let input = concat!(
+            "Test.Pcre.Trigger;Engine:81-255,Target:1;0&1&2;",
+            "aabbccdd;0/abc/;1/def/"
+        )
+        .into();
+        let (sig, sigmeta) = LogicalSig::from_sigbytes(&input).unwrap();
+        assert_eq!(
+            sig.validate(&sigmeta),
+            Err(ValidationError::InvalidSubSigReference {
+                from: 2,
+                to: 1,
+                reason: "PCRE sub-signatures may not trigger other PCRE sub-signatures",
+            }
+            .into())
+        );
+    }
+
+    #[test]
+    fn demote_to_extended() {
+        const LDB: &str = "Test.Sig;Engine:51-255,Target:1;0;EP+0:aabbccdd";
+        const NDB: &str = "Test.Sig:1:EP+0:aabbccdd";
+        let (sig, sigmeta) = LogicalSig::from_sigbytes(&LDB.into()).unwrap();
+        let ext = sig
+            .downcast::<LogicalSig>()
+            .unwrap()
+            .try_into_extended()
+            .unwrap();
+        // The demoted signature must itself be valid
+        ext.validate(&sigmeta).unwrap();
+        assert_eq!(NDB, &ext.to_sigbytes().unwrap().to_string());
+        // ...and promoting it again recovers the original
+        let logical = ext.into_logical(51..=255);
+        assert_eq!(LDB, &logical.to_sigbytes().unwrap().to_string());
+    }
+
+    #[test]
+    fn demote_only_trivially_logical_sigs() {
+        fn demote_err(sig: &str) -> ConversionError {
+            let (sig, _) = LogicalSig::from_sigbytes(&sig.into()).unwrap();
+            sig.downcast::<LogicalSig>()
+                .unwrap()
+                .try_into_extended()
+                .unwrap_err()
+        }
+
+        assert_eq!(
+            demote_err("Test.Sig;Target:0;0&1;aabbccdd;eeff0011"),
+            ConversionError::NotOneSubSig(2)
+        );
+        assert_eq!(
+            demote_err("Test.Sig;Target:0;0;0/abc/"),
+            ConversionError::SubSigNotExtended
+        );
+        assert_eq!(
+            demote_err("Test.Sig;Target:0;0;616263::i"),
+            ConversionError::SubSigHasModifier
+        );
+        assert_eq!(
+            demote_err("Test.Sig;Target:0;0&0;aabbccdd"),
+            ConversionError::ExpressionNotTrivial
+        );
+        assert_eq!(
+            demote_err("Test.Sig;Target:0,FileSize:0-100;0;aabbccdd"),
+            ConversionError::LogicalOnlyTargetDescAttr { attr: "FileSize" }
+        );
+    }
+
+    #[test]
+    fn dot_export_snapshot() {
+        let input = SAMPLE_SIG_WITH_PCRE_OFFSET.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let lsig = sig.downcast_ref::<LogicalSig>().unwrap();
+        // The bodies are rendered decoded, with backslashes doubled for DOT;
+        // the PCRE subsig has no hex body and falls back to its type
+        assert_eq!(
+            lsig.to_dot(),
+            concat!(
+                "digraph \"Win.Packed.Gandcrab-6535413-0\" {\n",
+                "  node [shape=box];\n",
+                "  target_desc [shape=note, label=\"Engine:81-255,Target:1\"];\n",
+                "  expr_0 [label=\"()\"];\n",
+                "  expr_0 -> subsig_4;\n",
+                "  subsig_0 [shape=ellipse, label=\"0: PPPPP\\\\xe8{2}(\\\\xff\\\\xff|\\\\x00\\\\x00)\"];\n",
+                "  subsig_1 [shape=ellipse, label=\"1: SSSSSSSSSSS\\\\xff\\\\x15\"];\n",
+                "  subsig_2 [shape=ellipse, label=\"2: SSSSS{7}\\\\xff\\\\x15\"];\n",
+                "  subsig_3 [shape=ellipse, label=\"3: mscoree.dll\"];\n",
+                "  subsig_4 [shape=ellipse, label=\"4: Pcre\"];\n",
+                "}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn dot_export_operator_nodes() {
+        let input = SAMPLE_SIG.into();
+        let (sig, _) = LogicalSig::from_sigbytes(&input).unwrap();
+        let lsig = sig.downcast_ref::<LogicalSig>().unwrap();
+        let dot = lsig.to_dot();
+        // (0&1)&(2|3): an outer `&` node over an `&` group and an `|` group
+        assert!(dot.contains("expr_0 [label=\"&\"];"));
+        assert!(dot.contains("expr_1 [label=\"&\"];"));
+        assert!(dot.contains("expr_2 [label=\"|\"];"));
+        assert!(dot.contains("expr_1 -> subsig_0;"));
+        assert!(dot.contains("expr_2 -> subsig_3;"));
+    }
 }
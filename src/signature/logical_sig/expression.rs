@@ -54,6 +54,17 @@ pub trait Element: fmt::Display + fmt::Debug {
 
     /// Set the modifier for this element
     fn set_modifier(&mut self, op: Option<Modifier>);
+
+    /// The nested elements grouped within this element (empty for a bare
+    /// subsig index)
+    fn children(&self) -> &[Box<dyn Element>] {
+        &[]
+    }
+
+    /// The subsig index this element references, if it's a bare index
+    fn sig_index(&self) -> Option<u8> {
+        None
+    }
 }
 
 /// An element's relationship to the prior element within the same expression.
@@ -142,6 +153,10 @@ impl Element for Expr {
     fn set_modifier(&mut self, modifier: Option<Modifier>) {
         self.modifier = modifier;
     }
+
+    fn children(&self) -> &[Box<dyn Element>] {
+        &self.elements
+    }
 }
 
 /*********************************************************************
@@ -246,6 +261,198 @@ impl Element for SigIndex {
     fn set_modifier(&mut self, modifier: Option<Modifier>) {
         self.modifier = modifier;
     }
+
+    fn sig_index(&self) -> Option<u8> {
+        Some(self.sig_index)
+    }
+}
+
+/*********************************************************************
+ * Semantic validation
+ *********************************************************************/
+
+/// A semantic problem found within a logical expression.  Expressions with
+/// these problems parse successfully, but are either meaningless or will be
+/// rejected by the engine.
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Hash)]
+pub enum ExprDiagnostic {
+    /// A `<1` or `=0` modifier, which is satisfied only by zero matches
+    #[error("modifier `{modifier}` requires zero matches")]
+    ZeroMatchModifier { modifier: String },
+
+    /// A unique-match requirement larger than the number of distinct subsig
+    /// indices the group references
+    #[error(
+        "modifier `{modifier}` requires {uniq} unique matches, but the group \
+         references only {distinct} distinct subsig(s)"
+    )]
+    MatchUniqExceedsDistinct {
+        modifier: String,
+        uniq: usize,
+        distinct: usize,
+    },
+
+    /// A unique-match requirement attached to a single subsig index, where it
+    /// is meaningless
+    #[error("modifier `{modifier}` specifies a unique-match count on a single subsig index")]
+    MatchUniqOnSingleIndex { modifier: String },
+
+    /// A `>n` threshold on a group joined entirely by `&`, where `n` meets or
+    /// exceeds the number of distinct subsigs and so can never be satisfied
+    /// by distinct matches
+    #[error(
+        "modifier `{modifier}` on an `&` group referencing {distinct} distinct \
+         subsig(s) exceeds the group's plausible match count"
+    )]
+    MatchReqExceedsAndGroup { modifier: String, distinct: usize },
+}
+
+/// Check an expression tree for modifier semantics problems, returning a
+/// diagnostic for each problem found (or an empty list for a clean
+/// expression).  See [`ExprDiagnostic`] for the rules applied.
+#[must_use]
+pub fn validate(element: &dyn Element) -> Vec<ExprDiagnostic> {
+    let mut diagnostics = vec![];
+    check_element(element, &mut diagnostics);
+    diagnostics
+}
+
+fn check_element(element: &dyn Element, diagnostics: &mut Vec<ExprDiagnostic>) {
+    if let Some(modifier) = element.modifier() {
+        let distinct = distinct_indices(element).len();
+        match modifier.mod_op {
+            ModOp::LessThan if modifier.match_req <= 1 => {
+                diagnostics.push(ExprDiagnostic::ZeroMatchModifier {
+                    modifier: modifier.to_string(),
+                });
+            }
+            ModOp::Equal if modifier.match_req == 0 => {
+                diagnostics.push(ExprDiagnostic::ZeroMatchModifier {
+                    modifier: modifier.to_string(),
+                });
+            }
+            _ => (),
+        }
+        if let Some(uniq) = modifier.match_uniq {
+            if distinct <= 1 {
+                diagnostics.push(ExprDiagnostic::MatchUniqOnSingleIndex {
+                    modifier: modifier.to_string(),
+                });
+            } else if uniq > distinct {
+                diagnostics.push(ExprDiagnostic::MatchUniqExceedsDistinct {
+                    modifier: modifier.to_string(),
+                    uniq,
+                    distinct,
+                });
+            }
+        }
+        if matches!(modifier.mod_op, ModOp::GreaterThan)
+            && distinct > 1
+            && modifier.match_req >= distinct
+            && is_all_and_group(element)
+        {
+            diagnostics.push(ExprDiagnostic::MatchReqExceedsAndGroup {
+                modifier: modifier.to_string(),
+                distinct,
+            });
+        }
+    }
+    for child in element.children() {
+        check_element(child.as_ref(), diagnostics);
+    }
+}
+
+/// Collect the distinct subsig indices referenced within an element,
+/// including nested expressions
+fn distinct_indices(element: &dyn Element) -> std::collections::BTreeSet<u8> {
+    let mut indices = std::collections::BTreeSet::new();
+    collect_indices(element, &mut indices);
+    indices
+}
+
+fn collect_indices(element: &dyn Element, indices: &mut std::collections::BTreeSet<u8>) {
+    if let Some(index) = element.sig_index() {
+        indices.insert(index);
+    }
+    for child in element.children() {
+        collect_indices(child.as_ref(), indices);
+    }
+}
+
+/// Whether an element is a group joined exclusively by `&` operations.
+/// Single-child wrappers (as produced when a modifier trails a
+/// parenthesized group) are transparent.
+fn is_all_and_group(element: &dyn Element) -> bool {
+    match element.children() {
+        [] => false,
+        [only] => is_all_and_group(only.as_ref()),
+        children => children
+            .iter()
+            .skip(1)
+            .all(|c| c.operation() == Some(Operation::And)),
+    }
+}
+
+/// Enforce operator placement as an element is added to a group: the first
+/// element must not carry an operation, and every subsequent element must.
+/// `op_pos` is the position of the pending operator (if any); `elem_pos` the
+/// position at which the element began.
+fn check_operator_placement(
+    is_first: bool,
+    op: Option<Operation>,
+    op_pos: Option<usize>,
+    elem_pos: usize,
+) -> Result<(), error::Parse> {
+    if is_first {
+        if op.is_some() {
+            return Err(error::Parse::LeadingOperator(
+                op_pos.unwrap_or(elem_pos).into(),
+            ));
+        }
+    } else if op.is_none() {
+        return Err(error::Parse::MissingOperator(elem_pos.into()));
+    }
+    Ok(())
+}
+
+/// A structural problem within a built expression tree that would cause it to
+/// serialize to an expression the engine rejects.  The parser cannot produce
+/// such trees; this covers trees assembled or rearranged programmatically.
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Hash)]
+pub enum StructureError {
+    /// The tree root or the first element of a group carries an `Operation`,
+    /// which `Display` would emit as a leading operator (e.g. `(&1|2)`)
+    #[error("first element of a group (or the tree root) carries an operator")]
+    LeadingOperator,
+
+    /// An element other than the first in its group carries no `Operation`,
+    /// so `Display` would run it together with the prior element
+    #[error("element follows another element without an operator")]
+    MissingOperator,
+}
+
+/// Check that operators within a built expression tree are structurally valid
+/// for serialization: the root and the first element of every group must
+/// carry no [`Operation`], and every subsequent element must carry one.
+/// `Display` emits operators exactly as stored, so a tree violating these
+/// rules serializes to an expression ClamAV rejects.
+pub fn validate_structure(element: &dyn Element) -> Result<(), StructureError> {
+    if element.operation().is_some() {
+        return Err(StructureError::LeadingOperator);
+    }
+    check_structure(element)
+}
+
+fn check_structure(element: &dyn Element) -> Result<(), StructureError> {
+    for (idx, child) in element.children().iter().enumerate() {
+        match (idx, child.operation()) {
+            (0, Some(_)) => return Err(StructureError::LeadingOperator),
+            (1.., None) => return Err(StructureError::MissingOperator),
+            _ => (),
+        }
+        check_structure(child.as_ref())?;
+    }
+    Ok(())
 }
 
 /*********************************************************************
@@ -287,16 +494,22 @@ where
     let mut elements = vec![];
     let mut modifier = None;
     let mut modval_pos = None;
+    // Positions of the pending operator and the start of the pending element,
+    // for operator-placement errors
+    let mut operation_pos = None;
+    let mut sig_pos = None;
 
     'handle_stream: loop {
         let b = byte_stream.next();
         'handle_byte: loop {
             match state {
                 State::Initial => match b {
-                    Some((_, b'(')) => {
+                    Some((pos, b'(')) => {
                         let mut element = parse_element(byte_stream, depth + 1)?;
                         // Apply the prior operation (if any)
-                        element.set_operation(operation.take());
+                        let op = operation.take();
+                        check_operator_placement(elements.is_empty(), op, operation_pos, pos)?;
+                        element.set_operation(op);
                         elements.push(element);
                     }
                     Some((_, b')')) => {
@@ -307,14 +520,24 @@ where
                         panic!("unmatched closing paren found");
                     }
                     // next digit
-                    Some((_, b)) if b.is_ascii_digit() => {
+                    Some((pos, b)) if b.is_ascii_digit() => {
+                        if sig_id.is_none() {
+                            sig_pos = Some(pos);
+                        }
                         sig_id = Some((b - b'0') + sig_id.unwrap_or_default() * 10);
                     }
                     // everything else
                     Some((pos, op)) if b.is_some() => {
                         if sig_id.is_some() {
+                            let this_op = operation.take();
+                            check_operator_placement(
+                                elements.is_empty(),
+                                this_op,
+                                operation_pos,
+                                sig_pos.take().unwrap_or(pos),
+                            )?;
                             let expr = Box::new(SigIndex {
-                                operation: operation.take(),
+                                operation: this_op,
                                 sig_index: sig_id.take().unwrap(),
                                 modifier: modifier.take(),
                             });
@@ -326,7 +549,11 @@ where
                                 return Err(error::Parse::UnexpectedOperator(pos.into()));
                             }
                             operation = Some(this_op);
+                            operation_pos = Some(pos);
                         } else if let Ok(this_modop) = ModOp::try_from(op) {
+                            if elements.is_empty() {
+                                return Err(error::Parse::ModifierOnNonExistentElement(pos.into()));
+                            }
                             mod_op = Some(this_modop);
                             state = State::ModReq;
                             modval_pos = None;
@@ -422,7 +649,7 @@ where
                             element.set_modifier(this_modifier);
                             // eprintln!("Applied modifier to last element  ({:?}", &element);
                         } else {
-                            panic!("Modifier with no prior expression");
+                            return Err(error::Parse::ModifierOnNonExistentElement(b.into()));
                         }
                     } else {
                         // eprintln!("Apply modifier to this expression (saving for later)");
@@ -438,8 +665,12 @@ where
     }
 
     if let Some(sig_id) = sig_id {
+        let op = operation.take();
+        if let Some(sig_pos) = sig_pos {
+            check_operator_placement(elements.is_empty(), op, operation_pos, sig_pos)?;
+        }
         let expr = Box::new(SigIndex {
-            operation: operation.take(),
+            operation: op,
             sig_index: sig_id,
             // modifier: modifier.take(),
             modifier: None,
@@ -458,6 +689,177 @@ where
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    fn diags(expr: &[u8]) -> Vec<ExprDiagnostic> {
+        let element: Box<dyn Element> = expr.try_into().unwrap();
+        validate(element.as_ref())
+    }
+
+    #[test]
+    fn modifier_without_preceding_element() {
+        // A leading modifier has nothing to modify; this must be reported
+        // as a parse error rather than a panic
+        let result: Result<Box<dyn Element>, _> = b">3".as_slice().try_into();
+        assert_eq!(
+            result.unwrap_err(),
+            error::Parse::ModifierOnNonExistentElement(error::Position::Relative(0))
+        );
+        let result: Result<Box<dyn Element>, _> = b">3&0".as_slice().try_into();
+        assert_eq!(
+            result.unwrap_err(),
+            error::Parse::ModifierOnNonExistentElement(error::Position::Relative(0))
+        );
+    }
+
+    #[test]
+    fn zero_match_modifiers_flagged() {
+        assert_eq!(
+            diags(b"(0|1)<1"),
+            vec![ExprDiagnostic::ZeroMatchModifier {
+                modifier: "<1".to_owned()
+            }]
+        );
+        assert_eq!(
+            diags(b"0=0"),
+            vec![ExprDiagnostic::ZeroMatchModifier {
+                modifier: "=0".to_owned()
+            }]
+        );
+        assert!(diags(b"(0|1)>0").is_empty());
+        assert!(diags(b"(0|1)=1").is_empty());
+    }
+
+    #[test]
+    fn match_uniq_exceeding_distinct_indices_flagged() {
+        assert_eq!(
+            diags(b"(0|1)>1,3"),
+            vec![ExprDiagnostic::MatchUniqExceedsDistinct {
+                modifier: ">1,3".to_owned(),
+                uniq: 3,
+                distinct: 2,
+            }]
+        );
+        assert!(diags(b"(0|1)>1,2").is_empty());
+    }
+
+    #[test]
+    fn match_uniq_on_single_index_flagged() {
+        // Modifier attached mid-expression to a bare index
+        assert_eq!(
+            diags(b"0=1,1&1"),
+            vec![ExprDiagnostic::MatchUniqOnSingleIndex {
+                modifier: "=1,1".to_owned()
+            }]
+        );
+        // ... or trailing, on an expression referencing a single index
+        assert_eq!(
+            diags(b"0>1,1"),
+            vec![ExprDiagnostic::MatchUniqOnSingleIndex {
+                modifier: ">1,1".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn excessive_threshold_on_and_group_flagged() {
+        assert_eq!(
+            diags(b"(0&1)>2"),
+            vec![ExprDiagnostic::MatchReqExceedsAndGroup {
+                modifier: ">2".to_owned(),
+                distinct: 2,
+            }]
+        );
+        assert!(diags(b"(0&1)>1").is_empty());
+        // `|` groups can plausibly accumulate more matches
+        assert!(diags(b"(0|1)>2").is_empty());
+    }
+
+    #[test]
+    fn leading_operator_rejected() {
+        let result: Result<Box<dyn Element>, _> = b"(&1|2)".as_slice().try_into();
+        assert_eq!(
+            result.unwrap_err(),
+            error::Parse::LeadingOperator(error::Position::Relative(1))
+        );
+        let result: Result<Box<dyn Element>, _> = b"&0|1".as_slice().try_into();
+        assert_eq!(
+            result.unwrap_err(),
+            error::Parse::LeadingOperator(error::Position::Relative(0))
+        );
+        // Leading operator on a nested group rather than a bare index
+        let result: Result<Box<dyn Element>, _> = b"(&(0|1))".as_slice().try_into();
+        assert_eq!(
+            result.unwrap_err(),
+            error::Parse::LeadingOperator(error::Position::Relative(1))
+        );
+    }
+
+    #[test]
+    fn doubled_operator_rejected() {
+        let result: Result<Box<dyn Element>, _> = b"0&&1".as_slice().try_into();
+        assert_eq!(
+            result.unwrap_err(),
+            error::Parse::UnexpectedOperator(error::Position::Relative(2))
+        );
+    }
+
+    #[test]
+    fn missing_operator_rejected() {
+        let result: Result<Box<dyn Element>, _> = b"(0|1)(2|3)".as_slice().try_into();
+        assert_eq!(
+            result.unwrap_err(),
+            error::Parse::MissingOperator(error::Position::Relative(5))
+        );
+        let result: Result<Box<dyn Element>, _> = b"(0|1)2".as_slice().try_into();
+        assert_eq!(
+            result.unwrap_err(),
+            error::Parse::MissingOperator(error::Position::Relative(5))
+        );
+    }
+
+    #[test]
+    fn structure_validation_catches_hand_built_leading_operator() {
+        // A tree the parser can no longer produce: the inner group's first
+        // element carries an `&`, so Display emits `(&1|2)`
+        let inner = Expr {
+            depth: 1,
+            operation: None,
+            elements: vec![
+                Box::new(SigIndex {
+                    operation: Some(Operation::And),
+                    sig_index: 1,
+                    modifier: None,
+                }),
+                Box::new(SigIndex {
+                    operation: Some(Operation::Or),
+                    sig_index: 2,
+                    modifier: None,
+                }),
+            ],
+            modifier: None,
+        };
+        let root = Expr {
+            depth: 0,
+            operation: None,
+            elements: vec![Box::new(inner)],
+            modifier: None,
+        };
+        assert_eq!(root.to_string(), "(&1|2)");
+        assert_eq!(
+            validate_structure(&root),
+            Err(StructureError::LeadingOperator)
+        );
+    }
+
+    #[test]
+    fn structure_validation_accepts_parsed_trees() {
+        for expr in [&b"(0&1)&(2|3)"[..], b"0&1", b"(0|1)>1,2&2"] {
+            let element: Box<dyn Element> = expr.try_into().unwrap();
+            assert_eq!(validate_structure(element.as_ref()), Ok(()));
+        }
+    }
+
     #[test]
     fn large_set() {
         // This test mainly confirms that expressions don't crash, and outputs
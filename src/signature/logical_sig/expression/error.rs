@@ -23,14 +23,14 @@ use crate::sigbytes::SigChar;
 
 /// A position within the expression to report the error, either relative to the
 /// start of the expression or at the end (after all characters have been processed)
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub enum Position {
     End,
     Relative(usize),
     Range(RangeInclusive<usize>),
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
 pub enum Parse {
     #[error("invalid character at {0}: {1}")]
     InvalidCharacter(Position, SigChar),
@@ -38,6 +38,12 @@ pub enum Parse {
     #[error("unexpected operator at {0}")]
     UnexpectedOperator(Position),
 
+    #[error("operator at {0} precedes the first element of its group")]
+    LeadingOperator(Position),
+
+    #[error("element at {0} follows another element without an operator")]
+    MissingOperator(Position),
+
     #[error("modifier value specified at {0} is too large")]
     ModifierMatchValueOverflow(Position),
 
@@ -46,6 +52,9 @@ pub enum Parse {
 
     #[error("modifier match requirement missing after modifier operator at {0}")]
     ModifierMatchReqMissing(Position),
+
+    #[error("modifier at {0} has no preceding element to modify")]
+    ModifierOnNonExistentElement(Position),
 }
 
 impl std::fmt::Display for Position {
@@ -86,3 +95,25 @@ impl From<RangeInclusive<usize>> for Position {
         Position::Range(range)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_error_display() {
+        let errors = [
+            Parse::InvalidCharacter(Position::Relative(3), SigChar::from(b'!')),
+            Parse::UnexpectedOperator(Position::Relative(1)),
+            Parse::LeadingOperator(Position::Relative(1)),
+            Parse::MissingOperator(Position::Relative(2)),
+            Parse::ModifierMatchValueOverflow(Position::Range(2..=5)),
+            Parse::ModifierMatchUniqMissing(Position::Relative(4)),
+            Parse::ModifierMatchReqMissing(Position::End),
+            Parse::ModifierOnNonExistentElement(Position::Relative(0)),
+        ];
+        for err in errors {
+            assert!(!err.to_string().is_empty(), "empty Display for {err:?}");
+        }
+    }
+}
@@ -41,7 +41,7 @@ use std::fmt::Write;
 use thiserror::Error;
 
 /// These are all boxed to avoid the overhead of the largest variation
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SubSigType {
     Extended,
     Macro,
@@ -83,6 +83,18 @@ impl AppendSigBytes for SubSigModifier {
 
 pub trait SubSig: std::fmt::Debug + EngineReq + AppendSigBytes + Downcast {
     fn subsig_type(&self) -> SubSigType;
+
+    /// Whether this sub-signature is a PCRE pattern.  PCRE sub-signatures
+    /// require engine feature level 81 or higher.
+    fn contains_pcre(&self) -> bool {
+        matches!(self.subsig_type(), SubSigType::Pcre)
+    }
+
+    /// The offset prefix attached to this sub-signature, if any.  Only body
+    /// and PCRE sub-signatures accept one.
+    fn offset(&self) -> Option<Offset> {
+        None
+    }
 }
 
 impl_downcast!(SubSig);
@@ -238,34 +250,59 @@ mod tests {
     fn test_fuzzy_img_valid() {
         let subsig_bytes = b"fuzzy_img#9900e66e77bb1c4c";
         let result = parse_bytes(subsig_bytes, None);
-        assert_eq!(result.is_ok(), true, "Expected valid fuzzy image subsig, got: {:?}", result);
+        assert_eq!(
+            result.is_ok(),
+            true,
+            "Expected valid fuzzy image subsig, got: {:?}",
+            result
+        );
     }
 
     #[test]
     fn test_fuzzy_img_valid_hamming() {
         let subsig_bytes = b"fuzzy_img#9900e66e77bb1c4c#5";
         let result = parse_bytes(subsig_bytes, None);
-        assert_eq!(result.is_ok(), true, "Expected valid fuzzy image subsig with hamming distance, got: {:?}", result);
+        assert_eq!(
+            result.is_ok(),
+            true,
+            "Expected valid fuzzy image subsig with hamming distance, got: {:?}",
+            result
+        );
     }
 
     #[test]
     fn test_fuzzy_img_invalid_short_hash() {
         let subsig_bytes = b"fuzzy_img#9900e66e77bb1";
         let result = parse_bytes(subsig_bytes, None);
-        assert_eq!(result.is_err(), true, "Expected invalid fuzzy image subsig, got: {:?}", result);
+        assert_eq!(
+            result.is_err(),
+            true,
+            "Expected invalid fuzzy image subsig, got: {:?}",
+            result
+        );
     }
 
     #[test]
     fn test_fuzzy_img_invalid_long_hash() {
         let subsig_bytes = b"fuzzy_img#9900e66e77bb1c4cfff";
         let result = parse_bytes(subsig_bytes, None);
-        assert_eq!(result.is_err(), true, "Expected invalid fuzzy image subsig, got: {:?}", result);
+        assert_eq!(
+            result.is_err(),
+            true,
+            "Expected invalid fuzzy image subsig, got: {:?}",
+            result
+        );
     }
 
     #[test]
     fn test_fuzzy_img_invalid_hamming() {
         let subsig_bytes = b"fuzzy_img#9900e66e77bb1c4c#a";
         let result = parse_bytes(subsig_bytes, None);
-        assert_eq!(result.is_err(), true, "Expected invalid fuzzy image subsig, got: {:?}", result);
+        assert_eq!(
+            result.is_err(),
+            true,
+            "Expected invalid fuzzy image subsig, got: {:?}",
+            result
+        );
     }
 }
@@ -42,7 +42,7 @@ pub struct ByteCmpSubSig {
     modifier: Option<SubSigModifier>,
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error, PartialEq, Eq)]
 pub enum ByteCmpSubSigParseError {
     #[error("missing closing parenthesis")]
     MissingClosingParen,
@@ -51,10 +51,10 @@ pub enum ByteCmpSubSigParseError {
     MissingSubSigIdTrigger,
 
     #[error("invalid subsigid_trigger: {0}")]
-    InvalidTrigger(ParseNumberError<u8>),
+    InvalidTrigger(#[source] ParseNumberError<u8>),
 
     #[error("invalid offset: {0}")]
-    InvalidOffset(ParseNumberError<isize>),
+    InvalidOffset(#[source] ParseNumberError<isize>),
 
     #[error("missing parameters")]
     MissingParameters,
@@ -138,6 +138,12 @@ impl AppendSigBytes for ByteCmpSubSig {
 }
 
 impl ByteCmpSubSig {
+    /// The index of the sub-signature whose match triggers this comparison
+    #[must_use]
+    pub fn subsigid_trigger(&self) -> u8 {
+        self.subsigid_trigger
+    }
+
     pub fn from_bytes(
         bytes: &[u8],
         modifier: Option<SubSigModifier>,
@@ -30,7 +30,7 @@ pub struct ByteOptions {
     extract_bytes: u8,
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
 pub enum ByteOptionsParseError {
     #[error("unrecognized byte option")]
     Unrecognized,
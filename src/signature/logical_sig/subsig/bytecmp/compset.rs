@@ -32,7 +32,7 @@ pub struct ComparisonSet {
     encoding: Encoding,
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error, PartialEq, Eq)]
 pub enum ComparisonSetParseError {
     #[error("comparison set empty")]
     Empty,
@@ -44,10 +44,10 @@ pub enum ComparisonSetParseError {
     UnknownOperator,
 
     #[error("parsing value: {0}")]
-    ParseValue(ParseNumberError<i64>),
+    ParseValue(#[source] ParseNumberError<i64>),
 
     #[error("parsing value: {0}")]
-    ParseHexValue(ParseNumberError<u64>),
+    ParseHexValue(#[source] ParseNumberError<u64>),
 
     #[error("parsing value: too large for i64")]
     TooLarge(#[from] TryFromIntError),
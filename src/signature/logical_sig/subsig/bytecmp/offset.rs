@@ -28,7 +28,7 @@ pub struct Offset {
     encoding: Encoding,
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
 pub enum ParseError {
     #[error("missing offset modifier")]
     MissingOffsetModifier,
@@ -34,7 +34,7 @@ pub struct MacroSubSig {
     modifier: Option<SubSigModifier>,
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
 pub enum MacroSubSigParseError {
     #[error("missing range")]
     MissingRange,
@@ -55,13 +55,13 @@ pub enum MacroSubSigParseError {
     MissingRangeMax,
 
     #[error("parsing macro_id: {0}")]
-    ParseMacroID(ParseNumberError<usize>),
+    ParseMacroID(#[source] ParseNumberError<usize>),
 
     #[error("parsing RangeMin: {0}")]
-    ParseRangeMin(ParseNumberError<usize>),
+    ParseRangeMin(#[source] ParseNumberError<usize>),
 
     #[error("parsing RangeMax: {0}")]
-    ParseRangeMax(ParseNumberError<usize>),
+    ParseRangeMax(#[source] ParseNumberError<usize>),
 }
 
 impl super::SubSigError for MacroSubSigParseError {
@@ -43,6 +43,10 @@ impl SubSig for PCRESubSig {
     fn subsig_type(&self) -> SubSigType {
         SubSigType::Pcre
     }
+
+    fn offset(&self) -> Option<crate::signature::ext_sig::Offset> {
+        self.offset
+    }
 }
 
 impl EngineReq for PCRESubSig {
@@ -107,7 +111,7 @@ pub enum PCRESubSigParseError {
     UnknownFlag,
 
     #[error("regexp not unicode: {0}")]
-    NotUnicode(str::Utf8Error),
+    NotUnicode(#[source] str::Utf8Error),
 
     #[error("parsing logical expression: {0}")]
     ParseLogExpr(#[from] expression::LogExprParseError),
@@ -132,6 +136,31 @@ impl super::SubSigError for PCRESubSigParseError {
 }
 
 impl PCRESubSig {
+    /// The regular expression this sub-signature matches, with source escapes
+    /// (slashes, semicolons) already resolved
+    #[must_use]
+    pub fn regexp(&self) -> &Match {
+        &self.regexp
+    }
+
+    /// The sub-signature indices referenced by this sub-signature's trigger
+    /// expression, in the order they appear
+    #[must_use]
+    pub fn trigger_sig_indices(&self) -> Vec<u8> {
+        fn collect(element: &dyn expression::Element, indices: &mut Vec<u8>) {
+            if let Some(sig_index) = element.sig_index() {
+                indices.push(sig_index);
+            }
+            for child in element.children() {
+                collect(child.as_ref(), indices);
+            }
+        }
+
+        let mut indices = vec![];
+        collect(self.trigger_expr.as_ref(), &mut indices);
+        indices
+    }
+
     pub fn from_bytes(
         bytes: &[u8],
         modifier: Option<SubSigModifier>,
@@ -26,19 +26,23 @@ use crate::{
     Feature,
 };
 use num_traits::{FromPrimitive, ToPrimitive};
-use std::{fmt::Write, str};
+use std::{collections::HashSet, fmt::Write, str};
 use thiserror::Error;
 
 // The minimum Engine (flevel) that must be present when the Engine attribute is
 // specified
 const MINIMUM_ENGINE_SPEC: u32 = 51;
 
+// The engine's limit on the length of an icon group name, as defined in the
+// icon (`.idb`) database
+const MAX_ICONGROUP_NAME_LEN: usize = 32;
+
 #[derive(Debug, Default, PartialEq)]
 pub struct TargetDesc {
     pub(crate) attrs: Vec<TargetDescAttr>,
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error, PartialEq, Eq)]
 pub enum TargetDescParseError {
     #[error("unknown TargetDescription attribute: {0}")]
     UnknownTargetDescAttr(SigBytes),
@@ -56,37 +60,37 @@ pub enum TargetDescParseError {
     UnknownFileType,
 
     #[error("parsing EngineRange")]
-    EngineRange(util::RangeInclusiveParseError<u32>),
+    EngineRange(#[source] util::RangeInclusiveParseError<u32>),
 
     #[error("parsing FileSize")]
-    FileSize(util::RangeInclusiveParseError<usize>),
+    FileSize(#[source] util::RangeInclusiveParseError<usize>),
 
     #[error("parsing EntryPoint")]
-    EntryPoint(util::RangeInclusiveParseError<usize>),
+    EntryPoint(#[source] util::RangeInclusiveParseError<usize>),
 
     #[error("parsing NumberOfSections")]
-    NumberOfSections(util::RangeInclusiveParseError<usize>),
+    NumberOfSections(#[source] util::RangeInclusiveParseError<usize>),
 
     #[error("parsing container value: {0}")]
-    Container(FileTypeParseError),
+    Container(#[source] FileTypeParseError),
 
     #[error("parsing Intermediate container element: {0}")]
-    IntermediateContainer(FileTypeParseError),
+    IntermediateContainer(#[source] FileTypeParseError),
 
     #[error("parsing container value: {0}")]
-    HandlerType(FileTypeParseError),
+    HandlerType(#[source] FileTypeParseError),
 
     #[error("parsing IconGroup1 value: {0}")]
-    IconGroup1(std::str::Utf8Error),
+    IconGroup1(#[source] std::str::Utf8Error),
 
     #[error("parsing IconGroup2 value: {0}")]
-    IconGroup2(std::str::Utf8Error),
+    IconGroup2(#[source] std::str::Utf8Error),
 
     #[error("parsing target_type: {0}")]
-    TargetType(ParseNumberError<usize>),
+    TargetType(#[source] ParseNumberError<usize>),
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
 pub enum TargetDescValidationError {
     #[error("Engine attribute present, but not first TargetDesc attribute")]
     EnginePresentNotFirst,
@@ -102,6 +106,109 @@ pub enum TargetDescValidationError {
 
     #[error("IconGroup1/2 requires PE Target (found {target_type:?})")]
     IconGroupRequiresTargetTypePE { target_type: Option<TargetType> },
+
+    #[error("{attr} value is empty")]
+    IconGroupEmpty { attr: &'static str },
+
+    #[error("{attr} value {value:?} contains disallowed character {c:?}")]
+    IconGroupDisallowedCharacter {
+        attr: &'static str,
+        value: String,
+        c: char,
+    },
+
+    #[error("{attr} value {value:?} exceeds the maximum icon group name length ({MAX_ICONGROUP_NAME_LEN})")]
+    IconGroupTooLong { attr: &'static str, value: String },
+
+    #[error("{attr} references icon group {value:?}, which is not defined in the icon database")]
+    IconGroupUnknown { attr: &'static str, value: String },
+
+    #[error("Engine range must carry a lower bound")]
+    EngineRangeForm,
+}
+
+/// What a sub-signature offset resolves against when a signature matches.
+/// See [`TargetDesc::resolution_context`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum OffsetResolutionContext {
+    /// Offsets resolve within the scanned file itself, of the given target
+    /// type (if specified)
+    Target(Option<TargetType>),
+
+    /// Offsets resolve within the object extracted from the innermost
+    /// container, which is of the given file type
+    Container(FileType),
+}
+
+impl OffsetResolutionContext {
+    /// Whether executable-relative offsets (entry-point- and
+    /// section-relative) can be resolved in this context.  Within a
+    /// container, this requires the extracted object itself to be a native
+    /// executable image.
+    #[must_use]
+    pub fn supports_exec_offsets(&self) -> bool {
+        match self {
+            Self::Target(target_type) => target_type.is_some_and(|t| t.is_native_executable()),
+            Self::Container(file_type) => file_type.is_native_executable(),
+        }
+    }
+
+    /// Whether `VI` (PE version information) offsets can be resolved in this
+    /// context, which requires PE specifically
+    #[must_use]
+    pub fn supports_pe_version_info(&self) -> bool {
+        match self {
+            Self::Target(target_type) => *target_type == Some(TargetType::PE),
+            Self::Container(file_type) => *file_type == FileType::CL_TYPE_MSEXE,
+        }
+    }
+}
+
+/// How [`TargetDesc::append_sigbytes_with_order`] arranges attributes on
+/// export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AttrExportOrder {
+    /// Reorder attributes into the documented canonical order (Engine,
+    /// Target, FileSize, EntryPoint, NumberOfSections, Container,
+    /// Intermediates, HandlerType, IconGroup1, IconGroup2).  This is the
+    /// default for programmatically-assembled descriptors, which may add
+    /// attributes in any order.
+    #[default]
+    Canonical,
+
+    /// Emit attributes exactly as held, refusing to serialize an arrangement
+    /// that fails validation
+    Validated,
+}
+
+impl TargetDesc {
+    /// Serialize as with [`AppendSigBytes`], but with control over attribute
+    /// ordering.  The blanket [`AppendSigBytes`] implementation preserves
+    /// the held order, which round-trips parsed signatures byte-for-byte.
+    pub fn append_sigbytes_with_order(
+        &self,
+        sb: &mut SigBytes,
+        order: AttrExportOrder,
+    ) -> Result<(), ToSigBytesError> {
+        match order {
+            AttrExportOrder::Canonical => {
+                let mut attrs: Vec<&TargetDescAttr> = self.attrs.iter().collect();
+                attrs.sort_by_key(|attr| attr.canonical_rank());
+                for (i, attr) in attrs.iter().enumerate() {
+                    if i > 0 {
+                        sb.write_char(',')?;
+                    }
+                    attr.append_sigbytes(sb)?;
+                }
+                Ok(())
+            }
+            AttrExportOrder::Validated => {
+                self.validate()
+                    .map_err(|e| ToSigBytesError::UnsupportedValue(e.to_string()))?;
+                self.append_sigbytes(sb)
+            }
+        }
+    }
 }
 
 impl AppendSigBytes for TargetDesc {
@@ -260,6 +367,77 @@ impl EngineReq for TargetDesc {
 }
 
 impl TargetDesc {
+    /// The `Engine` (feature level) range attribute, if specified
+    #[must_use]
+    pub fn engine(&self) -> Option<&Range<u32>> {
+        self.attrs.iter().find_map(|attr| match attr {
+            TargetDescAttr::Engine(range) => Some(range),
+            _ => None,
+        })
+    }
+
+    /// The `Target` (target type) attribute, if specified
+    #[must_use]
+    pub fn target_type(&self) -> Option<TargetType> {
+        self.attrs.iter().find_map(|attr| match attr {
+            TargetDescAttr::TargetType(target_type) => Some(*target_type),
+            _ => None,
+        })
+    }
+
+    /// The `FileSize` range attribute, if specified
+    #[must_use]
+    pub fn file_size(&self) -> Option<&Range<usize>> {
+        self.attrs.iter().find_map(|attr| match attr {
+            TargetDescAttr::FileSize(range) => Some(range),
+            _ => None,
+        })
+    }
+
+    /// Every file type this descriptor references via its `Container`,
+    /// `Intermediates` and `HandlerType` attributes, in attribute order
+    pub fn referenced_file_types(&self) -> impl Iterator<Item = &FileType> {
+        self.attrs.iter().flat_map(|attr| match attr {
+            TargetDescAttr::Container(file_type) | TargetDescAttr::HandlerType(file_type) => {
+                std::slice::from_ref(file_type).iter()
+            }
+            TargetDescAttr::Intermediates(file_types) => file_types.iter(),
+            _ => [].iter(),
+        })
+    }
+
+    /// The `Container` attribute, if specified
+    #[must_use]
+    pub fn container(&self) -> Option<FileType> {
+        self.attrs.iter().find_map(|attr| match attr {
+            TargetDescAttr::Container(file_type) => Some(file_type.clone()),
+            _ => None,
+        })
+    }
+
+    /// The context against which sub-signature offsets are resolved at match
+    /// time.  Ordinarily this is the scanned file itself, described by the
+    /// `Target` attribute.  When `Container` (or `Intermediates`) is present,
+    /// the engine applies the signature to an object extracted from the
+    /// innermost container, and `Absolute` and EOF-relative offsets refer to
+    /// positions and sizes within that extracted object rather than the outer
+    /// file.
+    #[must_use]
+    pub fn resolution_context(&self) -> OffsetResolutionContext {
+        if let Some(container) = self.container() {
+            OffsetResolutionContext::Container(container)
+        } else if let Some(innermost) = self.attrs.iter().find_map(|attr| match attr {
+            // Intermediates are listed outermost-first; the last entry is
+            // the direct container of the matched object
+            TargetDescAttr::Intermediates(file_types) => file_types.last().cloned(),
+            _ => None,
+        }) {
+            OffsetResolutionContext::Container(innermost)
+        } else {
+            OffsetResolutionContext::Target(self.target_type())
+        }
+    }
+
     pub(crate) fn validate(&self) -> Result<(), TargetDescValidationError> {
         self.validate_engine()?;
         self.validate_native_exec_attrs()?;
@@ -282,17 +460,20 @@ impl TargetDesc {
                 // Engine must be in first position when present
                 return Err(TargetDescValidationError::EnginePresentNotFirst);
             }
-            if let Range::Inclusive(range) = range {
-                // This is the only range variant currently used for Engine
-                if *range.start() < MINIMUM_ENGINE_SPEC {
-                    // Engine must be in first position when present
-                    return Err(TargetDescValidationError::EngineNotMinimum {
-                        found: *range.start(),
-                    });
+            // Engine ranges parsed from signatures are always `Inclusive`,
+            // but programmatic construction may produce the other forms
+            let start = match range {
+                Range::Inclusive(range) => *range.start(),
+                Range::From(range) => range.start,
+                Range::Exact(n) => *n,
+                // An upper bound alone says nothing about the minimum
+                // engine, which is the attribute's purpose
+                Range::ToInclusive(_) => {
+                    return Err(TargetDescValidationError::EngineRangeForm);
                 }
-            } else {
-                // No other range variants are used in Engine attrs
-                unreachable!();
+            };
+            if start < MINIMUM_ENGINE_SPEC {
+                return Err(TargetDescValidationError::EngineNotMinimum { found: start });
             }
         } else {
             // Engine attr not present. Any attrs incompatible with this?
@@ -337,8 +518,62 @@ impl TargetDesc {
         Ok(())
     }
 
-    // IconGroup1/2 are only allowed when the TargetType is "PE"
+    /// The `IconGroup1`/`IconGroup2` attribute values held in this
+    /// descriptor, each tagged with its attribute name
+    fn icongroup_values(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        self.attrs.iter().filter_map(|attr| match attr {
+            TargetDescAttr::IconGroup1(value) => Some(("IconGroup1", value.as_str())),
+            TargetDescAttr::IconGroup2(value) => Some(("IconGroup2", value.as_str())),
+            _ => None,
+        })
+    }
+
+    /// Cross-check `IconGroup1`/`IconGroup2` references against the set of
+    /// icon group names defined in an icon (`.idb`) database, flagging
+    /// references to groups that don't exist there.  This is separate from
+    /// the syntactic validation since the icon database isn't generally
+    /// available while parsing signatures.
+    pub fn validate_icongroup_refs(
+        &self,
+        known_groups: &HashSet<&str>,
+    ) -> Result<(), TargetDescValidationError> {
+        for (attr, value) in self.icongroup_values() {
+            if !known_groups.contains(value) {
+                return Err(TargetDescValidationError::IconGroupUnknown {
+                    attr,
+                    value: value.to_owned(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // IconGroup1/2 must follow the icon database naming rules (non-empty,
+    // limited length, no separators), and are only allowed when the
+    // TargetType is "PE"
     fn validate_icongroup(&self) -> Result<(), TargetDescValidationError> {
+        for (attr, value) in self.icongroup_values() {
+            if value.is_empty() {
+                return Err(TargetDescValidationError::IconGroupEmpty { attr });
+            }
+            if let Some(c) = value
+                .chars()
+                .find(|&c| c.is_whitespace() || c == ':' || c == ';')
+            {
+                return Err(TargetDescValidationError::IconGroupDisallowedCharacter {
+                    attr,
+                    value: value.to_owned(),
+                    c,
+                });
+            }
+            if value.len() > MAX_ICONGROUP_NAME_LEN {
+                return Err(TargetDescValidationError::IconGroupTooLong {
+                    attr,
+                    value: value.to_owned(),
+                });
+            }
+        }
+
         let mut found_icongroup = false;
         let mut target_type = None;
 
@@ -370,7 +605,7 @@ impl TargetDesc {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum TargetDescAttr {
     Engine(Range<u32>),
     TargetType(TargetType),
@@ -385,6 +620,41 @@ pub enum TargetDescAttr {
     IconGroup2(String),
 }
 
+impl TargetDescAttr {
+    /// This attribute's position in the documented canonical ordering used
+    /// by [`AttrExportOrder::Canonical`]
+    fn canonical_rank(&self) -> usize {
+        match self {
+            TargetDescAttr::Engine(_) => 0,
+            TargetDescAttr::TargetType(_) => 1,
+            TargetDescAttr::FileSize(_) => 2,
+            TargetDescAttr::EntryPoint(_) => 3,
+            TargetDescAttr::NumberOfSections(_) => 4,
+            TargetDescAttr::Container(_) => 5,
+            TargetDescAttr::Intermediates(_) => 6,
+            TargetDescAttr::HandlerType(_) => 7,
+            TargetDescAttr::IconGroup1(_) => 8,
+            TargetDescAttr::IconGroup2(_) => 9,
+        }
+    }
+
+    /// The keyword with which this attribute is spelled in a `TargetDesc`
+    pub(crate) fn keyword(&self) -> &'static str {
+        match self {
+            TargetDescAttr::Engine(_) => "Engine",
+            TargetDescAttr::TargetType(_) => "Target",
+            TargetDescAttr::FileSize(_) => "FileSize",
+            TargetDescAttr::EntryPoint(_) => "EntryPoint",
+            TargetDescAttr::NumberOfSections(_) => "NumberOfSections",
+            TargetDescAttr::Container(_) => "Container",
+            TargetDescAttr::Intermediates(_) => "Intermediates",
+            TargetDescAttr::HandlerType(_) => "HandlerType",
+            TargetDescAttr::IconGroup1(_) => "IconGroup1",
+            TargetDescAttr::IconGroup2(_) => "IconGroup2",
+        }
+    }
+}
+
 impl AppendSigBytes for TargetDescAttr {
     fn append_sigbytes(&self, sb: &mut SigBytes) -> Result<(), crate::signature::ToSigBytesError> {
         match self {
@@ -411,6 +681,7 @@ impl AppendSigBytes for TargetDescAttr {
                 write!(sb, "Container:{file_type}")?;
             }
             TargetDescAttr::Intermediates(file_types) => {
+                write!(sb, "Intermediates:")?;
                 for (i, file_type) in file_types.iter().enumerate() {
                     if i > 0 {
                         sb.write_char('>')?;
@@ -432,6 +703,62 @@ impl AppendSigBytes for TargetDescAttr {
 mod tests {
     use super::*;
 
+    #[test]
+    fn canonical_export_places_engine_first() {
+        // Engine added last, as a builder might
+        let desc = TargetDesc {
+            attrs: vec![
+                TargetDescAttr::TargetType(TargetType::PE),
+                TargetDescAttr::Engine((51..=255).into()),
+            ],
+        };
+
+        let mut sb = SigBytes::new();
+        desc.append_sigbytes_with_order(&mut sb, AttrExportOrder::default())
+            .unwrap();
+        assert_eq!(sb.to_string(), "Engine:51-255,Target:1");
+
+        // The exported text parses back and passes validation
+        let reparsed = TargetDesc::try_from(sb.as_bytes()).unwrap();
+        assert_eq!(reparsed.validate(), Ok(()));
+
+        // The validated order refuses the as-held arrangement
+        let mut sb = SigBytes::new();
+        assert!(desc
+            .append_sigbytes_with_order(&mut sb, AttrExportOrder::Validated)
+            .is_err());
+    }
+
+    #[test]
+    fn validate_engine_range_forms() {
+        fn desc_with_engine(range: Range<u32>) -> TargetDesc {
+            TargetDesc {
+                attrs: vec![TargetDescAttr::Engine(range)],
+            }
+        }
+
+        assert_eq!(desc_with_engine((51..=255).into()).validate(), Ok(()));
+        assert_eq!(desc_with_engine((81..).into()).validate(), Ok(()));
+        assert_eq!(desc_with_engine(Range::Exact(51)).validate(), Ok(()));
+        assert_eq!(
+            desc_with_engine((50..=255).into()).validate(),
+            Err(TargetDescValidationError::EngineNotMinimum { found: 50 })
+        );
+        assert_eq!(
+            desc_with_engine((50..).into()).validate(),
+            Err(TargetDescValidationError::EngineNotMinimum { found: 50 })
+        );
+        assert_eq!(
+            desc_with_engine(Range::Exact(1)).validate(),
+            Err(TargetDescValidationError::EngineNotMinimum { found: 1 })
+        );
+        // An upper bound alone carries no minimum engine level
+        assert_eq!(
+            desc_with_engine((..=255).into()).validate(),
+            Err(TargetDescValidationError::EngineRangeForm)
+        );
+    }
+
     #[test]
     fn intermediates_from_sigbytes() {
         let bytes = b"Intermediates:CL_TYPE_ZIP>CL_TYPE_RAR>CL_TYPE_GRAPHICS".as_ref();
@@ -461,7 +788,59 @@ mod tests {
         desc.append_sigbytes(&mut exported).unwrap();
         assert_eq!(
             exported.to_string(),
-            "CL_TYPE_ZIP>CL_TYPE_RAR>CL_TYPE_GRAPHICS"
+            "Intermediates:CL_TYPE_ZIP>CL_TYPE_RAR>CL_TYPE_GRAPHICS"
+        );
+    }
+
+    #[test]
+    fn export_each_attr_variant() {
+        fn exported(attr: &TargetDescAttr) -> String {
+            let mut sb = SigBytes::default();
+            attr.append_sigbytes(&mut sb).unwrap();
+            sb.to_string()
+        }
+        assert_eq!(
+            exported(&TargetDescAttr::Engine((51..=255).into())),
+            "Engine:51-255"
+        );
+        assert_eq!(
+            exported(&TargetDescAttr::TargetType(TargetType::PE)),
+            "Target:1"
+        );
+        assert_eq!(
+            exported(&TargetDescAttr::FileSize((1024..=4096).into())),
+            "FileSize:1024-4096"
+        );
+        assert_eq!(
+            exported(&TargetDescAttr::EntryPoint((0..=512).into())),
+            "EntryPoint:0-512"
+        );
+        assert_eq!(
+            exported(&TargetDescAttr::NumberOfSections((2..=8).into())),
+            "NumberOfSections:2-8"
+        );
+        assert_eq!(
+            exported(&TargetDescAttr::Container(FileType::CL_TYPE_ZIP)),
+            "Container:CL_TYPE_ZIP"
+        );
+        assert_eq!(
+            exported(&TargetDescAttr::Intermediates(vec![
+                FileType::CL_TYPE_ZIP,
+                FileType::CL_TYPE_RAR,
+            ])),
+            "Intermediates:CL_TYPE_ZIP>CL_TYPE_RAR"
+        );
+        assert_eq!(
+            exported(&TargetDescAttr::HandlerType(FileType::CL_TYPE_OOXML_XL)),
+            "HandlerType:CL_TYPE_OOXML_XL"
+        );
+        assert_eq!(
+            exported(&TargetDescAttr::IconGroup1("x".to_owned())),
+            "IconGroup1:x"
+        );
+        assert_eq!(
+            exported(&TargetDescAttr::IconGroup2("y".to_owned())),
+            "IconGroup2:y"
         );
     }
 
@@ -576,4 +955,90 @@ mod tests {
             Err(TargetDescValidationError::IconGroupRequiresTargetTypePE { target_type: None })
         );
     }
+
+    /// A PE-targeted descriptor referencing the given icon group, as the
+    /// icon-group naming tests require
+    fn pe_desc_with_group(group: &str) -> TargetDesc {
+        TargetDesc {
+            attrs: vec![
+                TargetDescAttr::Engine((51..=255).into()),
+                TargetDescAttr::TargetType(TargetType::PE),
+                TargetDescAttr::IconGroup1(group.into()),
+            ],
+        }
+    }
+
+    #[test]
+    fn icongroup_empty_rejected() {
+        assert_eq!(
+            pe_desc_with_group("").validate(),
+            Err(TargetDescValidationError::IconGroupEmpty { attr: "IconGroup1" })
+        );
+    }
+
+    #[test]
+    fn icongroup_separators_rejected() {
+        for (group, c) in [("bad group", ' '), ("bad:group", ':'), ("bad;group", ';')] {
+            assert_eq!(
+                pe_desc_with_group(group).validate(),
+                Err(TargetDescValidationError::IconGroupDisallowedCharacter {
+                    attr: "IconGroup1",
+                    value: group.into(),
+                    c,
+                })
+            );
+        }
+
+        // The attribute name in the error reflects which group failed
+        let desc = TargetDesc {
+            attrs: vec![
+                TargetDescAttr::Engine((51..=255).into()),
+                TargetDescAttr::TargetType(TargetType::PE),
+                TargetDescAttr::IconGroup1("ok".into()),
+                TargetDescAttr::IconGroup2("not ok".into()),
+            ],
+        };
+        assert_eq!(
+            desc.validate(),
+            Err(TargetDescValidationError::IconGroupDisallowedCharacter {
+                attr: "IconGroup2",
+                value: "not ok".into(),
+                c: ' ',
+            })
+        );
+    }
+
+    #[test]
+    fn icongroup_too_long_rejected() {
+        let group = "a".repeat(MAX_ICONGROUP_NAME_LEN + 1);
+        assert_eq!(
+            pe_desc_with_group(&group).validate(),
+            Err(TargetDescValidationError::IconGroupTooLong {
+                attr: "IconGroup1",
+                value: group,
+            })
+        );
+
+        // A name right at the limit is fine
+        assert_eq!(
+            pe_desc_with_group(&"a".repeat(MAX_ICONGROUP_NAME_LEN)).validate(),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn icongroup_dangling_reference() {
+        let known: HashSet<&str> = ["spam", "phish"].into();
+        assert_eq!(
+            pe_desc_with_group("phish").validate_icongroup_refs(&known),
+            Ok(())
+        );
+        assert_eq!(
+            pe_desc_with_group("fish").validate_icongroup_refs(&known),
+            Err(TargetDescValidationError::IconGroupUnknown {
+                attr: "IconGroup1",
+                value: "fish".into(),
+            })
+        );
+    }
 }
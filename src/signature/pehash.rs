@@ -21,6 +21,7 @@ use crate::{
     sigbytes::{AppendSigBytes, FromSigBytes, SigBytes},
     signature::{hash::ParseError, FromSigBytesParseError, SigMeta, Signature},
     util::{self, parse_field, parse_number_dec, Hash},
+    SigType,
 };
 use std::{fmt::Write, str};
 
@@ -36,15 +37,22 @@ impl Signature for PESectionHashSig {
     fn name(&self) -> &str {
         &self.name
     }
+
+    fn sig_type(&self) -> SigType {
+        SigType::PESectionHash
+    }
 }
 
 impl EngineReq for PESectionHashSig {
     fn features(&self) -> Set {
         Set::from_static(match (self.size, &self.hash) {
+            (None, Hash::Md5(_)) => &[Feature::HashSizeUnknown][..],
             (None, Hash::Sha1(_)) => &[Feature::HashSizeUnknown, Feature::HashSha1],
             (None, Hash::Sha2_256(_)) => &[Feature::HashSizeUnknown, Feature::HashSha256],
             (Some(_), Hash::Sha1(_)) => &[Feature::HashSha1],
             (Some(_), Hash::Sha2_256(_)) => &[Feature::HashSha256],
+            // MD5 section hashes with a known size are the original format
+            // and carry no feature requirement
             _ => return Set::default(),
         })
     }
@@ -79,11 +87,15 @@ impl FromSigBytes for PESectionHashSig {
             ParseError::MissingFileSize,
             ParseError::ParseSize
         )?;
-        let hash = util::parse_hash(fields.next().ok_or(ParseError::MissingField("hash_string".to_string()))?)
-            .map_err(ParseError::ParseHash)?;
-        let name = str::from_utf8(fields.next().ok_or(FromSigBytesParseError::MissingName)?)
-            .map_err(FromSigBytesParseError::NameNotUnicode)?
-            .to_owned();
+        let hash = util::parse_hash(
+            fields
+                .next()
+                .ok_or(ParseError::MissingField("hash_string".to_string()))?,
+        )
+        .map_err(ParseError::ParseHash)?;
+        let name =
+            super::parse_sig_name(fields.next().ok_or(FromSigBytesParseError::MissingName)?)?
+                .to_owned();
 
         // Parse optional min/max flevel
         if let Some(min_flevel) = fields.next() {
@@ -122,6 +134,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn export_with_flevel() {
+        let bytes = b"45056:f9b304ced34fcce3ab75c6dc58ad59e4d62177ffed35494f79f09bc4e8986c16:Win.Test.EICAR_MSB-1:74:255".into();
+        let (sig, sigmeta) = PESectionHashSig::from_sigbytes(&bytes).unwrap();
+        assert_eq!(sigmeta.f_level, Some((74..=255).into()));
+        let exported = sig.to_sigbytes_with_meta(&sigmeta).unwrap();
+        assert_eq!(&bytes, &exported);
+    }
+
+    #[test]
+    fn validate_flevel_by_hash_algorithm() {
+        // The original MD5 format carries no feature requirement
+        let bytes = b"45056:d41d8cd98f00b204e9800998ecf8427e:Win.Test.EICAR_MDB-1".into();
+        let (sig, sigmeta) = PESectionHashSig::from_sigbytes(&bytes).unwrap();
+        assert!(sig.validate(&sigmeta).is_ok());
+
+        // SHA2-256 section hashes require an flevel declaration
+        let bytes = b"45056:f9b304ced34fcce3ab75c6dc58ad59e4d62177ffed35494f79f09bc4e8986c16:Win.Test.EICAR_MSB-1".into();
+        let (sig, sigmeta) = PESectionHashSig::from_sigbytes(&bytes).unwrap();
+        assert!(matches!(
+            sig.validate(&sigmeta),
+            Err(
+                crate::signature::SigValidationError::MinFLevelNotSpecified {
+                    computed_min_flevel: 74,
+                    ..
+                }
+            )
+        ));
+
+        // ...as do wildcard sizes, even with an MD5 hash
+        let bytes = b"*:d41d8cd98f00b204e9800998ecf8427e:Win.Test.EICAR_MDB-1".into();
+        let (sig, sigmeta) = PESectionHashSig::from_sigbytes(&bytes).unwrap();
+        assert!(matches!(
+            sig.validate(&sigmeta),
+            Err(
+                crate::signature::SigValidationError::MinFLevelNotSpecified {
+                    computed_min_flevel: 73,
+                    ..
+                }
+            )
+        ));
+        let (sig, sigmeta) = PESectionHashSig::from_sigbytes(
+            &b"*:d41d8cd98f00b204e9800998ecf8427e:Win.Test.EICAR_MDB-1:73".into(),
+        )
+        .unwrap();
+        assert!(sig.validate(&sigmeta).is_ok());
+    }
+
     #[test]
     fn export() {
         let bytes = b"45056:f9b304ced34fcce3ab75c6dc58ad59e4d62177ffed35494f79f09bc4e8986c16:Win.Test.EICAR_MSB-1".into();
@@ -20,12 +20,13 @@ use crate::{
     feature::EngineReq,
     regexp,
     sigbytes::{AppendSigBytes, FromSigBytes, SigBytes},
-    signature::{SigMeta, ToSigBytesError},
+    signature::{SigMeta, ToSigBytesError, TrailingFieldMode},
     util::{
-        parse_field, parse_hash, parse_number_dec, parse_range_inclusive, string_from_bytes,
-        unescaped_element, Hash, ParseHashError, ParseNumberError, RangeInclusiveParseError,
+        parse_field, parse_hash_expecting, parse_number_dec, parse_range_inclusive,
+        string_from_bytes, unescaped_element, Hash, ParseHashError, ParseNumberError,
+        RangeInclusiveParseError, SHA2_256_LEN,
     },
-    Signature,
+    SigType, Signature,
 };
 use std::{fmt::Write, str};
 use thiserror::Error;
@@ -89,6 +90,26 @@ pub enum GSBPred {
     Hash(Hash),
 }
 
+impl GSBPred {
+    /// The raw host-prefix bytes, if this is a `P`-type predicate
+    #[must_use]
+    pub fn prefix_bytes(&self) -> Option<&[u8; 4]> {
+        match self {
+            GSBPred::HostPrefixHash(bytes) => Some(bytes),
+            GSBPred::Hash(_) => None,
+        }
+    }
+
+    /// The URL hash, if this is an `F`- or `W`-type predicate
+    #[must_use]
+    pub fn hash(&self) -> Option<&Hash> {
+        match self {
+            GSBPred::HostPrefixHash(_) => None,
+            GSBPred::Hash(hash) => Some(hash),
+        }
+    }
+}
+
 #[derive(Debug, Error, PartialEq)]
 pub enum ParseError {
     #[error("Missing preamble (first) field")]
@@ -104,19 +125,19 @@ pub enum ParseError {
     MissingDisplayedHostname,
 
     #[error("DisplayedHostname not unicode: {0}")]
-    DisplayedHostnameNotUnicode(std::str::Utf8Error),
+    DisplayedHostnameNotUnicode(#[source] std::str::Utf8Error),
 
     #[error("Missing RealURL field")]
     MissingRealUrl,
 
     #[error("Parsing RealURL field: {0}")]
-    RealUrlRegexpParse(regexp::ParseError),
+    RealUrlRegexpParse(#[source] regexp::ParseError),
 
     #[error("Missing DisplayedURL field")]
     MissingDisplayedUrl,
 
     #[error("Parsing DisplayedURL field: {0}")]
-    DisplayedUrlRegexpParse(regexp::ParseError),
+    DisplayedUrlRegexpParse(#[source] regexp::ParseError),
 
     #[error("Google Safe Browsing signature missing predicate type field")]
     MissingGSBPredType,
@@ -128,26 +149,61 @@ pub enum ParseError {
     AllowNotAllowed,
 
     #[error("Invalid Google Safe Browsing host prefix: {0}")]
-    InvalidGSBHostPrefix(hex::FromHexError),
+    InvalidGSBHostPrefix(#[source] hex::FromHexError),
 
-    #[error("Invalid Google Safe Browsing hash: {0}")]
-    InvalidGSBHash(ParseHashError),
+    #[error(
+        "Invalid Google Safe Browsing host prefix length: got {got} hex characters, expected {expected}"
+    )]
+    InvalidGSBHostPrefixLength { got: usize, expected: usize },
 
-    #[error("Invalid Google Safe Browsing hash size: must be SHA2-256")]
-    InvalidGSBHashType,
+    #[error("Invalid Google Safe Browsing hash: {0}")]
+    InvalidGSBHash(#[source] ParseHashError),
 
     #[error("Invalid Google Safe Browsing predicate type: {pred_type}")]
     InvalidPredicateType { pred_type: SigBytes },
 
     #[error("Parsing FuncLevelSpec range: {0}")]
-    FLevelRange(RangeInclusiveParseError<u32>),
+    FLevelRange(#[source] RangeInclusiveParseError<u32>),
 
     #[error("Parsing FuncLevelSpec minimum: {0}")]
-    FLevelMin(ParseNumberError<u32>),
+    FLevelMin(#[source] ParseNumberError<u32>),
+}
+
+/// Problems found when validating a phishing signature's hostname fields
+/// against RFC-1123 label rules
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
+pub enum ValidationError {
+    #[error("hostname `{host}` carries a URL scheme prefix")]
+    SchemePrefix { host: String },
+
+    #[error("hostname is empty")]
+    EmptyHostname,
+
+    #[error("hostname `{host}` has an empty label (doubled or trailing dot)")]
+    EmptyLabel { host: String },
+
+    #[error("hostname `{host}` label `{label}` exceeds 63 characters")]
+    LabelTooLong { host: String, label: String },
+
+    #[error("hostname `{host}` label `{label}` begins or ends with a hyphen")]
+    LabelHyphenEdge { host: String, label: String },
+
+    #[error("hostname `{host}` contains invalid character {c:?}")]
+    InvalidCharacter { host: String, c: char },
+
+    #[error("hostname `{host}` contains a path separator")]
+    PathInHostname { host: String },
+
+    #[error("hostname `{host}` has no dot; expected a fully-qualified host")]
+    MissingDot { host: String },
+
+    #[error("{which} URL regexp is empty")]
+    EmptyRegexp { which: &'static str },
 }
 
+/// The matching rule carried by a phishing signature, per database prefix
 #[derive(Debug)]
-pub enum PhishingSig {
+pub enum PhishingMatch {
     PDB(PDBMatch),
     GSB {
         match_type: GSBMatchType,
@@ -156,18 +212,181 @@ pub enum PhishingSig {
     WDB(WDBMatch),
 }
 
+#[derive(Debug)]
+pub struct PhishingSig {
+    /// The matching rule parsed from the record
+    pub matcher: PhishingMatch,
+    /// Fields beyond those the format defines, retained when parsing with
+    /// [`TrailingFieldMode::Preserve`] so that export doesn't lose them
+    trailing: Vec<SigBytes>,
+}
+
+impl From<PhishingMatch> for PhishingSig {
+    fn from(matcher: PhishingMatch) -> Self {
+        Self {
+            matcher,
+            trailing: vec![],
+        }
+    }
+}
+
+impl PhishingSig {
+    /// The hostname fields carried by this signature.  Only the `H` (PDB
+    /// displayed hostname) and `M` (WDB hostname pair) forms carry any;
+    /// regexp- and hash-based matches report nothing.
+    fn hostnames(&self) -> Vec<&str> {
+        match &self.matcher {
+            PhishingMatch::PDB(PDBMatch::DisplayedHostname(host)) => vec![host],
+            PhishingMatch::WDB(WDBMatch::MatchHostname { real, displayed }) => {
+                vec![real, displayed]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Lowercase the ASCII characters of this signature's hostname fields.
+    /// The engine compares hostnames after lowercasing, so uppercase entries
+    /// behave inconsistently; normalizing makes the stored form canonical.
+    pub fn normalize(&mut self) {
+        match &mut self.matcher {
+            PhishingMatch::PDB(PDBMatch::DisplayedHostname(host)) => host.make_ascii_lowercase(),
+            PhishingMatch::WDB(WDBMatch::MatchHostname { real, displayed }) => {
+                real.make_ascii_lowercase();
+                displayed.make_ascii_lowercase();
+            }
+            _ => (),
+        }
+    }
+
+    /// Fields beyond those the format defines, as retained by
+    /// [`TrailingFieldMode::Preserve`]
+    #[must_use]
+    pub fn trailing_fields(&self) -> &[SigBytes] {
+        &self.trailing
+    }
+}
+
+/// Check a hostname against RFC-1123 label rules.  A single leading `.`
+/// (matching any subdomain, as ClamAV permits) is allowed; a URL scheme
+/// prefix is not.  Non-ASCII (IDN) characters are accepted verbatim here and
+/// instead reported by [`PhishingSig::warnings`], which suggests the
+/// punycode (ACE) form.
+fn validate_hostname(host: &str) -> Result<(), ValidationError> {
+    if host.contains("://") {
+        return Err(ValidationError::SchemePrefix {
+            host: host.to_owned(),
+        });
+    }
+    if host.contains('/') {
+        return Err(ValidationError::PathInHostname {
+            host: host.to_owned(),
+        });
+    }
+    let labels = host.strip_prefix('.').unwrap_or(host);
+    if labels.is_empty() {
+        return Err(ValidationError::EmptyHostname);
+    }
+    for label in labels.split('.') {
+        if label.is_empty() {
+            return Err(ValidationError::EmptyLabel {
+                host: host.to_owned(),
+            });
+        }
+        if label.len() > 63 {
+            return Err(ValidationError::LabelTooLong {
+                host: host.to_owned(),
+                label: label.to_owned(),
+            });
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(ValidationError::LabelHyphenEdge {
+                host: host.to_owned(),
+                label: label.to_owned(),
+            });
+        }
+        if let Some(c) = label
+            .chars()
+            .find(|c| c.is_ascii() && !c.is_ascii_alphanumeric() && *c != '-')
+        {
+            return Err(ValidationError::InvalidCharacter {
+                host: host.to_owned(),
+                c,
+            });
+        }
+    }
+    Ok(())
+}
+
 impl Signature for PhishingSig {
     fn name(&self) -> &str {
         // Mostphishing signatures don't have names
-        match self {
+        match &self.matcher {
             // This is the only signature with a defined name
-            PhishingSig::GSB {
+            PhishingMatch::GSB {
                 match_type: GSBMatchType::PhishingBlock1,
                 ..
             } => "Phishing.URL.Blocked",
             _ => "?",
         }
     }
+
+    fn sig_type(&self) -> SigType {
+        SigType::PhishingURL
+    }
+
+    /// Phishing signatures require URL extraction and regexp matching, which
+    /// is costlier than a hash lookup but cheaper than body-sig scanning
+    fn estimated_scan_cost(&self) -> u64 {
+        50
+    }
+
+    fn validate_subelements(&self, _sigmeta: &SigMeta) -> Result<(), super::SigValidationError> {
+        fn check_regexp(re: &regexp::Match, which: &'static str) -> Result<(), ValidationError> {
+            if re.raw.is_empty() {
+                return Err(ValidationError::EmptyRegexp { which });
+            }
+            Ok(())
+        }
+
+        match &self.matcher {
+            PhishingMatch::PDB(PDBMatch::DisplayedHostname(host)) => {
+                validate_hostname(host).map_err(super::SigValidationError::PhishingSig)?;
+            }
+            PhishingMatch::WDB(WDBMatch::MatchHostname { real, displayed }) => {
+                for host in [real, displayed] {
+                    validate_hostname(host).map_err(super::SigValidationError::PhishingSig)?;
+                    // These fields name a specific host on each side, so a
+                    // bare single-label entry is almost certainly a mistake
+                    if !host.contains('.') {
+                        return Err(super::SigValidationError::PhishingSig(
+                            ValidationError::MissingDot { host: host.clone() },
+                        ));
+                    }
+                }
+            }
+            PhishingMatch::PDB(PDBMatch::Regexp(UrlRegexpPair { real, displayed }))
+            | PhishingMatch::WDB(WDBMatch::Regexp(UrlRegexpPair { real, displayed })) => {
+                check_regexp(real, "real").map_err(super::SigValidationError::PhishingSig)?;
+                check_regexp(displayed, "displayed")
+                    .map_err(super::SigValidationError::PhishingSig)?;
+            }
+            PhishingMatch::WDB(WDBMatch::RealOnly(real)) => {
+                check_regexp(real, "real").map_err(super::SigValidationError::PhishingSig)?;
+            }
+            PhishingMatch::GSB { .. } => (),
+        }
+        Ok(())
+    }
+
+    fn warnings(&self) -> Vec<super::SigWarning> {
+        self.hostnames()
+            .into_iter()
+            .filter(|host| !host.is_ascii())
+            .map(|host| super::SigWarning::NonAsciiHostname {
+                host: host.to_owned(),
+            })
+            .collect()
+    }
 }
 
 impl EngineReq for PhishingSig {
@@ -179,8 +398,8 @@ impl EngineReq for PhishingSig {
 
 impl AppendSigBytes for PhishingSig {
     fn append_sigbytes(&self, sb: &mut SigBytes) -> std::result::Result<(), ToSigBytesError> {
-        match self {
-            PhishingSig::PDB(psig) => match psig {
+        match &self.matcher {
+            PhishingMatch::PDB(psig) => match psig {
                 PDBMatch::Regexp(UrlRegexpPair { real, displayed }) => {
                     sb.write_str("R:")?;
                     real.append_sigbytes(sb)?;
@@ -191,7 +410,7 @@ impl AppendSigBytes for PhishingSig {
                     write!(sb, "H:{host}")?;
                 }
             },
-            PhishingSig::GSB { match_type, pred } => {
+            PhishingMatch::GSB { match_type, pred } => {
                 match match_type {
                     GSBMatchType::Malware | GSBMatchType::Allow => sb.write_str("S:")?,
                     GSBMatchType::PhishingBlock1 => sb.write_str("S1:")?,
@@ -212,7 +431,7 @@ impl AppendSigBytes for PhishingSig {
                     }
                 }
             }
-            PhishingSig::WDB(wsig) => match wsig {
+            PhishingMatch::WDB(wsig) => match wsig {
                 WDBMatch::Regexp(UrlRegexpPair { real, displayed }) => {
                     sb.write_str("X:")?;
                     real.append_sigbytes(sb)?;
@@ -229,22 +448,32 @@ impl AppendSigBytes for PhishingSig {
             },
         }
 
+        for t in &self.trailing {
+            write!(sb, ":{t}")?;
+        }
+
         Ok(())
     }
 }
 
-impl FromSigBytes for PhishingSig {
-    fn from_sigbytes<'a, SB: Into<&'a SigBytes>>(
+impl PhishingSig {
+    /// As [`from_sigbytes`](FromSigBytes::from_sigbytes), but with fields
+    /// beyond the optional flevel handled per `trailing_mode` rather than
+    /// always rejected
+    pub fn from_sigbytes_with_mode<'a, SB: Into<&'a SigBytes>>(
         sb: SB,
+        trailing_mode: TrailingFieldMode,
     ) -> Result<(Box<dyn Signature>, super::SigMeta), super::FromSigBytesParseError> {
         let mut sigmeta = SigMeta::default();
-        let mut fields = sb.into().as_bytes().split(unescaped_element(b'\\', b':'));
+        let sb = sb.into();
+        let total_fields = sb.as_bytes().split(unescaped_element(b'\\', b':')).count();
+        let mut fields = sb.as_bytes().split(unescaped_element(b'\\', b':'));
 
         let prefix = fields.next().ok_or(ParseError::MissingPreamble)?;
 
         // `R` and `H` may include a filter which is (per specification) ignored
-        let sig = if prefix.starts_with(b"R") {
-            Ok(PhishingSig::PDB(PDBMatch::Regexp(make_url_regexp_pair(
+        let matcher = if prefix.starts_with(b"R") {
+            Ok(PhishingMatch::PDB(PDBMatch::Regexp(make_url_regexp_pair(
                 &mut fields,
             )?)))
         } else if prefix.starts_with(b"H") {
@@ -265,16 +494,21 @@ impl FromSigBytes for PhishingSig {
                     let pred = match pred_type {
                         b"P" => {
                             let mut bytes = [0; 4];
+                            if pred_str.len() != bytes.len() * 2 {
+                                return Err(ParseError::InvalidGSBHostPrefixLength {
+                                    got: pred_str.len(),
+                                    expected: bytes.len() * 2,
+                                }
+                                .into());
+                            }
                             hex::decode_to_slice(pred_str, &mut bytes)
                                 .map_err(ParseError::InvalidGSBHostPrefix)?;
                             GSBPred::HostPrefixHash(bytes)
                         }
                         // These both contain the same hash field type
                         b"F" | b"W" => {
-                            let hash = parse_hash(pred_str).map_err(ParseError::InvalidGSBHash)?;
-                            if !matches!(hash, Hash::Sha2_256(_)) {
-                                return Err(ParseError::InvalidGSBHashType.into());
-                            }
+                            let hash = parse_hash_expecting(pred_str, SHA2_256_LEN)
+                                .map_err(ParseError::InvalidGSBHash)?;
                             // Special handling for allow type
                             if pred_type == b"W" {
                                 // Override the match type as an "allow" type
@@ -294,9 +528,9 @@ impl FromSigBytes for PhishingSig {
                             .into())
                         }
                     };
-                    Ok(PhishingSig::GSB { match_type, pred })
+                    Ok(PhishingMatch::GSB { match_type, pred })
                 }
-                b"X" => Ok(PhishingSig::WDB(WDBMatch::Regexp(make_url_regexp_pair(
+                b"X" => Ok(PhishingMatch::WDB(WDBMatch::Regexp(make_url_regexp_pair(
                     &mut fields,
                 )?))),
                 b"M" => make_wdbmatch_hostname(&mut fields),
@@ -319,7 +553,17 @@ impl FromSigBytes for PhishingSig {
             }
         }
 
-        Ok((Box::new(sig), sigmeta))
+        let trailing = super::take_trailing_fields(fields, total_fields, trailing_mode)?;
+
+        Ok((Box::new(PhishingSig { matcher, trailing }), sigmeta))
+    }
+}
+
+impl FromSigBytes for PhishingSig {
+    fn from_sigbytes<'a, SB: Into<&'a SigBytes>>(
+        sb: SB,
+    ) -> Result<(Box<dyn Signature>, super::SigMeta), super::FromSigBytesParseError> {
+        Self::from_sigbytes_with_mode(sb, TrailingFieldMode::Error)
     }
 }
 
@@ -343,19 +587,19 @@ fn make_url_regexp_pair<'a, I: Iterator<Item = &'a [u8]>>(
 
 fn make_pdbmatch_hostname<'a, I: Iterator<Item = &'a [u8]>>(
     fields: &mut I,
-) -> Result<PhishingSig, ParseError> {
+) -> Result<PhishingMatch, ParseError> {
     let hostname = parse_field!(
         fields,
         string_from_bytes,
         ParseError::MissingDisplayedHostname,
         ParseError::DisplayedHostnameNotUnicode
     )?;
-    Ok(PhishingSig::PDB(PDBMatch::DisplayedHostname(hostname)))
+    Ok(PhishingMatch::PDB(PDBMatch::DisplayedHostname(hostname)))
 }
 
 fn make_wdbmatch_hostname<'a, I: Iterator<Item = &'a [u8]>>(
     fields: &mut I,
-) -> Result<PhishingSig, ParseError> {
+) -> Result<PhishingMatch, ParseError> {
     let real = parse_field!(
         fields,
         string_from_bytes,
@@ -368,7 +612,7 @@ fn make_wdbmatch_hostname<'a, I: Iterator<Item = &'a [u8]>>(
         ParseError::MissingDisplayedHostname,
         ParseError::DisplayedHostnameNotUnicode
     )?;
-    Ok(PhishingSig::WDB(WDBMatch::MatchHostname {
+    Ok(PhishingMatch::WDB(WDBMatch::MatchHostname {
         real,
         displayed,
     }))
@@ -376,14 +620,14 @@ fn make_wdbmatch_hostname<'a, I: Iterator<Item = &'a [u8]>>(
 
 fn make_wdbmatch_real_only<'a, I: Iterator<Item = &'a [u8]>>(
     fields: &mut I,
-) -> Result<PhishingSig, ParseError> {
+) -> Result<PhishingMatch, ParseError> {
     let real = parse_field!(
         fields,
         regexp::Match::try_from,
         ParseError::MissingRealUrl,
         ParseError::RealUrlRegexpParse
     )?;
-    Ok(PhishingSig::WDB(WDBMatch::RealOnly(real)))
+    Ok(PhishingMatch::WDB(WDBMatch::RealOnly(real)))
 }
 
 #[cfg(test)]
@@ -406,7 +650,10 @@ mod tests {
             }
         );
         let sig = sig.downcast_ref::<PhishingSig>().unwrap();
-        assert!(matches!(sig, PhishingSig::PDB(PDBMatch::Regexp { .. })));
+        assert!(matches!(
+            sig.matcher,
+            PhishingMatch::PDB(PDBMatch::Regexp { .. })
+        ));
     }
 
     #[test]
@@ -415,7 +662,10 @@ mod tests {
         let (sig, sigmeta) = PhishingSig::from_sigbytes(&input).unwrap();
         assert_eq!(sigmeta, SigMeta::default(),);
         let sig = sig.downcast_ref::<PhishingSig>().unwrap();
-        assert!(matches!(sig, PhishingSig::PDB(PDBMatch::Regexp { .. })));
+        assert!(matches!(
+            sig.matcher,
+            PhishingMatch::PDB(PDBMatch::Regexp { .. })
+        ));
     }
 
     #[test]
@@ -454,8 +704,8 @@ mod tests {
         );
         let sig = sig.downcast_ref::<PhishingSig>().unwrap();
         assert!(matches!(
-            sig,
-            PhishingSig::GSB {
+            sig.matcher,
+            PhishingMatch::GSB {
                 match_type: GSBMatchType::Malware,
                 pred: GSBPred::HostPrefixHash(_)
             }
@@ -469,8 +719,8 @@ mod tests {
         assert_eq!(sigmeta, SigMeta::default());
         let sig = sig.downcast_ref::<PhishingSig>().unwrap();
         assert!(matches!(
-            sig,
-            PhishingSig::GSB {
+            sig.matcher,
+            PhishingMatch::GSB {
                 match_type: GSBMatchType::Allow,
                 pred: GSBPred::Hash(_)
             }
@@ -490,8 +740,8 @@ mod tests {
         );
         let sig = sig.downcast_ref::<PhishingSig>().unwrap();
         assert!(matches!(
-            sig,
-            PhishingSig::GSB {
+            sig.matcher,
+            PhishingMatch::GSB {
                 match_type: GSBMatchType::PhishingBlock1,
                 pred: GSBPred::Hash(_),
             }
@@ -505,8 +755,8 @@ mod tests {
         assert_eq!(sigmeta, SigMeta::default());
         let sig = sig.downcast_ref::<PhishingSig>().unwrap();
         assert!(matches!(
-            sig,
-            PhishingSig::GSB {
+            sig.matcher,
+            PhishingMatch::GSB {
                 match_type: GSBMatchType::PhishingBlock2,
                 pred: GSBPred::HostPrefixHash(_)
             }
@@ -545,11 +795,74 @@ mod tests {
         assert!(matches!(
             result,
             Err(FromSigBytesParseError::PhishingSig(
-                ParseError::InvalidGSBHashType
+                ParseError::InvalidGSBHash(ParseHashError::WrongAlgorithm {
+                    expected_len: SHA2_256_LEN,
+                    found_len: crate::util::MD5_LEN,
+                })
+            ))
+        ));
+    }
+
+    #[test]
+    fn gsb_host_prefix_wrong_length() {
+        // 7 and 9 hex characters: reported as a length problem, not a hex one
+        for input in [&br"S:P:fdcbe05"[..], &br"S:P:fdcbe0541"[..]] {
+            let result = PhishingSig::from_sigbytes(&input.into());
+            assert!(matches!(
+                result,
+                Err(FromSigBytesParseError::PhishingSig(
+                    ParseError::InvalidGSBHostPrefixLength {
+                        got: 7 | 9,
+                        expected: 8
+                    }
+                ))
+            ));
+        }
+
+        // Correct length but non-hex characters
+        let input = br"S:P:fdcbe0zz".into();
+        let result = PhishingSig::from_sigbytes(&input);
+        assert!(matches!(
+            result,
+            Err(FromSigBytesParseError::PhishingSig(
+                ParseError::InvalidGSBHostPrefix(_)
             ))
         ));
     }
 
+    #[test]
+    fn gsb_pred_accessors() {
+        let (sig, _) = PhishingSig::from_sigbytes(&br"S:P:fdcbe054".into()).unwrap();
+        let sig = sig.downcast_ref::<PhishingSig>().unwrap();
+        let PhishingMatch::GSB { pred, .. } = &sig.matcher else {
+            panic!("expected GSB signature");
+        };
+        assert_eq!(pred.prefix_bytes(), Some(&[0xfd, 0xcb, 0xe0, 0x54]));
+        assert_eq!(pred.hash(), None);
+
+        let (sig, _) = PhishingSig::from_sigbytes(
+            &br"S:F:00111810e04eaf02975558467f74ec430ee0698a6d82bed1ff7a0fd772dfe863".into(),
+        )
+        .unwrap();
+        let sig = sig.downcast_ref::<PhishingSig>().unwrap();
+        let PhishingMatch::GSB { pred, .. } = &sig.matcher else {
+            panic!("expected GSB signature");
+        };
+        assert_eq!(pred.prefix_bytes(), None);
+        assert!(pred.hash().is_some());
+    }
+
+    #[test]
+    fn gsb_host_prefix_exports_lowercase_hex() {
+        // The export path relies on `[u8]::append_sigbytes` emitting lowercase
+        // hex; pin that so the round-trip stays strict
+        let sig = PhishingSig::from(PhishingMatch::GSB {
+            match_type: GSBMatchType::Malware,
+            pred: GSBPred::HostPrefixHash([0xfd, 0xcb, 0xe0, 0x54]),
+        });
+        assert_eq!(sig.to_sigbytes().unwrap().to_string(), "S:P:fdcbe054");
+    }
+
     #[test]
     fn gsb_invalid_pred_type() {
         let input =
@@ -565,13 +878,15 @@ mod tests {
 
     #[test]
     fn names() {
-        let sig = PhishingSig::GSB {
+        let sig = PhishingSig::from(PhishingMatch::GSB {
             match_type: GSBMatchType::PhishingBlock1,
             pred: GSBPred::HostPrefixHash([0; 4]),
-        };
+        });
         assert_eq!(sig.name(), "Phishing.URL.Blocked");
 
-        let sig = PhishingSig::PDB(PDBMatch::DisplayedHostname("example.com".into()));
+        let sig = PhishingSig::from(PhishingMatch::PDB(PDBMatch::DisplayedHostname(
+            "example.com".into(),
+        )));
         assert_eq!(sig.name(), "?");
     }
 
@@ -593,6 +908,129 @@ mod tests {
         let input = br"S2:P:e5172364".into();
         let (sig, _) = PhishingSig::from_sigbytes(&input).unwrap();
         assert_eq!(sig.to_sigbytes().unwrap(), input);
+
+        // PDB regexp pair
+        let input = br"R:.*\.com:.*\.org".into();
+        let (sig, _) = PhishingSig::from_sigbytes(&input).unwrap();
+        assert_eq!(sig.to_sigbytes().unwrap(), input);
+
+        // PDB displayed hostname
+        let input = br"H:example.com".into();
+        let (sig, _) = PhishingSig::from_sigbytes(&input).unwrap();
+        assert_eq!(sig.to_sigbytes().unwrap(), input);
+
+        // WDB regexp pair
+        let input = br"X:.*\.example\.com:.*\.example\.org".into();
+        let (sig, _) = PhishingSig::from_sigbytes(&input).unwrap();
+        assert_eq!(sig.to_sigbytes().unwrap(), input);
+
+        // WDB hostname pair
+        let input = br"M:real.example.com:displayed.example.com".into();
+        let (sig, _) = PhishingSig::from_sigbytes(&input).unwrap();
+        assert_eq!(sig.to_sigbytes().unwrap(), input);
+
+        // WDB real-only regexp
+        let input = br"Y:.*\.malicious\.com".into();
+        let (sig, _) = PhishingSig::from_sigbytes(&input).unwrap();
+        assert_eq!(sig.to_sigbytes().unwrap(), input);
+    }
+
+    #[test]
+    fn hostname_validation() {
+        fn validate(sig: &[u8]) -> Result<(), crate::signature::SigValidationError> {
+            let (sig, sigmeta) = PhishingSig::from_sigbytes(&sig.into()).unwrap();
+            sig.validate(&sigmeta)
+        }
+
+        // A leading wildcard `.` is fine, as are hyphens within labels
+        assert!(validate(b"H:.example-site.com").is_ok());
+        assert!(validate(b"M:real.example.com:displayed.example.com").is_ok());
+
+        // Scheme prefixes are refused outright
+        assert!(matches!(
+            validate(br"H:http\://example.com"),
+            Err(crate::signature::SigValidationError::PhishingSig(
+                ValidationError::SchemePrefix { .. }
+            ))
+        ));
+        // Embedded spaces
+        assert!(matches!(
+            validate(b"H:exam ple.com"),
+            Err(crate::signature::SigValidationError::PhishingSig(
+                ValidationError::InvalidCharacter { c: ' ', .. }
+            ))
+        ));
+        // Trailing dot yields an empty final label
+        assert!(matches!(
+            validate(b"H:example.com."),
+            Err(crate::signature::SigValidationError::PhishingSig(
+                ValidationError::EmptyLabel { .. }
+            ))
+        ));
+        // Hyphen at a label edge
+        assert!(matches!(
+            validate(b"M:-bad.example.com:displayed.example.com"),
+            Err(crate::signature::SigValidationError::PhishingSig(
+                ValidationError::LabelHyphenEdge { .. }
+            ))
+        ));
+        // Uppercase is tolerated by validation (normalization lowercases it)
+        assert!(validate(b"H:EXAMPLE.COM").is_ok());
+        // Path separators are refused
+        assert!(matches!(
+            validate(b"H:example.com/login"),
+            Err(crate::signature::SigValidationError::PhishingSig(
+                ValidationError::PathInHostname { .. }
+            ))
+        ));
+        // Hostname-pair fields must be fully qualified
+        assert!(matches!(
+            validate(b"M:localhost:displayed.example.com"),
+            Err(crate::signature::SigValidationError::PhishingSig(
+                ValidationError::MissingDot { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn empty_regexp_fails_validation() {
+        let sig = PhishingSig::from(PhishingMatch::WDB(WDBMatch::RealOnly(regexp::Match {
+            raw: vec![],
+        })));
+        assert!(matches!(
+            sig.validate(&SigMeta::default()),
+            Err(crate::signature::SigValidationError::PhishingSig(
+                ValidationError::EmptyRegexp { which: "real" }
+            ))
+        ));
+    }
+
+    #[test]
+    fn hostname_normalization_lowercases_ascii() {
+        let (sig, _) =
+            PhishingSig::from_sigbytes(&b"M:Real.Example.COM:Displayed.Example.COM"[..].into())
+                .unwrap();
+        let mut sig = sig.downcast::<PhishingSig>().unwrap();
+        sig.normalize();
+        assert_eq!(
+            sig.to_sigbytes().unwrap().to_string(),
+            "M:real.example.com:displayed.example.com"
+        );
+    }
+
+    #[test]
+    fn idn_hostname_warned_but_accepted() {
+        let (sig, sigmeta) = PhishingSig::from_sigbytes(&"H:bücher.example".into()).unwrap();
+        assert!(sig.validate(&sigmeta).is_ok());
+        assert_eq!(
+            sig.warnings(),
+            vec![crate::signature::SigWarning::NonAsciiHostname {
+                host: "bücher.example".to_owned()
+            }]
+        );
+        // The ACE form raises no warning
+        let (sig, _) = PhishingSig::from_sigbytes(&"H:xn--bcher-kva.example".into()).unwrap();
+        assert!(sig.warnings().is_empty());
     }
 
     #[test]
@@ -601,7 +1039,10 @@ mod tests {
         let (sig, sigmeta) = PhishingSig::from_sigbytes(&input).unwrap();
         assert_eq!(sigmeta, SigMeta::default());
         let sig = sig.downcast_ref::<PhishingSig>().unwrap();
-        assert!(matches!(sig, PhishingSig::WDB(WDBMatch::RealOnly(_))));
+        assert!(matches!(
+            sig.matcher,
+            PhishingMatch::WDB(WDBMatch::RealOnly(_))
+        ));
     }
 
     #[test]
@@ -615,7 +1056,10 @@ mod tests {
             }
         );
         let sig = sig.downcast_ref::<PhishingSig>().unwrap();
-        assert!(matches!(sig, PhishingSig::WDB(WDBMatch::RealOnly(_))));
+        assert!(matches!(
+            sig.matcher,
+            PhishingMatch::WDB(WDBMatch::RealOnly(_))
+        ));
     }
 
     #[test]
@@ -624,4 +1068,58 @@ mod tests {
         let (sig, _) = PhishingSig::from_sigbytes(&input).unwrap();
         assert_eq!(sig.to_sigbytes().unwrap(), input);
     }
+
+    #[test]
+    fn trailing_fields_rejected_by_default() {
+        let input = br"R:.*\.com:.*\.org:99-105:extra".into();
+        let result = PhishingSig::from_sigbytes(&input);
+        assert!(matches!(
+            result,
+            Err(FromSigBytesParseError::TrailingFields {
+                count: 1,
+                first_pos: 4
+            })
+        ));
+
+        let input = br"R:.*\.com:.*\.org:99-105:one:two:three".into();
+        let result = PhishingSig::from_sigbytes(&input);
+        assert!(matches!(
+            result,
+            Err(FromSigBytesParseError::TrailingFields {
+                count: 3,
+                first_pos: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn trailing_fields_preserved_when_permitted() {
+        for (input, expected_count, expected_export) in [
+            (
+                &br"R:.*\.com:.*\.org:99-105:extra"[..],
+                1,
+                r"R:.*\.com:.*\.org:extra",
+            ),
+            (
+                &br"R:.*\.com:.*\.org:99-105:one:two:three"[..],
+                3,
+                r"R:.*\.com:.*\.org:one:two:three",
+            ),
+        ] {
+            let (sig, sigmeta) =
+                PhishingSig::from_sigbytes_with_mode(&input.into(), TrailingFieldMode::Preserve)
+                    .unwrap();
+            // The flevel field is still consumed as metadata; only fields
+            // beyond it are trailing
+            assert_eq!(
+                sigmeta,
+                SigMeta {
+                    f_level: Some((99..=105).into()),
+                }
+            );
+            let psig = sig.downcast_ref::<PhishingSig>().unwrap();
+            assert_eq!(psig.trailing_fields().len(), expected_count);
+            assert_eq!(sig.to_sigbytes().unwrap().to_string(), expected_export);
+        }
+    }
 }
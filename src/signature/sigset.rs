@@ -0,0 +1,793 @@
+/*
+ *  Copyright (C) 2024 Cisco Systems, Inc. and/or its affiliates. All rights reserved.
+ *
+ *  This program is free software; you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License version 2 as
+ *  published by the Free Software Foundation.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program; if not, write to the Free Software
+ *  Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston,
+ *  MA 02110-1301, USA.
+ */
+
+/// Shared-storage (interning) backing for large sets
+pub mod intern;
+
+use crate::{
+    feature::EngineReq,
+    filetype::FileType,
+    signature::{
+        bodysig::BodySigFingerprint, ext_sig::ExtendedSig, logical_sig::LogicalSig,
+        targettype::TargetType, Complexity,
+    },
+    util::Range,
+    SigType, Signature,
+};
+use intern::{BodyStore, StringInterner};
+use std::collections::HashMap;
+
+/// An ordered collection of parsed signatures, as would be obtained from a
+/// single database file.
+#[derive(Debug, Default)]
+pub struct SigSet {
+    sigs: Vec<Box<dyn Signature>>,
+    /// Shared storage for name and file-type strings across the set
+    names: StringInterner,
+    /// Shared storage for recurring subsig body content
+    bodies: BodyStore,
+}
+
+/// Approximate memory usage of a [`SigSet`], by category, as reported by
+/// [`SigSet::memory_stats`].  "Owned" figures count the string/body content
+/// each signature holds for itself; the interned/shared figures describe the
+/// set's deduplicated storage, with the difference being the duplication
+/// that interning avoids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// Bytes of signature-name content, as owned per signature
+    pub name_bytes: usize,
+    /// Bytes of distinct name and file-type string content, interned
+    pub interned_string_bytes: usize,
+    /// Bytes interning avoided duplicating (recurring names and `CL_TYPE_*`
+    /// strings)
+    pub interned_bytes_saved: usize,
+    /// Bytes of body-signature content (canonical serialization), as owned
+    /// per signature
+    pub body_bytes: usize,
+    /// Bytes of distinct body content, shared by fingerprint
+    pub shared_body_bytes: usize,
+    /// Bytes body-sharing avoided duplicating
+    pub body_bytes_saved: usize,
+}
+
+/// The engine feature-level range implied by a set's content, as computed by
+/// [`SigSet::required_flevel`]
+#[derive(Debug)]
+pub struct RequiredFlevel {
+    /// The narrowest feature-level range supporting every signature in the set
+    pub range: Range<u32>,
+    /// Signatures whose declared `Engine` range starts below their computed
+    /// minimum, or which omit one despite a computed minimum
+    pub underdeclared: Vec<SigRef>,
+}
+
+/// A lightweight reference to a signature within a [`SigSet`], by position.
+/// Remains valid until the set is reordered or signatures are removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SigRef(pub usize);
+
+impl SigSet {
+    /// Create an empty signature set
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a signature to the set, interning its name (and, for logical
+    /// signatures, its file-type strings) and sharing its body content with
+    /// any earlier signature carrying an identical body
+    pub fn push(&mut self, sig: Box<dyn Signature>) {
+        index_shared_content(&mut self.names, &mut self.bodies, sig.as_ref());
+        self.sigs.push(sig);
+    }
+
+    /// The number of signatures in the set
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.sigs.len()
+    }
+
+    /// Whether the set contains no signatures
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sigs.is_empty()
+    }
+
+    /// Iterate over the signatures in the set, in their current order
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Signature> {
+        self.sigs.iter().map(Box::as_ref)
+    }
+
+    /// Sort the set into canonical database order (by name, then signature
+    /// type, then exported content) as defined by [`Signature::sort_key()`].
+    /// The ordering is stable: identical input sets produce identical output
+    /// regardless of initial order.
+    pub fn sort_canonical(&mut self) {
+        self.sigs.sort_by_cached_key(|sig| sig.sort_key());
+    }
+
+    /// Obtain the signature referenced by `sig_ref`, if it exists
+    #[must_use]
+    pub fn get(&self, sig_ref: SigRef) -> Option<&dyn Signature> {
+        self.sigs.get(sig_ref.0).map(Box::as_ref)
+    }
+
+    /// Return the `n` most complex signatures in the set (fewer if the set is
+    /// smaller), ordered from most to least complex by
+    /// [`Complexity::score()`].  Audit reporting uses this to surface
+    /// signatures that are likely to scan slowly.
+    #[must_use]
+    pub fn most_complex(&self, n: usize) -> Vec<(SigRef, Complexity)> {
+        let mut scored: Vec<(SigRef, Complexity)> = self
+            .sigs
+            .iter()
+            .enumerate()
+            .map(|(idx, sig)| (SigRef(idx), sig.complexity()))
+            .collect();
+        scored.sort_by_key(|(_, cx)| std::cmp::Reverse(cx.score()));
+        scored.truncate(n);
+        scored
+    }
+
+    /// Group logical-signature subsig bodies that recur across the set.
+    /// Bodies appearing in at least `min_occurrences` places are reported
+    /// with each owning signature and subsig index; these are candidates for
+    /// conversion into a shared macro subsignature.  Groups are ordered by
+    /// their first occurrence.
+    #[must_use]
+    pub fn shared_subsig_bodies(
+        &self,
+        min_occurrences: usize,
+    ) -> Vec<(BodySigFingerprint, Vec<(SigRef, usize)>)> {
+        let mut groups: HashMap<BodySigFingerprint, Vec<(SigRef, usize)>> = HashMap::new();
+        for (idx, sig) in self.sigs.iter().enumerate() {
+            if let Some(lsig) = sig.downcast_ref::<LogicalSig>() {
+                for (subsig_idx, body) in lsig.sub_sig_body_sigs() {
+                    groups
+                        .entry(body.fingerprint())
+                        .or_default()
+                        .push((SigRef(idx), subsig_idx));
+                }
+            }
+        }
+        let mut shared: Vec<_> = groups
+            .into_iter()
+            .filter(|(_, occurrences)| occurrences.len() >= min_occurrences)
+            .collect();
+        shared.sort_by_key(|(_, occurrences)| (occurrences[0].0 .0, occurrences[0].1));
+        shared
+    }
+
+    /// Compute the engine feature-level range this set requires: the highest
+    /// computed (or declared) minimum across all signatures, and the lowest
+    /// declared maximum.  Signatures whose declared `Engine` range reaches
+    /// below their computed minimum (and so claim support from engines that
+    /// lack a required feature) are reported as underdeclared.
+    #[must_use]
+    pub fn required_flevel(&self) -> RequiredFlevel {
+        let mut min = 1u32;
+        let mut max: Option<u32> = None;
+        let mut underdeclared = Vec::new();
+        for (idx, sig) in self.sigs.iter().enumerate() {
+            let computed_min = sig.computed_feature_level().and_then(|r| r.start());
+            let declared = sig
+                .downcast_ref::<LogicalSig>()
+                .and_then(|lsig| lsig.target_desc().engine().cloned());
+            if let Some(computed_min) = computed_min {
+                min = min.max(computed_min);
+            }
+            if let Some(declared) = &declared {
+                if let Some(declared_min) = declared.start() {
+                    min = min.max(declared_min);
+                }
+                if let Some(declared_max) = declared.end() {
+                    max = Some(max.map_or(declared_max, |m| m.min(declared_max)));
+                }
+            }
+            match (computed_min, declared.as_ref().and_then(Range::start)) {
+                (Some(computed), Some(declared)) if declared < computed => {
+                    underdeclared.push(SigRef(idx));
+                }
+                (Some(_), None) => underdeclared.push(SigRef(idx)),
+                _ => {}
+            }
+        }
+        let range = match max {
+            Some(max) => (min..=max).into(),
+            None => (min..).into(),
+        };
+        RequiredFlevel {
+            range,
+            underdeclared,
+        }
+    }
+
+    /// Cross-reference each logical signature's `Container`, `Intermediates`
+    /// and `HandlerType` values against the feature level at which the
+    /// engine's filetype detection gained that type.  A signature whose
+    /// declared `Engine` range starts below that level claims support from
+    /// engines that can never produce the container type, and so can never
+    /// fire there; each such reference is reported with the file type and
+    /// the feature level that introduced it.  Signatures without a declared
+    /// `Engine` minimum are skipped (that omission is reported by
+    /// [`SigSet::required_flevel`]).
+    #[must_use]
+    pub fn find_unavailable_container_types(&self) -> Vec<(SigRef, FileType, u32)> {
+        let mut flagged = vec![];
+        for (idx, sig) in self.sigs.iter().enumerate() {
+            let Some(lsig) = sig.downcast_ref::<LogicalSig>() else {
+                continue;
+            };
+            let target_desc = lsig.target_desc();
+            let Some(declared_min) = target_desc.engine().and_then(Range::start) else {
+                continue;
+            };
+            for file_type in target_desc.referenced_file_types() {
+                if let Some(introduced) = file_type.min_flevel() {
+                    if declared_min < introduced {
+                        flagged.push((SigRef(idx), file_type.clone(), introduced));
+                    }
+                }
+            }
+        }
+        flagged
+    }
+
+    /// Iterate over the signatures that can apply when scanning with a
+    /// concrete engine feature level, and (optionally) a concrete file size
+    /// and target type.  A signature applies unless one of its stated
+    /// constraints excludes the given values: logical signatures are checked
+    /// against their `TargetDesc` `Engine`, `FileSize` and `Target`
+    /// attributes, and extended signatures against their target type.
+    /// Signatures without constraints (and constraints for which no concrete
+    /// value was given) always apply.
+    pub fn filter_applicable(
+        &self,
+        flevel: u32,
+        file_size: Option<usize>,
+        target: Option<TargetType>,
+    ) -> impl Iterator<Item = (SigRef, &dyn Signature)> {
+        self.sigs
+            .iter()
+            .enumerate()
+            .filter(move |(_, sig)| {
+                if let Some(lsig) = sig.downcast_ref::<LogicalSig>() {
+                    let target_desc = lsig.target_desc();
+                    if let Some(engine) = target_desc.engine() {
+                        if !engine.contains(&flevel) {
+                            return false;
+                        }
+                    }
+                    if let (Some(file_size), Some(range)) = (file_size, target_desc.file_size()) {
+                        if !range.contains(&file_size) {
+                            return false;
+                        }
+                    }
+                    if let (Some(target), Some(sig_target)) = (target, target_desc.target_type()) {
+                        if sig_target != TargetType::Any && sig_target != target {
+                            return false;
+                        }
+                    }
+                } else if let (Some(target), Some(sig_target)) = (
+                    target,
+                    sig.downcast_ref::<ExtendedSig>()
+                        .map(ExtendedSig::target_type),
+                ) {
+                    if sig_target != TargetType::Any && sig_target != target {
+                        return false;
+                    }
+                }
+                true
+            })
+            .map(|(idx, sig)| (SigRef(idx), sig.as_ref()))
+    }
+
+    /// Find signatures that shadow one another: pairs whose structural content
+    /// is identical, differing only in name.  For hash-based signatures, the
+    /// structure is the digest and size; for extended signatures, the target
+    /// type, offset and body; for logical signatures, the target description
+    /// (excluding the `Engine` attribute), expression, and ordered subsig
+    /// bodies.
+    ///
+    /// Each fingerprint is computed once, and detection is performed via hash
+    /// lookup.  Pairs are returned in the order encountered, with the earlier
+    /// signature first.
+    #[must_use]
+    pub fn find_shadowed(&self) -> Vec<(SigRef, SigRef)> {
+        let mut seen: HashMap<[u8; 32], usize> = HashMap::new();
+        let mut shadowed = vec![];
+        for (idx, sig) in self.sigs.iter().enumerate() {
+            let Some(structural) = structural_fingerprint(sig.as_ref()) else {
+                continue;
+            };
+            // Digest the structural form the same way Signature::fingerprint
+            // digests the full canonical form
+            let mut buf = Vec::with_capacity(structural.len() + 1);
+            buf.push(crate::signature::FINGERPRINT_VERSION);
+            buf.extend_from_slice(&structural);
+            match seen.entry(openssl::sha::sha256(&buf)) {
+                std::collections::hash_map::Entry::Occupied(entry) => {
+                    shadowed.push((SigRef(*entry.get()), SigRef(idx)));
+                }
+                std::collections::hash_map::Entry::Vacant(entry) => {
+                    entry.insert(idx);
+                }
+            }
+        }
+        shadowed
+    }
+
+    /// Remove signatures whose [`fingerprint`](Signature::fingerprint)
+    /// duplicates that of an earlier entry, keeping the first occurrence.
+    /// Since fingerprints are computed over the canonical serialization,
+    /// entries that differ only in formatting (hex case, redundant range
+    /// spellings) are treated as duplicates.  Returns the number of
+    /// signatures removed.
+    pub fn dedupe(&mut self) -> usize {
+        let mut seen = std::collections::HashSet::new();
+        let before = self.sigs.len();
+        self.sigs.retain(|sig| seen.insert(sig.fingerprint()));
+        // Rebuild the interned storage so its statistics describe only the
+        // retained signatures
+        self.names = StringInterner::new();
+        self.bodies = BodyStore::new();
+        for sig in &self.sigs {
+            index_shared_content(&mut self.names, &mut self.bodies, sig.as_ref());
+        }
+        before - self.sigs.len()
+    }
+
+    /// Tally signatures by the threat category embedded in their names (e.g.,
+    /// `Packer` in `PUA.Win.Packer.Upx-57`), per
+    /// [`crate::signature::name_info`].  Signatures whose names don't carry a
+    /// category are tallied under `None`.
+    #[must_use]
+    pub fn category_counts(&self) -> HashMap<Option<&str>, usize> {
+        let mut counts: HashMap<Option<&str>, usize> = HashMap::new();
+        for sig in self.iter() {
+            *counts
+                .entry(crate::signature::name_info(sig.name()).category)
+                .or_default() += 1;
+        }
+        counts
+    }
+
+    /// Approximate the set's memory usage by category.  The owned figures
+    /// are computed from the signatures themselves; the interned/shared
+    /// figures come from the set's deduplicated storage, so the `*_saved`
+    /// fields quantify how much duplication interning absorbed.
+    #[must_use]
+    pub fn memory_stats(&self) -> MemoryStats {
+        let mut name_bytes = 0;
+        let mut body_bytes = 0;
+        for sig in self.iter() {
+            name_bytes += sig.name().len();
+            for body in sig.body_sigs() {
+                let mut sb = crate::sigbytes::SigBytes::new();
+                if crate::sigbytes::AppendSigBytes::append_sigbytes(body, &mut sb).is_ok() {
+                    body_bytes += sb.len();
+                }
+            }
+        }
+        MemoryStats {
+            name_bytes,
+            interned_string_bytes: self.names.unique_bytes(),
+            interned_bytes_saved: self.names.bytes_saved(),
+            body_bytes,
+            shared_body_bytes: self.bodies.unique_bytes(),
+            body_bytes_saved: self.bodies.bytes_saved(),
+        }
+    }
+}
+
+/// Fold one signature's shareable content into the set's interned storage
+fn index_shared_content(names: &mut StringInterner, bodies: &mut BodyStore, sig: &dyn Signature) {
+    names.intern(sig.name());
+    if let Some(lsig) = sig.downcast_ref::<LogicalSig>() {
+        for file_type in lsig.target_desc().referenced_file_types() {
+            names.intern(&file_type.to_string());
+        }
+    }
+    for body in sig.body_sigs() {
+        bodies.store(body);
+    }
+}
+
+/// Compute a structural fingerprint for a signature: its exported form with
+/// the name removed (and, for logical signatures, the `Engine` attribute
+/// dropped so that flevel-only respins still compare equal).  Returns `None`
+/// for signature types without a defined structural comparison.
+fn structural_fingerprint(sig: &dyn Signature) -> Option<Vec<u8>> {
+    let exported = sig.to_sigbytes().ok()?;
+    let bytes = exported.as_bytes();
+    match sig.sig_type() {
+        // hash:size:name -- the digest and size lead
+        SigType::FileHash | SigType::PESectionHash => {
+            let mut fields = bytes.split(|&b| b == b':');
+            let digest = fields.next()?;
+            let size = fields.next()?;
+            let mut fp = digest.to_vec();
+            fp.push(b':');
+            fp.extend_from_slice(size);
+            Some(fp)
+        }
+        // name:target:offset:body -- skip the name
+        SigType::Extended => {
+            let name_end = bytes.iter().position(|&b| b == b':')?;
+            Some(bytes[name_end + 1..].to_vec())
+        }
+        // name;targetdesc;expr;subsigs -- skip the name, drop the Engine attr
+        SigType::Logical => {
+            let mut fields = bytes.split(|&b| b == b';');
+            let _name = fields.next()?;
+            let target_desc = fields.next()?;
+            let mut fp = vec![];
+            for (i, attr) in target_desc
+                .split(|&b| b == b',')
+                .filter(|attr| !attr.starts_with(b"Engine:"))
+                .enumerate()
+            {
+                if i > 0 {
+                    fp.push(b',');
+                }
+                fp.extend_from_slice(attr);
+            }
+            for field in fields {
+                fp.push(b';');
+                fp.extend_from_slice(field);
+            }
+            Some(fp)
+        }
+        _ => None,
+    }
+}
+
+impl FromIterator<Box<dyn Signature>> for SigSet {
+    fn from_iter<I: IntoIterator<Item = Box<dyn Signature>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<Box<dyn Signature>> for SigSet {
+    fn extend<I: IntoIterator<Item = Box<dyn Signature>>>(&mut self, iter: I) {
+        for sig in iter {
+            self.push(sig);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{signature::parse_from_cvd, SigType};
+
+    const HASH_SIGS: &[&str] = &[
+        "aabbccddeeff00112233445566778899:68:Zed-Test-Signature",
+        "44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature",
+        "00112233445566778899aabbccddeeff:68:Mid-Test-Signature",
+    ];
+
+    fn set_from(sigs: &[&str]) -> SigSet {
+        sigs.iter()
+            .map(|s| parse_from_cvd(SigType::FileHash, &s.as_bytes().into()).unwrap())
+            .collect()
+    }
+
+    fn exported(set: &SigSet) -> Vec<String> {
+        set.iter()
+            .map(|sig| sig.to_sigbytes().unwrap().to_string())
+            .collect()
+    }
+
+    fn logical_set_from(sigs: &[&str]) -> SigSet {
+        sigs.iter()
+            .map(|s| parse_from_cvd(SigType::Logical, &s.as_bytes().into()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn shared_subsig_bodies_groups_identical_bodies() {
+        let set = logical_set_from(&[
+            "A.Sig;Engine:51-255,Target:0;0&1;deadbeef;aabbccdd",
+            "B.Sig;Engine:51-255,Target:0;0&1;00112233;deadbeef",
+            "C.Sig;Engine:51-255,Target:0;0;deadbeef",
+        ]);
+        let shared = set.shared_subsig_bodies(3);
+        assert_eq!(shared.len(), 1);
+        let (_, occurrences) = &shared[0];
+        assert_eq!(
+            occurrences,
+            &vec![(SigRef(0), 0), (SigRef(1), 1), (SigRef(2), 0)]
+        );
+
+        // No body recurs four times
+        assert!(set.shared_subsig_bodies(4).is_empty());
+    }
+
+    #[test]
+    fn required_flevel_raised_by_pcre_subsig() {
+        let set = logical_set_from(&[
+            "Plain.Sig;Engine:51-255,Target:0;0;aabbccdd",
+            "Pcre.Sig;Engine:81-255,Target:0;0&1;aabbccdd;0/abc/",
+        ]);
+        let req = set.required_flevel();
+        // The PCRE-bearing signature raises the floor to 81
+        assert_eq!(req.range, (81..=255).into());
+        assert!(req.underdeclared.is_empty());
+    }
+
+    #[test]
+    fn required_flevel_flags_underdeclared_engine_range() {
+        // The PCRE subsig requires feature level 81, but the signature
+        // declares support from 51
+        let set = logical_set_from(&["Low.Sig;Engine:51-255,Target:0;0&1;aabbccdd;0/abc/"]);
+        let req = set.required_flevel();
+        assert_eq!(req.range, (81..=255).into());
+        assert_eq!(req.underdeclared, vec![SigRef(0)]);
+    }
+
+    #[test]
+    fn container_type_available_within_engine_range() {
+        // OOXML Word detection arrived at feature level 75, and the Engine
+        // range starts there
+        let set = logical_set_from(&[
+            "Doc.Sig;Engine:75-255,Target:0,Container:CL_TYPE_OOXML_WORD;0;aabbccdd",
+        ]);
+        assert!(set.find_unavailable_container_types().is_empty());
+    }
+
+    #[test]
+    fn container_type_unavailable_below_engine_minimum() {
+        use crate::filetype::FileType;
+        // The declared range reaches back to 51, but engines below 75 can't
+        // detect CL_TYPE_OOXML_WORD
+        let set = logical_set_from(&[
+            "Ok.Sig;Engine:51-255,Target:0;0;aabbccdd",
+            "Doc.Sig;Engine:51-255,Target:0,Container:CL_TYPE_OOXML_WORD;0;aabbccdd",
+        ]);
+        assert_eq!(
+            set.find_unavailable_container_types(),
+            vec![(SigRef(1), FileType::CL_TYPE_OOXML_WORD, 75)]
+        );
+    }
+
+    #[test]
+    fn sort_canonical_orders_by_name() {
+        let mut set = set_from(HASH_SIGS);
+        set.sort_canonical();
+        let names: Vec<&str> = set.iter().map(Signature::name).collect();
+        assert_eq!(
+            names,
+            vec![
+                "Eicar-Test-Signature",
+                "Mid-Test-Signature",
+                "Zed-Test-Signature"
+            ]
+        );
+    }
+
+    #[test]
+    fn sort_canonical_is_stable_across_input_order() {
+        let mut shuffles = vec![
+            set_from(HASH_SIGS),
+            set_from(&[HASH_SIGS[1], HASH_SIGS[2], HASH_SIGS[0]]),
+            set_from(&[HASH_SIGS[2], HASH_SIGS[0], HASH_SIGS[1]]),
+        ];
+        for set in &mut shuffles {
+            set.sort_canonical();
+        }
+        let expected = exported(&shuffles[0]);
+        for set in &shuffles[1..] {
+            assert_eq!(exported(set), expected);
+        }
+    }
+
+    #[test]
+    fn dedupe_by_canonical_fingerprint() {
+        let mut set = SigSet::new();
+        for sig in [
+            "Dup.Sig:0:*:AABB*CCDD", // differs from the next only in hex case
+            "Dup.Sig:0:*:aabb*ccdd",
+            "Dup.Sig:0:*:aabb*ccde",
+        ] {
+            set.push(parse_from_cvd(SigType::Extended, &sig.into()).unwrap());
+        }
+        assert_eq!(set.dedupe(), 1);
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn category_counts_by_name() {
+        let set = set_from(&[
+            "aabbccddeeff00112233445566778899:68:PUA.Win.Packer.Upx-57",
+            "00112233445566778899aabbccddeeff:68:Win.Trojan.Agent-123",
+            "99887766554433221100aabbccddeeff:68:Win.Trojan.MSShellcode-6360730",
+            HASH_SIGS[1], // Eicar-Test-Signature: free-form, no category
+        ]);
+        let counts = set.category_counts();
+        assert_eq!(counts.get(&Some("Packer")), Some(&1));
+        assert_eq!(counts.get(&Some("Trojan")), Some(&2));
+        assert_eq!(counts.get(&None), Some(&1));
+        assert_eq!(counts.len(), 3);
+    }
+
+    #[test]
+    fn filter_applicable_mixed_constraints() {
+        let mut set = SigSet::new();
+        // No constraints: always applies
+        set.push(parse_from_cvd(SigType::FileHash, &HASH_SIGS[1].as_bytes().into()).unwrap());
+        // Applies only from flevel 100
+        let ldb = b"New.Sig;Engine:100-255,Target:0;(0&1);414141;424242";
+        set.push(parse_from_cvd(SigType::Logical, &ldb[..].into()).unwrap());
+        // Applies only to small PE files
+        let ldb = b"Pe.Sig;Engine:51-255,Target:1,FileSize:1024-4096;(0&1);414141;424242";
+        set.push(parse_from_cvd(SigType::Logical, &ldb[..].into()).unwrap());
+        // Extended signature scoped to OLE2
+        let ndb = b"Ole.Sig:2:*:aabbccdd";
+        set.push(parse_from_cvd(SigType::Extended, &ndb[..].into()).unwrap());
+
+        let names = |flevel, file_size, target| -> Vec<String> {
+            set.filter_applicable(flevel, file_size, target)
+                .map(|(_, sig)| sig.name().to_owned())
+                .collect()
+        };
+
+        // A 2 MB PE on flevel 161: the FileSize and OLE2 constraints exclude
+        assert_eq!(
+            names(161, Some(2 * 1024 * 1024), Some(TargetType::PE)),
+            vec!["Eicar-Test-Signature", "New.Sig"]
+        );
+        // A small PE on an old engine
+        assert_eq!(
+            names(60, Some(2048), Some(TargetType::PE)),
+            vec!["Eicar-Test-Signature", "Pe.Sig"]
+        );
+        // Unstated values leave the corresponding constraints unchecked
+        assert_eq!(
+            names(161, None, None),
+            vec!["Eicar-Test-Signature", "New.Sig", "Pe.Sig", "Ole.Sig"]
+        );
+    }
+
+    #[test]
+    fn find_shadowed_flags_exact_duplicates() {
+        let ldb_a = concat!(
+            "Test.Sig.A;Engine:51-255,Target:0;(0&1);",
+            "414141;",
+            "424242"
+        );
+        // Identical structure, different name and Engine range
+        let ldb_b = concat!(
+            "Test.Sig.B;Engine:81-255,Target:0;(0&1);",
+            "414141;",
+            "424242"
+        );
+        let mut set = SigSet::new();
+        set.push(parse_from_cvd(SigType::Logical, &ldb_a.as_bytes().into()).unwrap());
+        set.push(parse_from_cvd(SigType::Logical, &ldb_b.as_bytes().into()).unwrap());
+        let shadowed = set.find_shadowed();
+        assert_eq!(shadowed, vec![(SigRef(0), SigRef(1))]);
+        assert_eq!(set.get(shadowed[0].0).unwrap().name(), "Test.Sig.A");
+        assert_eq!(set.get(shadowed[0].1).unwrap().name(), "Test.Sig.B");
+    }
+
+    #[test]
+    fn find_shadowed_ignores_near_duplicates() {
+        let ldb_a = concat!(
+            "Test.Sig.A;Engine:51-255,Target:0;(0&1);",
+            "414141;",
+            "424242"
+        );
+        // Same shape, but one subsig body differs
+        let ldb_b = concat!(
+            "Test.Sig.B;Engine:51-255,Target:0;(0&1);",
+            "414141;",
+            "434343"
+        );
+        let mut set = SigSet::new();
+        set.push(parse_from_cvd(SigType::Logical, &ldb_a.as_bytes().into()).unwrap());
+        set.push(parse_from_cvd(SigType::Logical, &ldb_b.as_bytes().into()).unwrap());
+        assert!(set.find_shadowed().is_empty());
+    }
+
+    #[test]
+    fn find_shadowed_hash_sigs() {
+        let mut set = set_from(&[
+            "44d88612fea8a8f36de82e1278abb02f:68:Eicar-Test-Signature",
+            "44d88612fea8a8f36de82e1278abb02f:68:Eicar-Renamed",
+            // Same digest, different size: not shadowed
+            "44d88612fea8a8f36de82e1278abb02f:69:Eicar-Different-Size",
+        ]);
+        assert_eq!(set.find_shadowed(), vec![(SigRef(0), SigRef(1))]);
+        set.sort_canonical();
+        // References are positional; recompute after sorting
+        let shadowed = set.find_shadowed();
+        assert_eq!(shadowed.len(), 1);
+    }
+
+    #[test]
+    fn memory_stats_reflect_interning_savings() {
+        // Generate a 100k-signature database and load it twice, so that
+        // every name and body recurs at least once; the interning layer
+        // should absorb the duplication
+        let lines: Vec<String> = (0..100_000)
+            .map(|i| {
+                format!(
+                    "Generated.Sig-{}:0:*:{:08x}{:08x}",
+                    i % 1000,
+                    i % 256,
+                    i % 256
+                )
+            })
+            .collect();
+        let mut set = SigSet::new();
+        for _ in 0..2 {
+            for line in &lines {
+                set.push(parse_from_cvd(SigType::Extended, &line.as_str().into()).unwrap());
+            }
+        }
+        assert_eq!(set.len(), 200_000);
+
+        let stats = set.memory_stats();
+        // 1000 distinct names vs 200k owned copies
+        assert!(stats.interned_string_bytes < stats.name_bytes);
+        assert_eq!(
+            stats.interned_bytes_saved,
+            stats.name_bytes - stats.interned_string_bytes
+        );
+        // 256 distinct bodies vs 200k owned copies
+        assert!(stats.shared_body_bytes < stats.body_bytes);
+        assert_eq!(
+            stats.body_bytes_saved,
+            stats.body_bytes - stats.shared_body_bytes
+        );
+    }
+
+    #[test]
+    fn memory_stats_track_dedupe() {
+        let mut set = set_from(&[
+            HASH_SIGS[0],
+            HASH_SIGS[0], // duplicate: removed by dedupe
+            HASH_SIGS[1],
+        ]);
+        assert!(set.memory_stats().interned_bytes_saved > 0);
+        assert_eq!(set.dedupe(), 1);
+        // After the rebuild, no duplication remains to be absorbed
+        let stats = set.memory_stats();
+        assert_eq!(stats.interned_bytes_saved, 0);
+        assert_eq!(stats.name_bytes, stats.interned_string_bytes);
+    }
+
+    #[test]
+    fn sort_key_orders_name_first() {
+        let a = parse_from_cvd(
+            SigType::FileHash,
+            &b"44d88612fea8a8f36de82e1278abb02f:68:Abc".into(),
+        )
+        .unwrap();
+        let b = parse_from_cvd(
+            SigType::FileHash,
+            &b"00112233445566778899aabbccddeeff:68:Xyz".into(),
+        )
+        .unwrap();
+        assert!(a.sort_key() < b.sort_key());
+    }
+}
@@ -0,0 +1,186 @@
+/*
+ *  Copyright (C) 2024 Cisco Systems, Inc. and/or its affiliates. All rights reserved.
+ *
+ *  This program is free software; you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License version 2 as
+ *  published by the Free Software Foundation.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program; if not, write to the Free Software
+ *  Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston,
+ *  MA 02110-1301, USA.
+ */
+
+//! Shared-storage backing for [`SigSet`](super::SigSet).  A full database
+//! holds millions of signatures, and much of their content recurs: threat
+//! family names, `CL_TYPE_*` strings, and (for respun logical signatures)
+//! entire subsig bodies.  [`StringInterner`] and [`BodyStore`] keep one
+//! `Arc`-backed copy of each distinct value, and track how many bytes the
+//! sharing avoided so that [`SigSet::memory_stats`](super::SigSet::memory_stats)
+//! can report it.
+
+use crate::{
+    sigbytes::{AppendSigBytes, SigBytes},
+    signature::bodysig::{BodySig, BodySigFingerprint},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+/// A deduplicating store of strings.  Interning a string returns a shared
+/// (`Arc`-backed) copy, with one allocation per distinct value no matter how
+/// often it recurs.
+#[derive(Debug, Default)]
+pub struct StringInterner {
+    strings: HashSet<Arc<str>>,
+    unique_bytes: usize,
+    bytes_saved: usize,
+}
+
+impl StringInterner {
+    /// Create an empty interner
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the shared copy of `s`, storing it on first sight
+    pub fn intern(&mut self, s: &str) -> Arc<str> {
+        if let Some(existing) = self.strings.get(s) {
+            self.bytes_saved += s.len();
+            Arc::clone(existing)
+        } else {
+            let shared: Arc<str> = Arc::from(s);
+            self.unique_bytes += shared.len();
+            self.strings.insert(Arc::clone(&shared));
+            shared
+        }
+    }
+
+    /// The number of distinct strings held
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Whether no strings have been interned
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Total bytes of distinct string content held
+    #[must_use]
+    pub fn unique_bytes(&self) -> usize {
+        self.unique_bytes
+    }
+
+    /// Bytes that sharing avoided duplicating: the combined length of every
+    /// `intern` call that found its string already present
+    #[must_use]
+    pub fn bytes_saved(&self) -> usize {
+        self.bytes_saved
+    }
+}
+
+/// A deduplicating store of body-signature content, keyed by
+/// [`BodySig::fingerprint`].  Bodies with identical canonical serializations
+/// share one `Arc`-backed copy.
+#[derive(Debug, Default)]
+pub struct BodyStore {
+    bodies: HashMap<BodySigFingerprint, Arc<[u8]>>,
+    unique_bytes: usize,
+    bytes_saved: usize,
+}
+
+impl BodyStore {
+    /// Create an empty store
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the shared serialized form of `body`, storing it on first sight
+    pub fn store(&mut self, body: &BodySig) -> Arc<[u8]> {
+        let fingerprint = body.fingerprint();
+        if let Some(existing) = self.bodies.get(&fingerprint) {
+            self.bytes_saved += existing.len();
+            Arc::clone(existing)
+        } else {
+            let mut sb = SigBytes::new();
+            body.append_sigbytes(&mut sb)
+                .expect("serializing a parsed body signature cannot fail");
+            let shared: Arc<[u8]> = Arc::from(sb.as_bytes());
+            self.unique_bytes += shared.len();
+            self.bodies.insert(fingerprint, Arc::clone(&shared));
+            shared
+        }
+    }
+
+    /// The number of distinct bodies held
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.bodies.len()
+    }
+
+    /// Whether no bodies have been stored
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.bodies.is_empty()
+    }
+
+    /// Total bytes of distinct body content held
+    #[must_use]
+    pub fn unique_bytes(&self) -> usize {
+        self.unique_bytes
+    }
+
+    /// Bytes that sharing avoided duplicating
+    #[must_use]
+    pub fn bytes_saved(&self) -> usize {
+        self.bytes_saved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interner_shares_storage() {
+        let mut interner = StringInterner::new();
+        let a = interner.intern("Win.Trojan.Agent");
+        let b = interner.intern("Win.Trojan.Agent");
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(interner.len(), 1);
+        assert_eq!(interner.unique_bytes(), "Win.Trojan.Agent".len());
+        assert_eq!(interner.bytes_saved(), "Win.Trojan.Agent".len());
+
+        interner.intern("Win.Trojan.Other");
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    fn body_store_shares_identical_bodies() {
+        let body_a = BodySig::try_from(&b"aabb*ccdd"[..]).unwrap();
+        // Differs from body_a only in hex case: same canonical form
+        let body_b = BodySig::try_from(&b"AABB*CCDD"[..]).unwrap();
+        let body_c = BodySig::try_from(&b"aabb*ccde"[..]).unwrap();
+
+        let mut store = BodyStore::new();
+        let a = store.store(&body_a);
+        let b = store.store(&body_b);
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.bytes_saved(), "aabb*ccdd".len());
+
+        store.store(&body_c);
+        assert_eq!(store.len(), 2);
+    }
+}
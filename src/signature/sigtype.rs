@@ -20,7 +20,7 @@ use std::{ffi::OsStr, path::Path, str::FromStr};
 use thiserror::Error;
 
 /// Signature types
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SigType {
     /// [Extended signature](crate::signature::ext::ExtendedSig)
     Extended,
@@ -36,6 +36,8 @@ pub enum SigType {
     FileHash,
     /// [Filetype Magic signature](crate::signature::filetype_magic::FTMagic)
     FTMagic,
+    /// [Icon signature](crate::signature::icon_sig::IconSig)
+    Icon,
     /// [Portable Executable Section Hash signature](crate::signature::pehash::PESectionHashSig)
     PESectionHash,
     /// Yara signature
@@ -113,10 +115,7 @@ impl SigType {
             }
 
             // Icon signatures
-            "idb" => {
-                println!("Support for .idb is not yet implemented.");
-                return None;
-            }
+            "idb" => SigType::Icon,
 
             // Deprecated types
             "zmd" | "rmd" | "db" => {
@@ -26,7 +26,7 @@ use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive, ToPrimitive};
 use thiserror::Error;
 
-#[derive(Copy, Clone, Debug, PartialEq, FromPrimitive, ToPrimitive)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, FromPrimitive, ToPrimitive)]
 pub enum TargetType {
     /// Any file
     Any = 0,
@@ -56,7 +56,7 @@ pub enum TargetType {
     Java = 12,
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
 pub enum TargetTypeParseError {
     #[error("invalid number: {0}")]
     ParseNumUsize(#[from] ParseNumberError<usize>),
@@ -103,4 +103,125 @@ impl TargetType {
     pub fn is_native_executable(&self) -> bool {
         matches!(self, TargetType::PE | TargetType::ELF | TargetType::MachO)
     }
+
+    /// Whether the specified TargetType is PDF
+    #[must_use]
+    pub fn is_pdf(&self) -> bool {
+        matches!(self, TargetType::PDF)
+    }
+
+    /// Whether the specified TargetType is normalized HTML
+    #[must_use]
+    pub fn is_html(&self) -> bool {
+        matches!(self, TargetType::HTML)
+    }
+
+    /// Whether the specified TargetType is an OLE2 compound document (MS
+    /// Office documents, MSI installers)
+    #[must_use]
+    pub fn is_ole2(&self) -> bool {
+        matches!(self, TargetType::OLE2)
+    }
+
+    /// Whether the specified TargetType is a document format (PDF, HTML, or
+    /// an OLE2 compound document)
+    #[must_use]
+    pub fn is_document(&self) -> bool {
+        self.is_pdf() || self.is_html() || self.is_ole2()
+    }
+
+    /// Whether the specified TargetType is a Java class file
+    #[must_use]
+    pub fn is_java(&self) -> bool {
+        matches!(self, TargetType::Java)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The numeric values of TargetType are defined by the CVD format and must
+    // never change.  Each variant is checked explicitly so that a refactor
+    // that disturbs the mapping is caught here.
+    const TARGET_TYPE_IDS: &[(TargetType, usize)] = &[
+        (TargetType::Any, 0),
+        (TargetType::PE, 1),
+        (TargetType::OLE2, 2),
+        (TargetType::HTML, 3),
+        (TargetType::Mail, 4),
+        (TargetType::Graphics, 5),
+        (TargetType::ELF, 6),
+        (TargetType::Text, 7),
+        (TargetType::Unused, 8),
+        (TargetType::MachO, 9),
+        (TargetType::PDF, 10),
+        (TargetType::Flash, 11),
+        (TargetType::Java, 12),
+    ];
+
+    #[test]
+    fn to_primitive_values() {
+        for (target_type, id) in TARGET_TYPE_IDS {
+            assert_eq!(target_type.to_usize(), Some(*id), "{target_type:?}");
+        }
+    }
+
+    #[test]
+    fn from_primitive_values() {
+        for (target_type, id) in TARGET_TYPE_IDS {
+            assert_eq!(
+                <TargetType as FromPrimitive>::from_usize(*id),
+                Some(*target_type),
+                "{id}"
+            );
+        }
+        // Values beyond the last variant don't map to anything
+        assert_eq!(<TargetType as FromPrimitive>::from_usize(13), None);
+    }
+
+    #[test]
+    fn classification_helpers() {
+        for (target_type, _) in TARGET_TYPE_IDS {
+            assert_eq!(
+                target_type.is_pdf(),
+                matches!(target_type, TargetType::PDF),
+                "{target_type:?}"
+            );
+            assert_eq!(
+                target_type.is_html(),
+                matches!(target_type, TargetType::HTML),
+                "{target_type:?}"
+            );
+            assert_eq!(
+                target_type.is_ole2(),
+                matches!(target_type, TargetType::OLE2),
+                "{target_type:?}"
+            );
+            assert_eq!(
+                target_type.is_java(),
+                matches!(target_type, TargetType::Java),
+                "{target_type:?}"
+            );
+            assert_eq!(
+                target_type.is_document(),
+                matches!(
+                    target_type,
+                    TargetType::PDF | TargetType::HTML | TargetType::OLE2
+                ),
+                "{target_type:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn round_trip_all_variants() {
+        for (target_type, _) in TARGET_TYPE_IDS {
+            let n = target_type.to_usize().unwrap();
+            assert_eq!(
+                <TargetType as FromPrimitive>::from_usize(n),
+                Some(*target_type)
+            );
+        }
+    }
 }
@@ -0,0 +1,119 @@
+/*
+ *  Copyright (C) 2024 Cisco Systems, Inc. and/or its affiliates. All rights reserved.
+ *
+ *  This program is free software; you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License version 2 as
+ *  published by the Free Software Foundation.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program; if not, write to the Free Software
+ *  Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston,
+ *  MA 02110-1301, USA.
+ */
+
+//! Visitors over a signature's constituent elements, for tools that examine
+//! or rewrite signatures in bulk (e.g., "replace every `EOF-0` offset")
+//! without re-implementing per-type traversal.  Implement [`SigVisitor`] (or
+//! [`SigVisitorMut`] for edits) and pass it to
+//! [`Signature::walk`](crate::signature::Signature::walk) /
+//! [`Signature::walk_mut`](crate::signature::Signature::walk_mut); all hooks
+//! default to no-ops, so visitors only name the elements they care about.
+
+use super::{
+    bodysig::BodySig,
+    ext_sig::Offset,
+    logical_sig::{subsig::SubSig, targetdesc::TargetDesc},
+};
+
+/// Read-only hooks, invoked for each element a signature contains.  Logical
+/// signatures recurse into their sub-signatures, so a single walk sees every
+/// body signature and offset.
+#[allow(unused_variables)]
+pub trait SigVisitor {
+    fn visit_body_sig(&mut self, body_sig: &BodySig) {}
+
+    fn visit_offset(&mut self, offset: &Offset) {}
+
+    fn visit_target_desc(&mut self, target_desc: &TargetDesc) {}
+
+    fn visit_subsig(&mut self, subsig: &dyn SubSig) {}
+}
+
+/// Mutating hooks, as [`SigVisitor`], for bulk edits
+#[allow(unused_variables)]
+pub trait SigVisitorMut {
+    fn visit_body_sig(&mut self, body_sig: &mut BodySig) {}
+
+    fn visit_offset(&mut self, offset: &mut Offset) {}
+
+    fn visit_target_desc(&mut self, target_desc: &mut TargetDesc) {}
+
+    fn visit_subsig(&mut self, subsig: &mut dyn SubSig) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        sigbytes::SigBytes,
+        signature::{ext_sig::Offset, parse_from_cvd},
+        SigType,
+    };
+
+    #[test]
+    fn count_wildcards_across_logical_sig() {
+        #[derive(Default)]
+        struct WildcardCounter {
+            wildcards: usize,
+            subsigs: usize,
+            target_descs: usize,
+        }
+        impl SigVisitor for WildcardCounter {
+            fn visit_body_sig(&mut self, body_sig: &BodySig) {
+                self.wildcards += body_sig.wildcard_count();
+            }
+            fn visit_subsig(&mut self, _subsig: &dyn SubSig) {
+                self.subsigs += 1;
+            }
+            fn visit_target_desc(&mut self, _target_desc: &TargetDesc) {
+                self.target_descs += 1;
+            }
+        }
+
+        let sig = parse_from_cvd(
+            SigType::Logical,
+            &SigBytes::from(
+                "TestSig;Engine:51-255,Target:0;(0&1);aabbcc*ddeeff;414141*424242*434343",
+            ),
+        )
+        .unwrap();
+        let mut counter = WildcardCounter::default();
+        sig.walk(&mut counter);
+        assert_eq!(counter.wildcards, 3);
+        assert_eq!(counter.subsigs, 2);
+        assert_eq!(counter.target_descs, 1);
+    }
+
+    #[test]
+    fn rewrite_offset_via_visitor() {
+        struct PinOffset;
+        impl SigVisitorMut for PinOffset {
+            fn visit_offset(&mut self, offset: &mut Offset) {
+                *offset = Offset::try_from(&b"100"[..]).unwrap();
+            }
+        }
+
+        let mut sig =
+            parse_from_cvd(SigType::Extended, &SigBytes::from("Test:0:EOF-4:aabbccdd")).unwrap();
+        sig.walk_mut(&mut PinOffset);
+        assert_eq!(
+            sig.to_sigbytes().unwrap().to_string(),
+            "Test:0:100:aabbccdd"
+        );
+    }
+}
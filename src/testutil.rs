@@ -0,0 +1,157 @@
+/*
+ *  Copyright (C) 2024 Cisco Systems, Inc. and/or its affiliates. All rights reserved.
+ *
+ *  This program is free software; you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License version 2 as
+ *  published by the Free Software Foundation.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program; if not, write to the Free Software
+ *  Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston,
+ *  MA 02110-1301, USA.
+ */
+
+//! `proptest` strategies for generating structurally-valid signature
+//! elements.  These are used by this crate's own property tests, and are
+//! exported (behind the `testutil` feature) so that downstream crates can
+//! fuzz their own signature-handling code with realistic inputs.
+
+use crate::signature::bodysig::BodySig;
+use proptest::prelude::*;
+
+/// A single full hex byte (e.g., `3f`)
+fn full_byte() -> impl Strategy<Value = String> {
+    any::<u8>().prop_map(|b| format!("{b:02x}"))
+}
+
+/// A single match byte: a full byte, a nyble wildcard, or `??`
+fn match_byte() -> impl Strategy<Value = String> {
+    prop_oneof![
+        4 => full_byte(),
+        1 => (0u8..16).prop_map(|n| format!("?{n:x}")),
+        1 => (0u8..16).prop_map(|n| format!("{n:x}?")),
+        1 => Just("??".to_owned()),
+    ]
+}
+
+/// A match string: an optional wildcard-bearing prefix and suffix around a
+/// static core of at least two bytes (satisfying the parser's minimum
+/// static-bytes requirement)
+fn match_string() -> impl Strategy<Value = String> {
+    (
+        proptest::collection::vec(match_byte(), 0..3),
+        proptest::collection::vec(full_byte(), 2..6),
+        proptest::collection::vec(match_byte(), 0..3),
+    )
+        .prop_map(|(prefix, core, suffix)| {
+            let mut s = prefix.concat();
+            s.push_str(&core.concat());
+            s.push_str(&suffix.concat());
+            s
+        })
+}
+
+/// A parenthesized alternative-string group, possibly negated (negation is
+/// only valid for fixed-width groups)
+fn alternatives() -> impl Strategy<Value = String> {
+    let fixed_width = (1usize..=3, 2usize..=3).prop_flat_map(|(width, count)| {
+        proptest::collection::vec(proptest::collection::vec(full_byte(), width), count)
+    });
+    let generic = proptest::collection::vec(proptest::collection::vec(full_byte(), 1..=3), 2..=3);
+    prop_oneof![
+        3 => fixed_width.clone().prop_map(|branches| render_group(&branches, false)),
+        1 => fixed_width.prop_map(|branches| render_group(&branches, true)),
+        2 => generic.prop_map(|branches| render_group(&branches, false)),
+    ]
+}
+
+fn render_group(branches: &[Vec<String>], negated: bool) -> String {
+    let mut s = String::new();
+    if negated {
+        s.push('!');
+    }
+    s.push('(');
+    for (pos, branch) in branches.iter().enumerate() {
+        if pos > 0 {
+            s.push('|');
+        }
+        s.push_str(&branch.concat());
+    }
+    s.push(')');
+    s
+}
+
+/// A wildcard or byte-range separator between match strings
+fn separator() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just("*".to_owned()),
+        (1usize..=300).prop_map(|n| format!("{{{n}}}")),
+        (1usize..=100, 1usize..=200).prop_map(|(lo, extent)| format!("{{{lo}-{}}}", lo + extent)),
+        (1usize..=300).prop_map(|n| format!("{{{n}-}}")),
+        (1usize..=300).prop_map(|n| format!("{{-{n}}}")),
+        alternatives(),
+    ]
+}
+
+/// The serialized form of a structurally-valid body signature: a leading
+/// match string followed by a bounded number of separator/string pairs
+pub fn arb_body_sig_bytes() -> impl Strategy<Value = Vec<u8>> {
+    (
+        match_string(),
+        proptest::collection::vec((separator(), match_string()), 0..4),
+    )
+        .prop_map(|(first, rest)| {
+            let mut s = first;
+            for (sep, string) in rest {
+                s.push_str(&sep);
+                s.push_str(&string);
+            }
+            s.into_bytes()
+        })
+}
+
+/// A structurally-valid [`BodySig`], as parsed from
+/// [`arb_body_sig_bytes`]
+///
+/// # Panics
+///
+/// Panics if the generated serialization fails to parse, which indicates a
+/// bug in either the generator or the parser.
+pub fn arb_body_sig() -> impl Strategy<Value = BodySig> {
+    arb_body_sig_bytes().prop_map(|bytes| {
+        BodySig::try_from(bytes.as_slice()).expect("generated body signature must parse")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sigbytes::{AppendSigBytes, SigBytes};
+
+    proptest! {
+        #[test]
+        fn generated_body_sigs_round_trip(sig in arb_body_sig()) {
+            let mut sb = SigBytes::new();
+            sig.append_sigbytes(&mut sb).unwrap();
+            let reparsed = BodySig::try_from(sb.as_bytes())
+                .expect("exported body signature must re-parse");
+            prop_assert_eq!(sig, reparsed);
+        }
+
+        #[test]
+        fn parsed_export_reparses_equal(bytes in arb_body_sig_bytes()) {
+            let sig = BodySig::try_from(bytes.as_slice())
+                .expect("generated body signature must parse");
+            let mut sb = SigBytes::new();
+            sig.append_sigbytes(&mut sb).unwrap();
+            let reparsed = BodySig::try_from(sb.as_bytes())
+                .expect("exported body signature must re-parse");
+            prop_assert_eq!(sig, reparsed);
+        }
+    }
+}
@@ -109,6 +109,25 @@ pub enum ParseHashError {
 
     #[error("unsupported hex-encoded hash length ({0})")]
     UnsupportedHashLength(usize),
+
+    #[error(
+        "wrong hash algorithm: expected {} ({expected_len}-byte digest), found {found_len}-byte digest",
+        algorithm_name(*.expected_len)
+    )]
+    WrongAlgorithm {
+        expected_len: usize,
+        found_len: usize,
+    },
+}
+
+/// Name of the hash algorithm with the given digest length, for diagnostics
+fn algorithm_name(digest_len: usize) -> &'static str {
+    match digest_len {
+        MD5_LEN => "MD5",
+        SHA1_LEN => "SHA1",
+        SHA2_256_LEN => "SHA2-256",
+        _ => "unknown",
+    }
 }
 
 /// Decode a hex-encoded byte sequence of given SIZE
@@ -130,6 +149,22 @@ pub fn parse_hash(hex: &[u8]) -> Result<Hash, ParseHashError> {
     }
 }
 
+/// Parse a hex-encoded byte sequence into a digest container, requiring the
+/// digest length (in bytes) of a particular algorithm.  A well-formed hash of
+/// any other supported algorithm is reported as
+/// [`ParseHashError::WrongAlgorithm`], identifying both the expected and found
+/// digest lengths.
+pub fn parse_hash_expecting(hex: &[u8], expected_len: usize) -> Result<Hash, ParseHashError> {
+    let found_len = hex.len() / 2;
+    if found_len != expected_len && algorithm_name(found_len) != "unknown" {
+        return Err(ParseHashError::WrongAlgorithm {
+            expected_len,
+            found_len,
+        });
+    }
+    parse_hash(hex)
+}
+
 /// Errors that can occur when parsing a number when represented as &[u8] decimal number
 #[derive(Debug, Error)]
 pub enum ParseNumberError<T>
@@ -158,13 +193,33 @@ where
             // mainly used for unit testing.
             (Self::Unparseable(_), Self::Unparseable(_)) => true,
             (Self::Utf8Error(l0), Self::Utf8Error(r0)) => l0 == r0,
+            (Self::NegativeValue(l0), Self::NegativeValue(r0)) => l0 == r0,
             _ => false,
         }
     }
 }
 
+impl<T> Eq for ParseNumberError<T>
+where
+    T: std::str::FromStr,
+    <T as std::str::FromStr>::Err: std::fmt::Debug,
+{
+}
+
+impl<T> std::hash::Hash for ParseNumberError<T>
+where
+    T: std::str::FromStr,
+    <T as std::str::FromStr>::Err: std::fmt::Debug,
+{
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        // Hash only the variant, consistent with the loose equality above
+        // (equal values must hash equally; the converse needn't hold)
+        std::mem::discriminant(self).hash(state);
+    }
+}
+
 /// Errors that can be encountered while trying to parse an inclusive range
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
 pub enum RangeInclusiveParseError<T>
 where
     T: std::str::FromStr,
@@ -232,7 +287,7 @@ where
     Ok(lower..=upper)
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
 #[error("invalid boolean value (must be 0 or 1)")]
 pub struct ParseBoolFromIntError;
 
@@ -269,6 +324,24 @@ pub fn unescaped_element<T: PartialEq + Copy>(
     }
 }
 
+/// Render a byte in a human-readable form suitable for error messages:
+/// printable ASCII as the character itself, common control characters as
+/// their escape sequence, and anything else as `\x`-prefixed hex -- each
+/// uniformly wrapped in single quotes (with the quote character itself
+/// escaped, so the output is never ambiguous).
+#[must_use]
+pub fn byte_to_printable(b: u8) -> String {
+    match b {
+        b'\n' => r"'\n'".into(),
+        b'\r' => r"'\r'".into(),
+        b'\t' => r"'\t'".into(),
+        b'\0' => r"'\0'".into(),
+        b'\'' => r"'\''".into(),
+        b if b.is_ascii_graphic() || b == b' ' => format!("'{}'", char::from(b)),
+        b => format!(r"'\x{b:02x}'"),
+    }
+}
+
 /// Detect whether the a field has a wildcard (`*`) value, returning None if it
 /// does, or Some(orig_field_value) if it doesn't.
 #[must_use]
@@ -287,7 +360,21 @@ pub fn opt_field_value(bytes: &[u8]) -> Option<&[u8]> {
 /// If the `OPTIONAL` prefix is specified, returns an `Option`, substituting
 /// `None` for a literal field value of "`*`" rather than passing the value to
 /// the parser.
+///
+/// If the `TRAILING` prefix is specified, behaves as `OPTIONAL`, except that a
+/// missing field (i.e., an exhausted iterator) or an empty field also yields
+/// `None` rather than an error.  This suits fields that may be omitted
+/// entirely at end-of-line; no `$missing_err` is taken.
 macro_rules! parse_field {
+    ( TRAILING $field_iter:expr, $parser:expr, $parse_err:expr) => {
+        $field_iter
+            .next()
+            .filter(|field| !field.is_empty())
+            .and_then(crate::util::opt_field_value)
+            .map($parser)
+            .transpose()
+            .map_err($parse_err)
+    };
     ( OPTIONAL $field_iter:expr, $parser:expr, $missing_err:expr, $parse_err:expr) => {
         crate::util::opt_field_value($field_iter.next().ok_or($missing_err)?)
             .map($parser)
@@ -301,7 +388,7 @@ macro_rules! parse_field {
 pub(crate) use parse_field;
 
 /// Generic container for any range of number
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Range<T: std::str::FromStr> {
     // {n}
     Exact(T),
@@ -324,6 +411,65 @@ impl<T: std::str::FromStr + Clone> Range<T> {
             Range::Inclusive(r) => Some(r.start().clone()),
         }
     }
+
+    /// Obtain the upper bound of a range, if applicable (or None, if the range
+    /// has no upper bound)
+    pub fn end(&self) -> Option<T> {
+        match self {
+            Range::Exact(n) => Some(n.clone()),
+            Range::ToInclusive(r) => Some(r.end.clone()),
+            Range::From(_) => None,
+            Range::Inclusive(r) => Some(r.end().clone()),
+        }
+    }
+
+    /// Convert each bound of this range with a fallible function, preserving
+    /// the range's form.  The error from the first failing bound is returned.
+    pub fn try_map<U, E>(&self, mut f: impl FnMut(&T) -> Result<U, E>) -> Result<Range<U>, E>
+    where
+        U: std::str::FromStr,
+    {
+        Ok(match self {
+            Range::Exact(n) => Range::Exact(f(n)?),
+            Range::ToInclusive(r) => Range::ToInclusive(..=f(&r.end)?),
+            Range::From(r) => Range::From(f(&r.start)?..),
+            Range::Inclusive(r) => Range::Inclusive(f(r.start())?..=f(r.end())?),
+        })
+    }
+
+    /// Intersect this range with another, returning the range of values
+    /// contained in both, or None if they don't overlap.  `Exact` ranges
+    /// behave as single-point intervals (and a single-point intersection is
+    /// returned as `Exact`).
+    pub fn clamp_to(&self, other: &Range<T>) -> Option<Range<T>>
+    where
+        T: PartialOrd,
+    {
+        let start = match (self.start(), other.start()) {
+            (Some(a), Some(b)) => Some(if a > b { a } else { b }),
+            (start, None) | (None, start) => start,
+        };
+        let end = match (self.end(), other.end()) {
+            (Some(a), Some(b)) => Some(if a < b { a } else { b }),
+            (end, None) | (None, end) => end,
+        };
+        match (start, end) {
+            (Some(start), Some(end)) => {
+                if start > end {
+                    None
+                } else if start == end {
+                    Some(Range::Exact(start))
+                } else {
+                    Some((start..=end).into())
+                }
+            }
+            (Some(start), None) => Some((start..).into()),
+            (None, Some(end)) => Some((..=end).into()),
+            // Every Range variant is bounded on at least one side, so an
+            // intersection can't be unbounded on both
+            (None, None) => unreachable!(),
+        }
+    }
 }
 
 impl<T: std::str::FromStr> From<std::ops::RangeToInclusive<T>> for Range<T> {
@@ -358,20 +504,76 @@ impl<T: std::str::FromStr> Range<T> {
     }
 }
 
-#[derive(Debug, Error, PartialEq)]
+/// A bound failed a checked numeric conversion in [`Range::to_u32`] (or its
+/// sibling conversions): the value doesn't fit in the target type
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
+#[error("range bound {value} does not fit in {target}")]
+pub struct RangeConvertError {
+    /// The offending bound, rendered for display
+    pub value: String,
+    /// The name of the conversion's target type
+    pub target: &'static str,
+}
+
+impl<T> Range<T>
+where
+    T: std::str::FromStr + Copy + std::fmt::Display,
+{
+    /// Checked conversion of each bound into `U`, reporting the first bound
+    /// that doesn't fit
+    fn convert_bounds<U>(&self, target: &'static str) -> Result<Range<U>, RangeConvertError>
+    where
+        U: std::str::FromStr + TryFrom<T>,
+    {
+        self.try_map(|&n| {
+            U::try_from(n).map_err(|_| RangeConvertError {
+                value: n.to_string(),
+                target,
+            })
+        })
+    }
+
+    /// Checked conversion into a `Range<u32>` (e.g., for comparing a size
+    /// range against engine feature levels), with overflow detection
+    pub fn to_u32(&self) -> Result<Range<u32>, RangeConvertError>
+    where
+        u32: TryFrom<T>,
+    {
+        self.convert_bounds("u32")
+    }
+
+    /// Checked conversion into a `Range<u64>`, with overflow detection
+    pub fn to_u64(&self) -> Result<Range<u64>, RangeConvertError>
+    where
+        u64: TryFrom<T>,
+    {
+        self.convert_bounds("u64")
+    }
+
+    /// Checked conversion into a `Range<usize>` (e.g., for comparing against
+    /// file and container sizes), with overflow detection
+    pub fn to_usize(&self) -> Result<Range<usize>, RangeConvertError>
+    where
+        usize: TryFrom<T>,
+    {
+        self.convert_bounds("usize")
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq, Hash)]
 pub enum RangeParseError<T>
 where
     T: std::str::FromStr,
     <T as std::str::FromStr>::Err: std::fmt::Debug,
 {
     #[error("parsing size range start: {0}")]
-    Start(ParseNumberError<T>),
+    Start(#[source] ParseNumberError<T>),
 
     #[error("parsing size range end: {0}")]
-    End(ParseNumberError<T>),
+    End(#[source] ParseNumberError<T>),
 
     #[error("parsing exact size: {0}")]
-    Exact(ParseNumberError<T>),
+    Exact(#[source] ParseNumberError<T>),
 }
 
 impl<T: std::str::FromStr + std::fmt::Display> AppendSigBytes for Range<T> {
@@ -431,7 +633,7 @@ pub fn string_from_bytes(bytes: &[u8]) -> Result<String, std::str::Utf8Error> {
 
 /// A relative or absolute location within a string. This is primarily used for
 /// error reporting.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub enum Position {
     End,
     Absolute(usize),
@@ -439,6 +641,30 @@ pub enum Position {
     Range(RangeInclusive<usize>),
 }
 
+impl Position {
+    /// A validated span covering `start..=end`, for pointing at a whole
+    /// sub-expression rather than a single character
+    ///
+    /// # Panics
+    /// Panics if `start` exceeds `end`
+    #[must_use]
+    pub fn span(start: usize, end: usize) -> Self {
+        assert!(start <= end, "span start ({start}) exceeds end ({end})");
+        Position::Range(start..=end)
+    }
+
+    /// The position `pos`, expressed relative to `base` (e.g., within a
+    /// sub-slice beginning at `base`)
+    ///
+    /// # Panics
+    /// Panics if `base` exceeds `pos`
+    #[must_use]
+    pub fn relative_to(base: usize, pos: usize) -> Self {
+        assert!(base <= pos, "base ({base}) exceeds position ({pos})");
+        Position::Relative(pos - base)
+    }
+}
+
 impl std::fmt::Display for Position {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -470,6 +696,127 @@ impl From<Option<usize>> for Position {
 mod tests {
     use super::*;
 
+    #[test]
+    fn byte_to_printable_forms() {
+        assert_eq!(byte_to_printable(b'x'), "'x'");
+        assert_eq!(byte_to_printable(b' '), "' '");
+        assert_eq!(byte_to_printable(b'\n'), r"'\n'");
+        assert_eq!(byte_to_printable(b'\t'), r"'\t'");
+        assert_eq!(byte_to_printable(0x00), r"'\0'");
+        assert_eq!(byte_to_printable(0x1b), r"'\x1b'");
+        assert_eq!(byte_to_printable(0xa2), r"'\xa2'");
+        assert_eq!(byte_to_printable(b'\''), r"'\''");
+    }
+
+    #[test]
+    fn position_constructors() {
+        assert_eq!(Position::span(5, 14), Position::Range(5..=14));
+        assert_eq!(Position::span(5, 14).to_string(), "from pos 5 to 14");
+        assert_eq!(Position::span(3, 3), Position::Range(3..=3));
+        assert_eq!(Position::relative_to(10, 14), Position::Relative(4));
+        assert_eq!(
+            Position::relative_to(10, 14).to_string(),
+            "at relative pos 4"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "span start (5) exceeds end (4)")]
+    fn position_span_inverted() {
+        let _ = Position::span(5, 4);
+    }
+
+    #[test]
+    fn parse_hash_expecting_algorithm() {
+        let md5 = b"44d88612fea8a8f36de82e1278abb02f";
+        assert!(matches!(
+            parse_hash_expecting(md5, MD5_LEN),
+            Ok(Hash::Md5(_))
+        ));
+
+        // A valid hash of a different supported algorithm is identified as such
+        let err = parse_hash_expecting(md5, SHA2_256_LEN).unwrap_err();
+        assert_eq!(
+            err,
+            ParseHashError::WrongAlgorithm {
+                expected_len: SHA2_256_LEN,
+                found_len: MD5_LEN,
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "wrong hash algorithm: expected SHA2-256 (32-byte digest), found 16-byte digest"
+        );
+
+        // An unrecognized length is still an unsupported length, not a
+        // wrong algorithm
+        assert_eq!(
+            parse_hash_expecting(b"aabb", SHA2_256_LEN),
+            Err(ParseHashError::UnsupportedHashLength(2))
+        );
+    }
+
+    #[test]
+    fn clamp_to_intersects_ranges() {
+        let a: Range<u32> = (10..=20).into();
+        let b: Range<u32> = (15..=30).into();
+        assert_eq!(a.clamp_to(&b), Some((15..=20).into()));
+
+        // Disjoint ranges have no intersection
+        let c: Range<u32> = (25..=30).into();
+        assert_eq!(a.clamp_to(&c), None);
+
+        // A single-point intersection collapses to Exact
+        let d: Range<u32> = (20..=30).into();
+        assert_eq!(a.clamp_to(&d), Some(Range::Exact(20)));
+
+        // Exact ranges behave as single-point intervals
+        assert_eq!(Range::Exact(15).clamp_to(&a), Some(Range::Exact(15)));
+        assert_eq!(Range::Exact(5).clamp_to(&a), None);
+
+        // Half-open ranges clamp on the bounded side only
+        let from: Range<u32> = (15..).into();
+        assert_eq!(a.clamp_to(&from), Some((15..=20).into()));
+        let to: Range<u32> = (..=15).into();
+        assert_eq!(a.clamp_to(&to), Some((10..=15).into()));
+        assert_eq!(from.clamp_to(&to), Some(Range::Exact(15)));
+    }
+
+    #[test]
+    fn range_try_map_preserves_form() {
+        let r: Range<usize> = (10..=20).into();
+        assert_eq!(r.try_map(|&n| u32::try_from(n)).unwrap(), (10..=20).into());
+        let r: Range<usize> = (10..).into();
+        assert_eq!(r.try_map(|&n| u32::try_from(n)).unwrap(), (10..).into());
+        let r: Range<usize> = (..=20).into();
+        assert_eq!(r.try_map(|&n| u32::try_from(n)).unwrap(), (..=20).into());
+        let r: Range<usize> = Range::Exact(7);
+        assert_eq!(r.try_map(|&n| u32::try_from(n)).unwrap(), Range::Exact(7));
+    }
+
+    #[test]
+    fn range_checked_conversions() {
+        let sizes: Range<usize> = (1024..=4096).into();
+        assert_eq!(sizes.to_u32(), Ok((1024..=4096).into()));
+        assert_eq!(sizes.to_u64(), Ok((1024..=4096).into()));
+
+        let flevels: Range<u32> = (51..=255).into();
+        assert_eq!(flevels.to_usize(), Ok((51..=255).into()));
+
+        // A bound that overflows the narrower type is reported, naming the
+        // bound and the target
+        let too_big: Range<usize> = ((1 << 40)..).into();
+        let err = too_big.to_u32().unwrap_err();
+        assert_eq!(err.value, (1usize << 40).to_string());
+        assert_eq!(err.target, "u32");
+        assert_eq!(
+            err.to_string(),
+            format!("range bound {} does not fit in u32", 1u64 << 40)
+        );
+        // ...while the same range fits in the wider types
+        assert!(too_big.to_u64().is_ok());
+    }
+
     #[test]
     fn split_on_escaped_delimiter() {
         let bytes = r"abc:def\:ghi:hij\:\::klm".as_bytes();